@@ -0,0 +1,14 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/liebert_mpx.proto");
+        let fds = protox::compile(["proto/liebert_mpx.proto"], ["proto"]).unwrap();
+        tonic_prost_build::configure().build_client(false).compile_fds(fds).unwrap();
+    }
+}
@@ -0,0 +1,11 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    liebert_mpx::fuzzing::parse_all(data);
+});
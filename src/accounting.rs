@@ -0,0 +1,329 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Energy accounting periods
+//!
+//! Turns the accumulated energy counters of the receptacles into per
+//! period consumption (e.g. monthly for billing). Feed snapshots into
+//! [`Accounting::observe`] regularly; once a period boundary has been
+//! passed the finished [`Period`] is returned. Counters reset on the
+//! device in between are handled, and the counters can optionally be
+//! reset at every period boundary.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::accounting::{Accounting, Schedule};
+//! use liebert::metadata::MetadataStore;
+//!
+//! fn poll(accounting: &mut Accounting, snapshot: &liebert::Snapshot) {
+//!     if let Some(period) = accounting.observe(snapshot, std::time::SystemTime::now()) {
+//!         println!("{}", period.report(&MetadataStore::new()).to_markdown());
+//!     }
+//! }
+//! ```
+
+#[cfg(feature = "client")]
+use crate::{MPX, MPXError};
+use crate::Snapshot;
+use crate::l10n::tr;
+use crate::metadata::{Address, MetadataStore};
+use crate::report::{Report, Section};
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+const DAY: u64 = 86400;
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Proleptic Gregorian date (year, month, day) for days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn epoch_days(time: SystemTime) -> i64 {
+    (time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) / DAY) as i64
+}
+
+fn from_days(days: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(days.max(0) as u64 * DAY)
+}
+
+/// Format a time as UTC date (YYYY-MM-DD)
+fn format_date(time: SystemTime) -> String {
+    let (year, month, day) = civil_from_days(epoch_days(time));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Length of accounting periods, boundaries are at midnight UTC
+pub enum Schedule {
+    Daily,
+    /// Periods start on Monday
+    Weekly,
+    /// Periods start on the first day of the month
+    Monthly,
+}
+
+impl Schedule {
+    /// First period boundary after `time`
+    pub fn next_boundary(&self, time: SystemTime) -> SystemTime {
+        let days = epoch_days(time);
+        match self {
+            Schedule::Daily => from_days(days + 1),
+            Schedule::Weekly => {
+                // 1970-01-01 has been a Thursday
+                let weekday = (days + 3).rem_euclid(7);
+                from_days(days + 7 - weekday)
+            },
+            Schedule::Monthly => {
+                let (year, month, _) = civil_from_days(days);
+                let (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+                from_days(days_from_civil(year, month, 1))
+            },
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// Energy consumption of a finished accounting period
+pub struct Period {
+    pub start: SystemTime,
+    pub end: SystemTime,
+    /// Consumption per receptacle in kWh
+    pub consumption: BTreeMap<Address, f32>,
+}
+
+impl Period {
+    /// Total consumption of all receptacles in kWh
+    pub fn total(&self) -> f32 {
+        self.consumption.values().sum()
+    }
+
+    /// Consumption grouped by the hosts from the metadata store in kWh;
+    /// receptacles without host are not included
+    pub fn by_host(&self, metadata: &MetadataStore) -> BTreeMap<String, f32> {
+        let mut result = BTreeMap::new();
+        for (address, energy) in self.consumption.iter() {
+            if let Some(meta) = metadata.get(*address).filter(|m| !m.host.is_empty()) {
+                *result.entry(meta.host.clone()).or_insert(0.0) += energy;
+            }
+        }
+        result
+    }
+
+    /// Build a billing report, including per host totals if the
+    /// metadata store knows any hosts
+    pub fn report(&self, metadata: &MetadataStore) -> Report {
+        let rows = self.consumption.iter()
+            .map(|((pdu, branch, receptacle), energy)| vec![
+                format!("{}-{}-{}", pdu, branch, receptacle),
+                metadata.get((*pdu, *branch, *receptacle)).map(|m| m.host.clone()).unwrap_or_default(),
+                format!("{:.2}", energy),
+            ])
+            .collect();
+
+        let mut sections = vec![
+            Section::Fields {
+                title: tr("Period"),
+                fields: vec![
+                    (tr("Start"), format_date(self.start)),
+                    (tr("End"), format_date(self.end)),
+                    (tr("Total energy"), format!("{:.2} kWh", self.total())),
+                ],
+            },
+            Section::Table {
+                title: tr("Receptacles"),
+                header: vec![tr("Receptacle"), tr("Host"), tr("Energy (kWh)")],
+                rows,
+            },
+        ];
+
+        let hosts = self.by_host(metadata);
+        if !hosts.is_empty() {
+            sections.push(Section::Table {
+                title: tr("Hosts"),
+                header: vec![tr("Host"), tr("Energy (kWh)")],
+                rows: hosts.into_iter().map(|(h, e)| vec![h, format!("{:.2}", e)]).collect(),
+            });
+        }
+
+        Report {
+            title: format!("{} {} - {}", tr("Energy consumption"), format_date(self.start), format_date(self.end)),
+            sections,
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// Energy accounting state
+pub struct Accounting {
+    pub schedule: Schedule,
+    /// Reset the receptacle energy counters at period boundaries,
+    /// see [`Accounting::observe_and_reset`]
+    pub reset_energy: bool,
+    start: Option<SystemTime>,
+    end: Option<SystemTime>,
+    last: BTreeMap<Address, f32>,
+    consumed: BTreeMap<Address, f32>,
+    periods: Vec<Period>,
+}
+
+impl Accounting {
+    pub fn new(schedule: Schedule) -> Self {
+        Accounting {
+            schedule,
+            reset_energy: false,
+            start: None,
+            end: None,
+            last: BTreeMap::new(),
+            consumed: BTreeMap::new(),
+            periods: Vec::new(),
+        }
+    }
+
+    /// Account a snapshot taken at `now`, returning the finished period
+    /// if a period boundary has been passed
+    ///
+    /// The first snapshot starts the first (partial) period. Energy
+    /// consumed between the last snapshot before and the first snapshot
+    /// after a boundary is accounted to the finished period.
+    pub fn observe(&mut self, snapshot: &Snapshot, now: SystemTime) -> Option<Period> {
+        for r in snapshot.receptacles.iter() {
+            let address = (snapshot.pdu, r.branch, r.receptacle);
+            // receptacles of modules without receptacle measurement
+            let energy = match r.info.status.accumulated_energy {
+                Some(energy) => energy,
+                None => continue,
+            };
+            if let Some(last) = self.last.insert(address, energy) {
+                // counter has been reset in between
+                let delta = if energy >= last { energy - last } else { energy };
+                *self.consumed.entry(address).or_insert(0.0) += delta;
+            } else {
+                self.consumed.entry(address).or_insert(0.0);
+            }
+        }
+
+        let (start, end) = match (self.start, self.end) {
+            (Some(start), Some(end)) => (start, end),
+            _ => {
+                self.start = Some(now);
+                self.end = Some(self.schedule.next_boundary(now));
+                return None;
+            },
+        };
+
+        if now < end {
+            return None;
+        }
+
+        let period = Period {
+            start,
+            end,
+            consumption: std::mem::take(&mut self.consumed),
+        };
+        self.start = Some(end);
+        self.end = Some(self.schedule.next_boundary(now));
+        self.periods.push(period.clone());
+        Some(period)
+    }
+
+    /// Like [`Accounting::observe`], but also resets the energy counters
+    /// of all accounted receptacles when a period finished and
+    /// `reset_energy` is set
+    #[cfg(feature = "client")]
+    pub async fn observe_and_reset(&mut self, mpx: &MPX, snapshot: &Snapshot, now: SystemTime) -> Result<Option<Period>, MPXError> {
+        let period = self.observe(snapshot, now);
+
+        if period.is_some() && self.reset_energy {
+            for ((pdu, branch, receptacle), last) in self.last.iter_mut() {
+                mpx.receptacle_reset_energy((*pdu, *branch, *receptacle)).await?;
+                *last = 0.0;
+            }
+        }
+
+        Ok(period)
+    }
+
+    /// All finished periods
+    pub fn periods(&self) -> &[Period] {
+        &self.periods
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::ReceptacleMetadata;
+    use crate::snapshot::tests::snapshot;
+
+    fn date(year: i64, month: u32, day: u32) -> SystemTime {
+        from_days(days_from_civil(year, month, day))
+    }
+
+    #[test]
+    fn test_schedule() {
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+        let time = date(2021, 12, 15) + Duration::from_secs(3600);
+        assert_eq!(Schedule::Daily.next_boundary(time), date(2021, 12, 16));
+        assert_eq!(Schedule::Weekly.next_boundary(time), date(2021, 12, 20));
+        assert_eq!(Schedule::Monthly.next_boundary(time), date(2022, 1, 1));
+        assert_eq!(Schedule::Monthly.next_boundary(date(2022, 1, 1)), date(2022, 2, 1));
+    }
+
+    #[test]
+    fn test_accounting() {
+        let mut snapshot = snapshot();
+        let mut accounting = Accounting::new(Schedule::Monthly);
+
+        snapshot.receptacles[0].info.status.accumulated_energy = Some(100.0);
+        snapshot.receptacles[1].info.status.accumulated_energy = Some(50.0);
+        assert!(accounting.observe(&snapshot, date(2021, 11, 20)).is_none());
+
+        // second counter reset on the device
+        snapshot.receptacles[0].info.status.accumulated_energy = Some(110.0);
+        snapshot.receptacles[1].info.status.accumulated_energy = Some(2.0);
+        assert!(accounting.observe(&snapshot, date(2021, 11, 25)).is_none());
+
+        snapshot.receptacles[0].info.status.accumulated_energy = Some(115.0);
+        snapshot.receptacles[1].info.status.accumulated_energy = Some(3.0);
+        let period = accounting.observe(&snapshot, date(2021, 12, 1)).unwrap();
+        assert_eq!(period.consumption[&(1, 1, 1)], 15.0);
+        assert_eq!(period.consumption[&(1, 1, 2)], 3.0);
+        assert_eq!(period.end, date(2021, 12, 1));
+
+        let mut metadata = MetadataStore::new();
+        metadata.set((1, 1, 1), ReceptacleMetadata { host: "db-01".to_string(), ..Default::default() });
+        metadata.set((1, 1, 2), ReceptacleMetadata { host: "db-01".to_string(), ..Default::default() });
+        assert_eq!(period.by_host(&metadata)["db-01"], 18.0);
+        assert_eq!(period.report(&metadata).title, "Energy consumption 2021-11-20 - 2021-12-01");
+
+        snapshot.receptacles[0].info.status.accumulated_energy = Some(125.0);
+        let period = accounting.observe(&snapshot, date(2022, 1, 1)).unwrap();
+        assert_eq!(period.start, date(2021, 12, 1));
+        assert_eq!(period.total(), 10.0);
+        assert_eq!(accounting.periods().len(), 2);
+    }
+}
@@ -0,0 +1,321 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! PDU, branch and receptacle addresses
+//!
+//! Information requests and commands take a [`ReceptacleAddress`] or
+//! [`BranchAddress`], which can be built from a tuple of numbers, from
+//! the [`PduId`], [`BranchId`] and [`ReceptacleId`] newtypes or parsed
+//! from strings like `1-2-3` and `1.2.3`:
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::address::ReceptacleAddress;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     let address: ReceptacleAddress = "1-2-3".parse().unwrap();
+//!     async {
+//!         pdu.receptacle_reboot(address).await.unwrap();
+//!         pdu.receptacle_reboot((1, 2, 3)).await.unwrap();
+//!     };
+//! }
+//! ```
+//!
+//! The card answers requests for non-existing modules with confusing
+//! error pages, and a number of 0 results in a URL addressing the parent
+//! module. Information requests, commands and settings writes therefore
+//! check their address first and fail with
+//! [`MPXError::InvalidAddress`](crate::MPXError::InvalidAddress) for
+//! numbers of 0. Once the receptacle list has been fetched, addresses
+//! are also checked against the discovered topology.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::ErrorKind;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         pdu.get_receptacles().await.unwrap();
+//!         let e = pdu.receptacle_reboot((1, 9, 1)).await.unwrap_err();
+//!         assert_eq!(e.kind(), ErrorKind::InvalidAddress);
+//!     };
+//! }
+//! ```
+
+use crate::{InvalidAddressError, MPXError};
+#[cfg(feature = "client")]
+use crate::{MPX, ReceptacleList};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "client")]
+use std::collections::HashSet;
+use std::str::FromStr;
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash,PartialOrd,Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// PDU number (usually 1)
+pub struct PduId(pub u8);
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash,PartialOrd,Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Branch number within a PDU (usually 1-6)
+pub struct BranchId(pub u8);
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash,PartialOrd,Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Receptacle number within a branch (usually 1-6)
+pub struct ReceptacleId(pub u8);
+
+macro_rules! impl_id {
+    ($id:ident) => {
+        impl From<u8> for $id {
+            fn from(number: u8) -> Self {
+                $id(number)
+            }
+        }
+
+        impl std::fmt::Display for $id {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+impl_id!(PduId);
+impl_id!(BranchId);
+impl_id!(ReceptacleId);
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash,PartialOrd,Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Address of a branch module
+pub struct BranchAddress {
+    pub pdu: PduId,
+    pub branch: BranchId,
+}
+
+impl BranchAddress {
+    pub fn new<P: Into<PduId>, B: Into<BranchId>>(pdu: P, branch: B) -> Self {
+        BranchAddress { pdu: pdu.into(), branch: branch.into() }
+    }
+}
+
+impl<P: Into<PduId>, B: Into<BranchId>> From<(P, B)> for BranchAddress {
+    fn from((pdu, branch): (P, B)) -> Self {
+        BranchAddress::new(pdu, branch)
+    }
+}
+
+impl std::fmt::Display for BranchAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}-{}", self.pdu, self.branch)
+    }
+}
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash,PartialOrd,Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Address of a receptacle
+pub struct ReceptacleAddress {
+    pub pdu: PduId,
+    pub branch: BranchId,
+    pub receptacle: ReceptacleId,
+}
+
+impl ReceptacleAddress {
+    pub fn new<P: Into<PduId>, B: Into<BranchId>, R: Into<ReceptacleId>>(pdu: P, branch: B, receptacle: R) -> Self {
+        ReceptacleAddress { pdu: pdu.into(), branch: branch.into(), receptacle: receptacle.into() }
+    }
+
+    /// Address of the branch module the receptacle belongs to
+    pub fn branch_address(&self) -> BranchAddress {
+        BranchAddress { pdu: self.pdu, branch: self.branch }
+    }
+}
+
+impl<P: Into<PduId>, B: Into<BranchId>, R: Into<ReceptacleId>> From<(P, B, R)> for ReceptacleAddress {
+    fn from((pdu, branch, receptacle): (P, B, R)) -> Self {
+        ReceptacleAddress::new(pdu, branch, receptacle)
+    }
+}
+
+impl std::fmt::Display for ReceptacleAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}-{}-{}", self.pdu, self.branch, self.receptacle)
+    }
+}
+
+/// Split a dashed (`1-2-3`) or dotted (`1.2.3`) address into `N` numbers
+fn parse_numbers<const N: usize>(input: &str) -> Result<[u8; N], MPXError> {
+    let invalid = || InvalidAddressError { address: input.to_string(), reason: format!("expected {} numbers separated by '-' or '.'", N) };
+    let separator = if input.contains('.') { '.' } else { '-' };
+    let parts: Vec<&str> = input.trim().split(separator).collect();
+    if parts.len() != N {
+        return Err(invalid().into());
+    }
+
+    let mut numbers = [0; N];
+    for (number, part) in numbers.iter_mut().zip(parts) {
+        *number = part.trim().parse().map_err(|_| invalid())?;
+    }
+    Ok(numbers)
+}
+
+impl FromStr for BranchAddress {
+    type Err = MPXError;
+
+    /// Parse a dashed (`1-2`) or dotted (`1.2`) branch address
+    fn from_str(input: &str) -> Result<BranchAddress, Self::Err> {
+        let [pdu, branch] = parse_numbers(input)?;
+        Ok(BranchAddress::new(pdu, branch))
+    }
+}
+
+impl FromStr for ReceptacleAddress {
+    type Err = MPXError;
+
+    /// Parse a dashed (`1-2-3`, as shown by the PDU) or dotted (`1.2.3`)
+    /// receptacle address
+    fn from_str(input: &str) -> Result<ReceptacleAddress, Self::Err> {
+        let [pdu, branch, receptacle] = parse_numbers(input)?;
+        Ok(ReceptacleAddress::new(pdu, branch, receptacle))
+    }
+}
+
+#[cfg(feature = "client")]
+fn format_address(pdu: u8, branch: Option<u8>, receptacle: Option<u8>) -> String {
+    match (branch, receptacle) {
+        (Some(branch), Some(receptacle)) => format!("{}-{}-{}", pdu, branch, receptacle),
+        (Some(branch), None) => format!("{}-{}", pdu, branch),
+        _ => format!("{}", pdu),
+    }
+}
+
+#[cfg(feature = "client")]
+fn check_nonzero(name: &str, value: u8) -> Result<(), String> {
+    match value {
+        0 => Err(format!("{} number must not be 0", name)),
+        _ => Ok(()),
+    }
+}
+
+/// Check that the numbers are not 0 and, if known, against the
+/// discovered receptacles and modules
+#[cfg(feature = "client")]
+fn check(pdu: u8, branch: Option<u8>, receptacle: Option<u8>, topology: Option<&HashSet<(u8, u8, u8)>>, modules: &[(u8, u8)]) -> Result<(), String> {
+    check_nonzero("PDU", pdu)?;
+    if let Some(branch) = branch {
+        check_nonzero("branch", branch)?;
+    }
+    if let Some(receptacle) = receptacle {
+        check_nonzero("receptacle", receptacle)?;
+    }
+
+    let topology = match topology {
+        Some(topology) => topology,
+        None => return Ok(()),
+    };
+
+    let found = match (branch, receptacle) {
+        (Some(b), Some(r)) => topology.contains(&(pdu, b, r)),
+        (Some(b), None) => modules.contains(&(pdu, b)) || topology.iter().any(|(p, br, _)| *p == pdu && *br == b),
+        _ => modules.iter().any(|(p, _)| *p == pdu) || topology.iter().any(|(p, _, _)| *p == pdu),
+    };
+    match found {
+        true => Ok(()),
+        false => Err("not present on the device".to_string()),
+    }
+}
+
+#[cfg(feature = "client")]
+impl<H> MPX<H> {
+    /// Remember the receptacles of the receptacle list as topology
+    pub(crate) fn remember_topology(&self, receptacles: &ReceptacleList) {
+        let topology = receptacles.iter().map(|r| (r.pdu, r.branch, r.receptacle)).collect();
+        *self.inner.topology.lock().unwrap() = Some(topology);
+    }
+
+    /// Fail with `InvalidAddress` for numbers of 0 or, once the
+    /// receptacle list is known, for modules not present on the device
+    pub(crate) fn check_address(&self, pdu: u8, branch: Option<u8>, receptacle: Option<u8>) -> Result<(), MPXError> {
+        let modules: Vec<(u8, u8)> = self.inner.modules.lock().unwrap().keys().copied().collect();
+        let topology = self.inner.topology.lock().unwrap();
+        check(pdu, branch, receptacle, topology.as_ref(), &modules).map_err(|reason| {
+            InvalidAddressError { address: format_address(pdu, branch, receptacle), reason }.into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        let address = ReceptacleAddress::from_str("1-2-3").unwrap();
+        assert_eq!(address, ReceptacleAddress::new(1, 2, 3));
+        assert_eq!(ReceptacleAddress::from_str("1.2.3").unwrap(), address);
+        assert_eq!(ReceptacleAddress::from((PduId(1), BranchId(2), ReceptacleId(3))), address);
+        assert_eq!(address.to_string(), "1-2-3");
+        assert_eq!(address.branch_address(), BranchAddress::from_str("1.2").unwrap());
+        assert!(ReceptacleAddress::from_str("1-2").is_err());
+        assert!(ReceptacleAddress::from_str("1-2-x").is_err());
+        assert_eq!(BranchAddress::from_str("1-2-3").unwrap_err().to_string(), "invalid address 1-2-3: expected 2 numbers separated by '-' or '.'");
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_ranges() {
+        assert!(check(1, Some(6), Some(12), None, &[]).is_ok());
+        assert!(check(8, Some(9), Some(48), None, &[]).is_ok());
+        assert_eq!(check(0, None, None, None, &[]), Err("PDU number must not be 0".to_string()));
+        assert_eq!(check(1, Some(0), None, None, &[]), Err("branch number must not be 0".to_string()));
+        assert_eq!(check(1, Some(1), Some(0), None, &[]), Err("receptacle number must not be 0".to_string()));
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_topology() {
+        let topology: HashSet<(u8, u8, u8)> = [(1, 1, 1), (1, 1, 2), (1, 2, 1)].iter().copied().collect();
+        assert!(check(1, Some(1), Some(2), Some(&topology), &[]).is_ok());
+        assert!(check(1, Some(1), Some(3), Some(&topology), &[]).is_err());
+        assert!(check(1, Some(2), None, Some(&topology), &[]).is_ok());
+        assert!(check(1, Some(3), None, Some(&topology), &[]).is_err());
+        // branches without receptacle management are known from their info page
+        assert!(check(1, Some(3), None, Some(&topology), &[(1, 3)]).is_ok());
+        assert!(check(2, None, None, Some(&topology), &[]).is_err());
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_invalid_address() {
+        use crate::{ErrorKind, EventLevel, ReceptacleListEntry};
+        use crate::transport::{HttpTransport, Request, Response};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Counter(AtomicUsize);
+
+        impl HttpTransport for Counter {
+            async fn send(&self, request: Request) -> Result<Response, MPXError> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(Response { status: 200, url: request.url, headers: Vec::new(), body: String::new() })
+            }
+        }
+
+        let mpx = MPX::builder("pdu1").build_with_transport(Counter(AtomicUsize::new(0)));
+        let e = mpx.receptacle_reboot((1, 0, 1)).await.unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidAddress);
+        assert_eq!(e.to_string(), "invalid address 1-0-1: branch number must not be 0");
+        assert!(mpx.get_info_receptacle((1, 1, 0)).await.is_err());
+        assert!(mpx.pdu_reset_energy(0).await.is_err());
+        assert_eq!(mpx.inner.transport.0.load(Ordering::SeqCst), 0);
+
+        let entry = ReceptacleListEntry { pdu: 1, branch: 1, receptacle: 1, enabled: true, locked: false, status: EventLevel::OK, label: String::new() };
+        mpx.remember_topology(&vec![entry]);
+        assert_eq!(mpx.branch_reset_energy((1, 2)).await.unwrap_err().to_string(), "invalid address 1-2: not present on the device");
+        mpx.receptacle_identify((1, 1, 1)).await.unwrap();
+        assert_eq!(mpx.inner.transport.0.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,168 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! External annotations for receptacles
+//!
+//! Attaches identifiers from other systems (CMDB ids, host names,
+//! owners, ...) to receptacle addresses. Annotations are loaded from a
+//! CSV file whose first column is the receptacle address and whose
+//! header row names the remaining columns:
+//!
+//! ```text
+//! address,host,cmdb_id,owner
+//! 1-1-1,db-01,CI0012345,"Database Team"
+//! 1-1-2,db-01,CI0012345,"Database Team"
+//! ```
+//!
+//! Annotated receptacles get additional columns in
+//! [`report::render_annotated`](crate::report::render_annotated) and
+//! events can be enriched with [`Annotations::enrich`].
+
+use crate::Event;
+use crate::metadata::{parse_address, Address};
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::path::Path;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+fn invalid(line: usize, msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("line {}: {}", line, msg))
+}
+
+/// Split a CSV line, supporting double quoted fields with `""` escapes
+fn split_csv(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            },
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field).trim().to_string()),
+            (c, _) => field.push(c),
+        }
+    }
+
+    if quoted {
+        return None;
+    }
+    fields.push(field.trim().to_string());
+    Some(fields)
+}
+
+/// Annotation fields of a receptacle (column name to value)
+pub type Fields = BTreeMap<String, String>;
+
+#[derive(Clone,Debug,Default,PartialEq)]
+/// Annotations of all receptacles
+pub struct Annotations {
+    columns: Vec<String>,
+    entries: BTreeMap<Address, Fields>,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Event together with the annotations of the affected receptacle
+pub struct AnnotatedEvent {
+    pub event: Event,
+    /// Empty for events without annotated receptacle
+    pub annotations: Fields,
+}
+
+impl Annotations {
+    /// Load annotations from a CSV file
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Self::from_csv(std::io::BufReader::new(std::fs::File::open(path)?))
+    }
+
+    /// Parse annotations in CSV format
+    pub fn from_csv<R: BufRead>(input: R) -> std::io::Result<Self> {
+        let mut result = Self::default();
+
+        for (no, line) in input.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = split_csv(&line).ok_or_else(|| invalid(no + 1, "unterminated quote"))?;
+
+            if result.columns.is_empty() {
+                result.columns = fields.into_iter().skip(1).collect();
+                continue;
+            }
+
+            if fields.len() != result.columns.len() + 1 {
+                return Err(invalid(no + 1, "wrong number of columns"));
+            }
+            let address = parse_address(&fields[0]).ok_or_else(|| invalid(no + 1, "invalid address"))?;
+            let values = result.columns.iter().cloned()
+                .zip(fields.into_iter().skip(1))
+                .filter(|(_, v)| !v.is_empty())
+                .collect();
+            result.entries.insert(address, values);
+        }
+
+        Ok(result)
+    }
+
+    /// Column names in file order (without the address column)
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// All annotations of a receptacle
+    pub fn get(&self, address: Address) -> Option<&Fields> {
+        self.entries.get(&address)
+    }
+
+    /// Single annotation of a receptacle
+    pub fn field(&self, address: Address, column: &str) -> Option<&str> {
+        self.get(address)?.get(column).map(|s| s.as_str())
+    }
+
+    /// Annotation values of a receptacle in column order (empty for missing values)
+    pub fn row(&self, address: Address) -> Vec<String> {
+        self.columns.iter()
+            .map(|c| self.field(address, c).unwrap_or("").to_string())
+            .collect()
+    }
+
+    /// Attach the receptacle annotations to events
+    pub fn enrich(&self, events: &[Event]) -> Vec<AnnotatedEvent> {
+        events.iter().map(|e| AnnotatedEvent {
+            event: e.clone(),
+            annotations: self.get((e.pdu, e.branch, e.receptacle)).cloned().unwrap_or_default(),
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventLevel, EventType};
+
+    const CSV: &str = "address,host,cmdb_id,owner\n1-1-1,db-01,CI0012345,\"Database, Team\"\n1-1-2,db-01,,\"say \"\"hi\"\"\"\n";
+
+    #[test]
+    fn test_annotations() {
+        let annotations = Annotations::from_csv(CSV.as_bytes()).unwrap();
+        assert_eq!(annotations.columns(), &["host", "cmdb_id", "owner"]);
+        assert_eq!(annotations.field((1, 1, 1), "owner"), Some("Database, Team"));
+        assert_eq!(annotations.field((1, 1, 2), "owner"), Some("say \"hi\""));
+        assert_eq!(annotations.field((1, 1, 2), "cmdb_id"), None);
+        assert_eq!(annotations.row((1, 1, 2)), vec!["db-01", "", "say \"hi\""]);
+
+        let event = Event { level: EventLevel::ALARM, pdu: 1, branch: 1, receptacle: 1, event: EventType::ReceptacleOverCurrent };
+        let enriched = annotations.enrich(&[event]);
+        assert_eq!(enriched[0].annotations.get("host").map(|s| s.as_str()), Some("db-01"));
+
+        assert!(Annotations::from_csv("address,host\n1-1-1,a,b\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,375 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Anomaly detection on receptacle currents
+//!
+//! The device only raises events when configured thresholds are
+//! crossed, so a server losing a power supply (load dropping to 0 A)
+//! usually goes unnoticed. [`StepDetector`] compares consecutive
+//! snapshots and reports sudden changes of receptacle current.
+//! [`CrestFactorMonitor`] tracks the current crest factor, which
+//! drifts away from its usual value when power supplies degrade.
+//! [`power_quality`] checks a single snapshot for low power factors and
+//! unusual crest factors.
+//!
+//! ```
+//! extern crate liebert_mpx as liebert;
+//! use liebert::anomaly::StepDetector;
+//!
+//! fn check(detector: &mut StepDetector, snapshot: &liebert::Snapshot) {
+//!     for anomaly in detector.observe(snapshot) {
+//!         println!("{}", anomaly);
+//!     }
+//! }
+//! ```
+
+use crate::Snapshot;
+use crate::snapshot::ReceptacleSnapshot;
+use crate::metadata::Address;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+fn label(snapshot: &Snapshot, r: &ReceptacleSnapshot) -> String {
+    match r.info.settings.label.is_empty() {
+        true => format!("{}-{}-{}", snapshot.pdu, r.branch, r.receptacle),
+        false => r.info.settings.label.clone(),
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Sudden change of a receptacle current
+pub struct Anomaly {
+    pub address: Address,
+    /// Receptacle label (or address if unlabeled)
+    pub label: String,
+    /// Previous current in A AC
+    pub old: f32,
+    /// Current in A AC
+    pub new: f32,
+}
+
+impl std::fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let direction = if self.new < self.old { "dropped" } else { "rose" };
+        write!(f, "load {} to {} A on '{}' (was {} A)", direction, self.new, self.label, self.old)
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// Detects step changes of receptacle currents between snapshots
+pub struct StepDetector {
+    /// Relative change (0-1) of the previous current considered a step
+    pub sensitivity: f32,
+    /// Absolute change in A AC below which changes are ignored as noise
+    pub min_delta: f32,
+    last: BTreeMap<Address, f32>,
+}
+
+impl Default for StepDetector {
+    fn default() -> Self {
+        Self::new(0.5, 0.2)
+    }
+}
+
+impl StepDetector {
+    pub fn new(sensitivity: f32, min_delta: f32) -> Self {
+        StepDetector {
+            sensitivity,
+            min_delta,
+            last: BTreeMap::new(),
+        }
+    }
+
+    /// Feed a new snapshot, returning the step changes since the previous one
+    ///
+    /// The first snapshot of a receptacle only establishes its baseline.
+    pub fn observe(&mut self, snapshot: &Snapshot) -> Vec<Anomaly> {
+        let mut result = Vec::new();
+
+        for r in snapshot.receptacles.iter() {
+            let address = (snapshot.pdu, r.branch, r.receptacle);
+            let new = match r.info.status.current {
+                Some(current) => current,
+                None => continue,
+            };
+
+            if let Some(old) = self.last.insert(address, new) {
+                let delta = (new - old).abs();
+                if delta >= self.min_delta && delta >= old.max(new) * self.sensitivity {
+                    result.push(Anomaly { address, label: label(snapshot, r), old, new });
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Forget all baselines, e.g. after planned maintenance
+    pub fn reset(&mut self) {
+        self.last.clear();
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Receptacle crest factor left the configured band
+pub struct CrestFactorAlert {
+    pub address: Address,
+    /// Receptacle label (or address if unlabeled)
+    pub label: String,
+    /// Average crest factor over the monitoring window
+    pub crest_factor: f32,
+}
+
+impl std::fmt::Display for CrestFactorAlert {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "current crest factor {:.2} on '{}' outside of normal range", self.crest_factor, self.label)
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// Tracks receptacle current crest factors between snapshots
+pub struct CrestFactorMonitor {
+    /// Lowest normal crest factor
+    pub min: f32,
+    /// Highest normal crest factor
+    pub max: f32,
+    /// Number of samples averaged before comparing with the band
+    pub window: usize,
+    /// Receptacles drawing less current (A AC) are not monitored,
+    /// since the crest factor of tiny loads is meaningless
+    pub min_current: f32,
+    history: BTreeMap<Address, VecDeque<f32>>,
+    active: BTreeSet<Address>,
+}
+
+impl Default for CrestFactorMonitor {
+    fn default() -> Self {
+        Self::new(1.2, 3.0)
+    }
+}
+
+impl CrestFactorMonitor {
+    pub fn new(min: f32, max: f32) -> Self {
+        CrestFactorMonitor {
+            min,
+            max,
+            window: 5,
+            min_current: 0.5,
+            history: BTreeMap::new(),
+            active: BTreeSet::new(),
+        }
+    }
+
+    /// Feed a new snapshot, returning receptacles whose averaged crest
+    /// factor just left the band
+    ///
+    /// Alerts are only raised once per excursion; a receptacle has to
+    /// return into the band before it is reported again.
+    pub fn observe(&mut self, snapshot: &Snapshot) -> Vec<CrestFactorAlert> {
+        let mut result = Vec::new();
+
+        for r in snapshot.receptacles.iter() {
+            let address = (snapshot.pdu, r.branch, r.receptacle);
+            let crest_factor = match (r.info.status.current, r.info.status.current_crest_factor) {
+                (Some(current), Some(crest_factor)) if current >= self.min_current => crest_factor,
+                _ => continue,
+            };
+
+            let samples = self.history.entry(address).or_default();
+            samples.push_back(crest_factor);
+            while samples.len() > self.window {
+                samples.pop_front();
+            }
+            if samples.len() < self.window {
+                continue;
+            }
+
+            let crest_factor = samples.iter().sum::<f32>() / samples.len() as f32;
+            if crest_factor >= self.min && crest_factor <= self.max {
+                self.active.remove(&address);
+            } else if self.active.insert(address) {
+                result.push(CrestFactorAlert { address, label: label(snapshot, r), crest_factor });
+            }
+        }
+
+        result
+    }
+
+    /// Receptacles currently outside of the band
+    pub fn alerting(&self) -> impl Iterator<Item = &Address> {
+        self.active.iter()
+    }
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Limits for [`power_quality`]
+pub struct PowerQualityLimits {
+    /// Power factors below this value are flagged
+    pub min_power_factor: f32,
+    /// Lowest normal current crest factor
+    pub min_crest_factor: f32,
+    /// Highest normal current crest factor
+    pub max_crest_factor: f32,
+    /// Loads drawing less current (A AC) are not checked, since power
+    /// and crest factor of tiny loads are meaningless
+    pub min_current: f32,
+}
+
+impl Default for PowerQualityLimits {
+    fn default() -> Self {
+        PowerQualityLimits {
+            min_power_factor: 0.9,
+            min_crest_factor: 1.2,
+            max_crest_factor: 3.0,
+            min_current: 0.5,
+        }
+    }
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Kind of a power quality issue
+pub enum PowerQualityKind {
+    /// Power factor below the limit
+    LowPowerFactor(f32),
+    /// Current crest factor outside of the normal range
+    CrestFactor(f32),
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Suspicious power quality reading of a branch module or receptacle
+pub struct PowerQualityIssue {
+    /// Receptacle address, receptacle 0 for branch modules
+    pub address: Address,
+    /// Label (or address if unlabeled)
+    pub label: String,
+    pub kind: PowerQualityKind,
+}
+
+impl std::fmt::Display for PowerQualityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.kind {
+            PowerQualityKind::LowPowerFactor(value) => write!(f, "low power factor {:.2} on '{}'", value, self.label),
+            PowerQualityKind::CrestFactor(value) => write!(f, "current crest factor {:.2} on '{}' outside of normal range", value, self.label),
+        }
+    }
+}
+
+/// Flag low power factors of branch modules and receptacles and unusual
+/// current crest factors of receptacles
+pub fn power_quality(snapshot: &Snapshot, limits: &PowerQualityLimits) -> Vec<PowerQualityIssue> {
+    let mut result = Vec::new();
+
+    for b in snapshot.branches.iter() {
+        let status = &b.info.status;
+        if status.current >= limits.min_current && status.power_factor < limits.min_power_factor {
+            let label = match b.info.settings.label.is_empty() {
+                true => format!("{}-{}", snapshot.pdu, b.branch),
+                false => b.info.settings.label.clone(),
+            };
+            let kind = PowerQualityKind::LowPowerFactor(status.power_factor);
+            result.push(PowerQualityIssue { address: (snapshot.pdu, b.branch, 0), label, kind });
+        }
+    }
+
+    for r in snapshot.receptacles.iter() {
+        let status = &r.info.status;
+        match status.current {
+            Some(current) if current >= limits.min_current => {},
+            _ => continue,
+        }
+        let address = (snapshot.pdu, r.branch, r.receptacle);
+        if let Some(power_factor) = status.power_factor.filter(|pf| *pf < limits.min_power_factor) {
+            result.push(PowerQualityIssue { address, label: label(snapshot, r), kind: PowerQualityKind::LowPowerFactor(power_factor) });
+        }
+        if let Some(crest_factor) = status.current_crest_factor.filter(|cf| *cf < limits.min_crest_factor || *cf > limits.max_crest_factor) {
+            result.push(PowerQualityIssue { address, label: label(snapshot, r), kind: PowerQualityKind::CrestFactor(crest_factor) });
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::tests::snapshot;
+
+    #[test]
+    fn test_step_detector() {
+        let mut snapshot = snapshot();
+        snapshot.receptacles[0].info.status.current = Some(2.0);
+        snapshot.receptacles[1].info.status.current = Some(2.0);
+        snapshot.receptacles[1].info.settings.label = "db-01 PSU1".to_string();
+
+        let mut detector = StepDetector::default();
+        assert!(detector.observe(&snapshot).is_empty());
+
+        snapshot.receptacles[0].info.status.current = Some(2.1);
+        snapshot.receptacles[1].info.status.current = Some(0.0);
+        let anomalies = detector.observe(&snapshot);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].address, (1, 1, 2));
+        assert_eq!(anomalies[0].to_string(), "load dropped to 0 A on 'db-01 PSU1' (was 2 A)");
+
+        assert!(detector.observe(&snapshot).is_empty());
+    }
+
+    #[test]
+    fn test_crest_factor_monitor() {
+        let mut snapshot = snapshot();
+        snapshot.receptacles[0].info.status.current = Some(2.0);
+        snapshot.receptacles[1].info.status.current = Some(0.0);
+
+        let mut monitor = CrestFactorMonitor { window: 2, ..CrestFactorMonitor::default() };
+        snapshot.receptacles[0].info.status.current_crest_factor = Some(1.8);
+        assert!(monitor.observe(&snapshot).is_empty());
+        assert!(monitor.observe(&snapshot).is_empty());
+
+        snapshot.receptacles[0].info.status.current_crest_factor = Some(4.6);
+        let alerts = monitor.observe(&snapshot);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].address, (1, 1, 1));
+        assert!((alerts[0].crest_factor - 3.2).abs() < 0.01);
+        assert!(monitor.observe(&snapshot).is_empty());
+        assert_eq!(monitor.alerting().count(), 1);
+
+        snapshot.receptacles[0].info.status.current_crest_factor = Some(1.8);
+        monitor.observe(&snapshot);
+        monitor.observe(&snapshot);
+        assert_eq!(monitor.alerting().count(), 0);
+    }
+
+    #[test]
+    fn test_power_quality() {
+        let mut snapshot = snapshot();
+        let limits = PowerQualityLimits::default();
+        snapshot.branches[0].info.status.current = 3.0;
+        snapshot.branches[0].info.status.power_factor = 0.95;
+        for r in snapshot.receptacles.iter_mut() {
+            r.info.status.current = Some(1.5);
+            r.info.status.power_factor = Some(0.98);
+            r.info.status.current_crest_factor = Some(1.6);
+        }
+        assert!(power_quality(&snapshot, &limits).is_empty());
+
+        snapshot.branches[0].info.status.power_factor = 0.7;
+        snapshot.receptacles[1].info.status.power_factor = Some(0.6);
+        snapshot.receptacles[1].info.status.current_crest_factor = Some(3.4);
+        snapshot.receptacles[1].info.settings.label = "db-01 PSU1".to_string();
+        let issues = power_quality(&snapshot, &limits);
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0].address, (1, 1, 0));
+        assert_eq!(issues[1].kind, PowerQualityKind::LowPowerFactor(0.6));
+        assert_eq!(issues[2].to_string(), "current crest factor 3.40 on 'db-01 PSU1' outside of normal range");
+
+        snapshot.receptacles[1].info.status.current = Some(0.1);
+        assert_eq!(power_quality(&snapshot, &limits).len(), 1);
+    }
+}
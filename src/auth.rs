@@ -0,0 +1,265 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Authentication scheme negotiation
+//!
+//! Depending on the card firmware the web interface expects HTTP basic
+//! auth, HTTP digest auth or a form based session login. Requests are
+//! sent with basic auth first; if the device answers with a challenge
+//! for another scheme (or with its login page, if a
+//! [`SessionLogin`](crate::session::SessionLogin) is configured) the
+//! request is repeated with that scheme. The negotiated scheme is
+//! cached on the [`MPX`] handle, so the extra round trip only happens
+//! once.
+
+use crate::{MPX, MPXError};
+use crate::transport::{HttpTransport, Request, Response};
+use md5::{Digest, Md5};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+/// Authentication scheme of a device
+pub enum AuthScheme {
+    /// HTTP basic auth
+    Basic,
+    /// HTTP digest auth (MD5)
+    Digest,
+    /// Form based session login with cookies
+    Session,
+}
+
+#[derive(Clone,Debug,Default,PartialEq)]
+/// Digest auth challenge from a `WWW-Authenticate` header
+pub(crate) struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    qop: Option<String>,
+    algorithm: Option<String>,
+    stale: bool,
+    /// Requests sent with this nonce
+    count: u32,
+}
+
+#[derive(Clone,Debug,Default)]
+/// Negotiated authentication state of a [`MPX`]
+pub(crate) struct AuthState {
+    scheme: Option<AuthScheme>,
+    digest: Option<DigestChallenge>,
+}
+
+/// Parse `key=value` / `key="value"` pairs of a challenge
+fn parse_params(input: &str) -> Vec<(String, String)> {
+    let mut result = Vec::new();
+    let mut rest = input.trim();
+
+    while let Some(pos) = rest.find('=') {
+        let key = rest[..pos].trim().trim_start_matches(',').trim().to_ascii_lowercase();
+        rest = rest[pos + 1..].trim_start();
+
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let value = &quoted[..end];
+            rest = quoted.get(end + 1..).unwrap_or("");
+            value
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let value = rest[..end].trim();
+            rest = &rest[end..];
+            value
+        };
+
+        result.push((key, value.to_string()));
+        rest = rest.trim_start().trim_start_matches(',');
+    }
+
+    result
+}
+
+pub(crate) fn parse_challenge(header: &str) -> Option<DigestChallenge> {
+    let (scheme, params) = header.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("digest") {
+        return None;
+    }
+
+    let mut challenge = DigestChallenge::default();
+    for (key, value) in parse_params(params) {
+        match key.as_str() {
+            "realm" => challenge.realm = value,
+            "nonce" => challenge.nonce = value,
+            "opaque" => challenge.opaque = Some(value),
+            // prefer "auth" if the server offers several qop values
+            "qop" => challenge.qop = value.split(',').map(|q| q.trim()).find(|q| *q == "auth").map(String::from),
+            "algorithm" => challenge.algorithm = Some(value),
+            "stale" => challenge.stale = value.eq_ignore_ascii_case("true"),
+            _ => {},
+        }
+    }
+
+    if challenge.nonce.is_empty() {
+        return None;
+    }
+    Some(challenge)
+}
+
+pub(crate) fn base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut result = String::new();
+    for chunk in input.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                result.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+fn md5_hex(input: &str) -> String {
+    Md5::digest(input.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl DigestChallenge {
+    /// `Authorization` header value for a request
+    fn authorization(&mut self, username: &str, password: &str, method: &str, uri: &str, cnonce: &str) -> String {
+        self.count += 1;
+        let nc = format!("{:08x}", self.count);
+        let ha1 = md5_hex(&format!("{}:{}:{}", username, self.realm, password));
+        let ha2 = md5_hex(&format!("{}:{}", method, uri));
+
+        let response = match &self.qop {
+            Some(qop) => md5_hex(&format!("{}:{}:{}:{}:{}:{}", ha1, self.nonce, nc, cnonce, qop, ha2)),
+            None => md5_hex(&format!("{}:{}:{}", ha1, self.nonce, ha2)),
+        };
+
+        let mut header = format!(r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", response="{}""#,
+            username, self.realm, self.nonce, uri, response);
+        if let Some(qop) = &self.qop {
+            header += &format!(r#", qop={}, nc={}, cnonce="{}""#, qop, nc, cnonce);
+        }
+        if let Some(opaque) = &self.opaque {
+            header += &format!(r#", opaque="{}""#, opaque);
+        }
+        if let Some(algorithm) = &self.algorithm {
+            header += &format!(", algorithm={}", algorithm);
+        }
+        header
+    }
+}
+
+impl<H: HttpTransport> MPX<H> {
+    /// Authentication scheme negotiated with the device, if any request
+    /// has been made yet
+    pub fn auth_scheme(&self) -> Option<AuthScheme> {
+        self.inner.auth.lock().unwrap().scheme
+    }
+
+    /// Scheme to retry a request with, if `response` asks for another
+    /// (or a fresh) authentication than `used`
+    fn negotiate(&self, response: &Response, used: AuthScheme) -> Option<AuthScheme> {
+        if response.status == 401 {
+            let challenges: Vec<&str> = response.header("WWW-Authenticate").collect();
+
+            if let Some(challenge) = challenges.iter().find_map(|c| parse_challenge(c)) {
+                let retry = used != AuthScheme::Digest || challenge.stale;
+                self.inner.auth.lock().unwrap().digest = Some(challenge);
+                return if retry { Some(AuthScheme::Digest) } else { None };
+            }
+            if used != AuthScheme::Basic && challenges.iter().any(|c| c.trim().to_ascii_lowercase().starts_with("basic")) {
+                return Some(AuthScheme::Basic);
+            }
+        }
+
+        match &self.inner.session {
+            Some(session) if session.expired(response) => Some(AuthScheme::Session),
+            _ => None,
+        }
+    }
+
+    async fn send_with(&self, request: &Request, scheme: AuthScheme) -> Result<Response, MPXError> {
+        let request = match scheme {
+            AuthScheme::Basic => {
+                let credentials = self.credentials().await?;
+                let plain = zeroize::Zeroizing::new(format!("{}:{}", credentials.username(), credentials.password()));
+                request.clone().header("Authorization", &format!("Basic {}", base64(plain.as_bytes())))
+            },
+            AuthScheme::Session => {
+                let cookie = self.inner.session_cookie.lock().unwrap().clone().unwrap_or_default();
+                request.clone().header("Cookie", &cookie)
+            },
+            AuthScheme::Digest => {
+                let method = match request.method {
+                    crate::transport::Method::Get => "GET",
+                    crate::transport::Method::Post => "POST",
+                };
+                let credentials = self.credentials().await?;
+                let cnonce = md5_hex(&format!("{:?}", SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()));
+                let header = self.inner.auth.lock().unwrap().digest.as_mut()
+                    .map(|d| d.authorization(credentials.username(), credentials.password(), method, request.path_and_query(), &cnonce));
+                match header {
+                    Some(header) => request.clone().header("Authorization", &header),
+                    None => request.clone(),
+                }
+            },
+        };
+
+        self.exchange(request).await
+    }
+
+    /// Send a request with the negotiated authentication scheme,
+    /// renegotiating (or logging in again) if the device asks for it
+    pub(crate) async fn request(&self, request: Request) -> Result<Response, MPXError> {
+        let scheme = self.auth_scheme().unwrap_or(AuthScheme::Basic);
+        if scheme == AuthScheme::Session && self.inner.session_cookie.lock().unwrap().is_none() {
+            self.login().await?;
+        }
+
+        let mut response = self.send_with(&request, scheme).await?;
+        let mut used = scheme;
+
+        if let Some(next) = self.negotiate(&response, scheme) {
+            if next == AuthScheme::Session {
+                self.login().await?;
+            }
+            response = self.send_with(&request, next).await?;
+            used = next;
+        }
+
+        self.inner.auth.lock().unwrap().scheme = Some(used);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_challenge() {
+        let challenge = parse_challenge(r#"Digest realm="testrealm@host.com", qop="auth,auth-int", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#).unwrap();
+        assert_eq!(challenge.realm, "testrealm@host.com");
+        assert_eq!(challenge.qop.as_deref(), Some("auth"));
+        assert_eq!(challenge.opaque.as_deref(), Some("5ccc069c403ebaf9f0171e9517f40e41"));
+        assert!(parse_challenge(r#"Basic realm="x""#).is_none());
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b"Aladdin:open sesame"), "QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+        assert_eq!(base64(b"Liebert:secret"), "TGllYmVydDpzZWNyZXQ=");
+        assert_eq!(base64(b"ab"), "YWI=");
+    }
+
+    #[test]
+    fn test_digest_response() {
+        // example from RFC 2617, section 3.5
+        let mut challenge = parse_challenge(r#"Digest realm="testrealm@host.com", qop="auth", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#).unwrap();
+        let header = challenge.authorization("Mufasa", "Circle Of Life", "GET", "/dir/index.html", "0a4f113b");
+        assert!(header.contains(r#"response="6629fae49393a05397450978507c4ef1""#));
+        assert!(header.contains("nc=00000001"));
+    }
+}
@@ -0,0 +1,162 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Phase load balancing advisor
+//!
+//! Suggests moving the load of single receptacles to a branch module
+//! fed by another phase, so that the currents of L1, L2 and L3 become
+//! more even. Every branch module is wired to a single line source,
+//! so a move always changes the phase of the device.
+
+use crate::{LineSource, Snapshot, WiringType};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Suggested move of a receptacle's load to another branch module
+pub struct Move {
+    /// Branch number the load is currently connected to
+    pub branch: u8,
+    /// Receptacle number the load is currently connected to
+    pub receptacle: u8,
+    /// Current drawn by the receptacle in A AC
+    pub current: f32,
+    /// Phase the load is currently connected to
+    pub from: LineSource,
+    /// Branch number the load should be moved to
+    pub to_branch: u8,
+    /// Phase of the target branch
+    pub to: LineSource,
+    /// Phase imbalance before the move in A AC
+    pub imbalance_before: f32,
+    /// Expected phase imbalance after the move in A AC
+    pub imbalance_after: f32,
+}
+
+impl Move {
+    /// Expected reduction of the phase imbalance in A AC
+    pub fn improvement(&self) -> f32 {
+        self.imbalance_before - self.imbalance_after
+    }
+}
+
+fn phase_index(source: &LineSource) -> Option<usize> {
+    match source {
+        LineSource::L1toN => Some(0),
+        LineSource::L2toN => Some(1),
+        LineSource::L3toN => Some(2),
+        _ => None,
+    }
+}
+
+/// Input currents of L1, L2 and L3 in A AC (0 for the phases missing
+/// on single phase PDUs)
+pub fn phase_currents(snapshot: &Snapshot) -> [f32; 3] {
+    let status = &snapshot.info.status;
+    [status.current_l1, status.current_l2.unwrap_or(0.0), status.current_l3.unwrap_or(0.0)]
+}
+
+/// Phase imbalance, i.e. difference between the highest and lowest phase current in A AC
+pub fn imbalance(currents: &[f32; 3]) -> f32 {
+    let max = currents.iter().cloned().fold(f32::MIN, f32::max);
+    let min = currents.iter().cloned().fold(f32::MAX, f32::min);
+    max - min
+}
+
+/// Suggest receptacle moves reducing the phase imbalance, best first
+///
+/// Only branch modules contained in the snapshot are considered as
+/// targets, and moves which would push the target branch over its
+/// over current warning threshold are skipped. Single phase PDUs
+/// never get any suggestions.
+pub fn suggest_moves(snapshot: &Snapshot) -> Vec<Move> {
+    if snapshot.info.hardware.wiring_type != WiringType::ThreePhase {
+        return Vec::new();
+    }
+
+    let currents = phase_currents(snapshot);
+    let before = imbalance(&currents);
+    let mut moves = Vec::new();
+
+    for r in snapshot.receptacles.iter() {
+        let from = &r.info.hardware.line_source;
+        let current = match r.info.status.current {
+            Some(current) if current > 0.0 => current,
+            _ => continue,
+        };
+        let from_index = match phase_index(from) {
+            Some(index) => index,
+            None => continue,
+        };
+
+        for target in snapshot.branches.iter() {
+            let to = &target.info.hardware.line_source;
+            let to_index = match phase_index(to) {
+                Some(index) if index != from_index => index,
+                _ => continue,
+            };
+            if target.info.over_current_warning_margin().remaining < current {
+                continue;
+            }
+
+            let mut after = currents;
+            after[from_index] -= current;
+            after[to_index] += current;
+            let after = imbalance(&after);
+
+            if after < before {
+                moves.push(Move {
+                    branch: r.branch,
+                    receptacle: r.receptacle,
+                    current,
+                    from: from.clone(),
+                    to_branch: target.branch,
+                    to: to.clone(),
+                    imbalance_before: before,
+                    imbalance_after: after,
+                });
+            }
+        }
+    }
+
+    moves.sort_by(|a, b| b.improvement().partial_cmp(&a.improvement()).unwrap_or(std::cmp::Ordering::Equal));
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::BranchSnapshot;
+    use crate::snapshot::tests::snapshot;
+
+    #[test]
+    fn test_suggest_moves() {
+        let mut snapshot = snapshot();
+        assert!(suggest_moves(&snapshot).is_empty());
+
+        // receptacles on branch 1 (L3) draw 2.5 A and 1.5 A, L1 is idle
+        snapshot.info.status.current_l1 = 0.0;
+        snapshot.info.status.current_l2 = Some(2.0);
+        snapshot.info.status.current_l3 = Some(4.0);
+        snapshot.receptacles[0].info.status.current = Some(2.5);
+        snapshot.receptacles[1].info.status.current = Some(1.5);
+
+        let mut l1 = snapshot.branches[0].info.clone();
+        l1.hardware.line_source = LineSource::L1toN;
+        snapshot.branches.push(BranchSnapshot { branch: 2, info: l1, taken_at: snapshot.taken_at });
+
+        let moves = suggest_moves(&snapshot);
+        assert_eq!(moves.len(), 2);
+        assert_eq!(moves[0].receptacle, 1);
+        assert_eq!(moves[0].to_branch, 2);
+        assert_eq!(moves[0].to, LineSource::L1toN);
+        assert_eq!(moves[0].imbalance_before, 4.0);
+        assert_eq!(moves[0].imbalance_after, 1.0);
+        assert_eq!(moves[1].improvement(), 3.0);
+
+        snapshot.info.hardware.wiring_type = WiringType::OnePhase;
+        assert!(suggest_moves(&snapshot).is_empty());
+    }
+}
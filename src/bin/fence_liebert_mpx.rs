@@ -0,0 +1,10 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Fence agent for Liebert MPX PDUs, see `liebert_mpx::fence`
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    std::process::exit(liebert_mpx::fence::main().await);
+}
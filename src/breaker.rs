@@ -0,0 +1,172 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Circuit breaker for unreachable devices
+//!
+//! After `threshold` consecutive transport failures (connection errors,
+//! timeouts) the circuit opens and all further requests fail
+//! immediately with `MPXError::CircuitOpen`, instead of waiting for
+//! another timeout. Once `cooldown` has passed a single probe request
+//! is let through; if it succeeds the circuit closes again, otherwise
+//! it stays open for another cooldown period.
+
+use crate::{CircuitOpenError, MPX, MPXError};
+use crate::transport::HttpTransport;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+/// Circuit breaker configuration
+pub struct CircuitBreaker {
+    /// Consecutive failures opening the circuit
+    pub threshold: u32,
+    /// Time until a probe request is let through
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        CircuitBreaker {
+            threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Clone,Debug,Default)]
+/// Circuit breaker state of a [`MPX`]
+pub(crate) struct BreakerState {
+    failures: u32,
+    /// Set while the circuit is open
+    opened: Option<Instant>,
+    /// A probe request is in flight
+    probing: bool,
+}
+
+impl BreakerState {
+    /// Check if a request may be sent at `now`
+    fn admit(&mut self, config: &CircuitBreaker, now: Instant) -> Result<(), CircuitOpenError> {
+        let opened = match self.opened {
+            Some(opened) => opened,
+            None => return Ok(()),
+        };
+
+        let elapsed = now.duration_since(opened);
+        if elapsed >= config.cooldown && !self.probing {
+            self.probing = true;
+            return Ok(());
+        }
+
+        Err(CircuitOpenError {
+            failures: self.failures,
+            retry_in: config.cooldown.saturating_sub(elapsed),
+        })
+    }
+
+    fn record(&mut self, config: &CircuitBreaker, success: bool, now: Instant) {
+        self.probing = false;
+        if success {
+            self.failures = 0;
+            self.opened = None;
+            return;
+        }
+
+        self.failures += 1;
+        if self.failures >= config.threshold {
+            self.opened = Some(now);
+        }
+    }
+}
+
+/// Clears the probe flag when a request is dropped before its result is
+/// recorded, so a cancelled probe does not keep the circuit open forever
+struct ProbeGuard<'a>(&'a Mutex<BreakerState>);
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().probing = false;
+    }
+}
+
+impl<H: HttpTransport> MPX<H> {
+    /// Run `operation` through the circuit breaker, if configured
+    pub(crate) async fn with_breaker<T, F>(&self, operation: F) -> Result<T, MPXError>
+    where
+        F: Future<Output = Result<T, MPXError>>,
+    {
+        let config = match &self.inner.breaker {
+            Some(config) => config,
+            None => return operation.await,
+        };
+
+        self.inner.breaker_state.lock().unwrap().admit(config, Instant::now())?;
+        let _probe = ProbeGuard(&self.inner.breaker_state);
+        let result = operation.await;
+
+        // only transport problems say something about the reachability
+        let failed = matches!(&result, Err(e) if e.is_retryable() || matches!(e, MPXError::Timeout(_)));
+        self.inner.breaker_state.lock().unwrap().record(config, !failed, Instant::now());
+        result
+    }
+
+    /// Check if the circuit breaker currently rejects requests
+    pub fn circuit_open(&self) -> bool {
+        self.inner.breaker_state.lock().unwrap().opened.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaker_state() {
+        let config = CircuitBreaker { threshold: 2, cooldown: Duration::from_secs(10) };
+        let mut state = BreakerState::default();
+        let now = Instant::now();
+
+        assert!(state.admit(&config, now).is_ok());
+        state.record(&config, false, now);
+        assert!(state.admit(&config, now).is_ok());
+        state.record(&config, false, now);
+
+        let err = state.admit(&config, now + Duration::from_secs(4)).unwrap_err();
+        assert_eq!(err.failures, 2);
+        assert_eq!(err.retry_in, Duration::from_secs(6));
+
+        // one probe after the cooldown, which fails
+        let later = now + Duration::from_secs(10);
+        assert!(state.admit(&config, later).is_ok());
+        assert!(state.admit(&config, later).is_err());
+        state.record(&config, false, later);
+        assert!(state.admit(&config, later + Duration::from_secs(1)).is_err());
+
+        // successful probe closes the circuit
+        let later = later + Duration::from_secs(10);
+        assert!(state.admit(&config, later).is_ok());
+        state.record(&config, true, later);
+        assert!(state.admit(&config, later).is_ok());
+        assert_eq!(state.failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_probe() {
+        use crate::TimeoutError;
+
+        let config = CircuitBreaker { threshold: 1, cooldown: Duration::ZERO };
+        let mpx = MPX::builder("pdu1").circuit_breaker(config).build().unwrap();
+        let failed: Result<(), MPXError> = mpx.with_breaker(async { Err(TimeoutError { deadline: None }.into()) }).await;
+        assert!(failed.is_err());
+        assert!(mpx.circuit_open());
+
+        // the probe is cancelled before it finishes
+        let probe = mpx.with_breaker(std::future::pending::<Result<(), MPXError>>());
+        assert!(tokio::time::timeout(Duration::from_millis(10), probe).await.is_err());
+
+        // so the next request is let through as probe
+        assert!(mpx.with_breaker(async { Ok(()) }).await.is_ok());
+        assert!(!mpx.circuit_open());
+    }
+}
@@ -0,0 +1,260 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Capabilities of branch receptacle modules
+//!
+//! Only receptacle-managed BRMs measure and switch single receptacles.
+//! Branch-monitored modules measure the whole branch, elementary modules
+//! nothing at all. The module type follows from the BRM model of the
+//! branch information, the receptacle information reports a
+//! [`Capability`] per receptacle. For receptacles without measurement the
+//! [`ReceptacleStatus`](crate::ReceptacleStatus) measurements are `None`.
+//! Commands and settings writes for receptacles which cannot be switched
+//! fail with [`MPXError::NotSupported`](crate::MPXError::NotSupported);
+//! the check uses the module and receptacle information fetched before.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::capabilities::ModuleType;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         if pdu.module_type((1, 2)).await.unwrap() == Some(ModuleType::ReceptacleManaged) {
+//!             pdu.receptacle_reboot((1, 2, 3)).await.unwrap();
+//!         }
+//!     };
+//! }
+//! ```
+
+use crate::BRMModel;
+#[cfg(feature = "client")]
+use crate::Capability;
+#[cfg(feature = "client")]
+use crate::{BranchAddress, ErrorKind, MPX, MPXError, NotSupportedError};
+#[cfg(feature = "client")]
+use crate::transport::HttpTransport;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Monitoring level of a branch receptacle module
+pub enum ModuleType {
+    /// No measurement, no receptacle control
+    Elementary,
+    /// Measurement of the whole branch
+    BranchMonitored,
+    /// Measurement and control of each receptacle
+    ReceptacleManaged,
+}
+
+impl BRMModel {
+    /// Monitoring level of the module, `None` for unknown models
+    pub fn module_type(&self) -> Option<ModuleType> {
+        match self {
+            BRMModel::EEBC7N1N | BRMModel::EEBC7N2N | BRMModel::EEBC7N3N |
+            BRMModel::EEBC4O1N | BRMModel::EEBC4O2N | BRMModel::EEBC4O3N |
+            BRMModel::EEBC3P1N | BRMModel::EEBC3P2N | BRMModel::EEBC3P3N => Some(ModuleType::Elementary),
+            BRMModel::EBBC6N1N | BRMModel::EBBC6N2N | BRMModel::EBBC6N3N |
+            BRMModel::EBBC4O1N | BRMModel::EBBC4O2N | BRMModel::EBBC4O3N |
+            BRMModel::EBBC3P1N | BRMModel::EBBC3P2N | BRMModel::EBBC3P3N => Some(ModuleType::BranchMonitored),
+            BRMModel::ERBC6N1N | BRMModel::ERBC6N2N | BRMModel::ERBC6N3N |
+            BRMModel::ERBC4O1N | BRMModel::ERBC4O2N | BRMModel::ERBC4O3N |
+            BRMModel::ERBC3P1N | BRMModel::ERBC3P2N | BRMModel::ERBC3P3N => Some(ModuleType::ReceptacleManaged),
+            BRMModel::Unknown(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+fn not_supported(operation: &str, target: String) -> MPXError {
+    NotSupportedError { operation: operation.to_string(), target }.into()
+}
+
+/// Fail with `NotSupported` if the module cannot control receptacles
+#[cfg(feature = "client")]
+fn check_module(model: &BRMModel, operation: &str) -> Result<(), MPXError> {
+    match model.module_type() {
+        Some(ModuleType::Elementary) | Some(ModuleType::BranchMonitored) => Err(not_supported(operation, format!("{} module", model))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "client")]
+impl<H> MPX<H> {
+    /// Remember the BRM model of a branch for later commands
+    pub(crate) fn remember_module(&self, pdu: u8, branch: u8, model: &BRMModel) {
+        self.inner.modules.lock().unwrap().insert((pdu, branch), model.clone());
+    }
+
+    /// Remember the capabilities of a receptacle for later commands
+    pub(crate) fn remember_capability(&self, pdu: u8, branch: u8, receptacle: u8, capability: &Capability) {
+        self.inner.receptacle_capabilities.lock().unwrap().insert((pdu, branch, receptacle), capability.clone());
+    }
+
+    fn known_module(&self, pdu: u8, branch: u8) -> Option<BRMModel> {
+        self.inner.modules.lock().unwrap().get(&(pdu, branch)).cloned()
+    }
+
+    /// Fail with `NotSupported` if the receptacle is known to lack control
+    fn check_receptacle(&self, address: (u8, u8, u8), operation: &str) -> Result<(), MPXError> {
+        let (pdu, branch, receptacle) = address;
+        match self.inner.receptacle_capabilities.lock().unwrap().get(&address) {
+            Some(capability) if !capability.can_control() => {
+                Err(not_supported(operation, format!("receptacle {}-{}-{} ({})", pdu, branch, receptacle, capability)))
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl<H: HttpTransport> MPX<H> {
+    /// Monitoring level of a branch module, `None` for unknown models
+    ///
+    /// The branch information is only fetched if the module has not been
+    /// seen before.
+    pub async fn module_type(&self, address: impl Into<BranchAddress>) -> Result<Option<ModuleType>, MPXError> {
+        let address = address.into();
+        let model = match self.known_module(address.pdu.0, address.branch.0) {
+            Some(model) => model,
+            None => self.get_info_branch(address).await?.hardware.brm_model,
+        };
+        Ok(model.module_type())
+    }
+
+    /// Run a receptacle command or settings write, failing with
+    /// `NotSupported` on receptacles without control
+    ///
+    /// Known modules and receptacles are checked upfront. Otherwise a
+    /// failed request is followed by a lookup of the module, so that the
+    /// error names the missing capability instead of the unexpected
+    /// response.
+    pub(crate) async fn receptacle_control<F>(&self, address: (u8, u8, u8), operation: &str, request: F) -> Result<(), MPXError>
+    where
+        F: std::future::Future<Output = Result<(), MPXError>>,
+    {
+        let (pdu, branch, _) = address;
+        self.check_receptacle(address, operation)?;
+        if let Some(model) = self.known_module(pdu, branch) {
+            check_module(&model, operation)?;
+            return request.await;
+        }
+
+        let result = request.await;
+        if matches!(&result, Err(e) if e.kind() == ErrorKind::Parse) {
+            if let Ok(info) = self.get_info_branch((pdu, branch)).await {
+                check_module(&info.hardware.brm_model, operation)?;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_receptacle_info, Capability};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_module_type() {
+        assert_eq!(BRMModel::from_str("MPXBRM-EEBC3P1N").unwrap().module_type(), Some(ModuleType::Elementary));
+        assert_eq!(BRMModel::from_str("MPXBRM-EBBC4O2N").unwrap().module_type(), Some(ModuleType::BranchMonitored));
+        assert_eq!(BRMModel::from_str("MPXBRM-ERBC6N3N").unwrap().module_type(), Some(ModuleType::ReceptacleManaged));
+        assert_eq!(BRMModel::from_str("MPXBRM-EXBC9N1N").unwrap().module_type(), None);
+    }
+
+    #[test]
+    fn test_unmeasured_receptacle() {
+        let html: String = include_str!("../testdata/receptacle-info.htm")
+            .replace("All Measurements/Control", "Monitoring Not Available")
+            .split_inclusive("</tr>")
+            .map(|row| match row.rfind("<tr") {
+                Some(start) if row[start..].contains(">Receptacle Power<") || row[start..].contains(">Receptacle Power Factor<") => &row[..start],
+                _ => row,
+            })
+            .collect();
+        let info = parse_receptacle_info(html).unwrap();
+        assert_eq!(info.status.power, None);
+        assert_eq!(info.status.power_factor, None);
+        assert!(info.status.voltage.is_some());
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_not_supported() {
+        use crate::transport::{Method, Request, Response};
+        use std::sync::Mutex;
+
+        struct Module(Mutex<Vec<Method>>);
+
+        impl HttpTransport for Module {
+            async fn send(&self, request: Request) -> Result<Response, MPXError> {
+                self.0.lock().unwrap().push(request.method);
+                let (status, body) = match request.method {
+                    Method::Get => (200, include_str!("../testdata/branch-info.htm").replace("MPXBRM-ERBC6N3N", "MPXBRM-EBBC6N3N")),
+                    Method::Post => (404, String::new()),
+                };
+                Ok(Response { status, url: request.url, headers: Vec::new(), body })
+            }
+        }
+
+        let mpx = MPX::builder("pdu1").build_with_transport(Module(Mutex::new(Vec::new())));
+        let e = mpx.receptacle_reboot((1, 2, 3)).await.unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NotSupported);
+        assert_eq!(e.to_string(), "Reboot not supported by MPXBRM-EBBC6N3N module");
+
+        // the module is known now, nothing is sent
+        mpx.inner.transport.0.lock().unwrap().clear();
+        assert!(mpx.receptacle_enable((1, 2, 3)).await.is_err());
+        assert!(mpx.inner.transport.0.lock().unwrap().is_empty());
+        assert_eq!(mpx.module_type((1, 2)).await.unwrap(), Some(ModuleType::BranchMonitored));
+    }
+
+    #[test]
+    fn test_capability() {
+        for (input, control, measure) in [
+            ("All Measurements/Control", true, true),
+            ("Measurements Only", false, false),
+            ("Something New", false, false),
+        ] {
+            let capability = Capability::from_str(input).unwrap();
+            assert_eq!((capability.can_control(), capability.can_measure()), (control, measure), "{}", input);
+        }
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_unknown_capability_receptacle() {
+        use crate::transport::{Method, Request, Response};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Receptacle(AtomicUsize);
+
+        impl HttpTransport for Receptacle {
+            async fn send(&self, request: Request) -> Result<Response, MPXError> {
+                if request.method == Method::Post {
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                }
+                let body = include_str!("../testdata/receptacle-info.htm").replace("All Measurements/Control", "Measurements Only");
+                Ok(Response { status: 200, url: request.url, headers: Vec::new(), body })
+            }
+        }
+
+        let mpx = MPX::builder("pdu1").build_with_transport(Receptacle(AtomicUsize::new(0)));
+        let info = mpx.get_info_receptacle((1, 1, 2)).await.unwrap();
+        assert_eq!(info.hardware.capabilities, Capability::Unknown("Measurements Only".to_string()));
+        assert!(info.status.current.is_some());
+
+        let e = mpx.receptacle_disable((1, 1, 2)).await.unwrap_err();
+        assert_eq!(e.to_string(), "Disable not supported by receptacle 1-1-2 (Measurements Only)");
+        assert_eq!(mpx.inner.transport.0.load(Ordering::SeqCst), 0);
+
+        // other receptacles are not affected
+        mpx.receptacle_disable((1, 1, 3)).await.unwrap();
+        assert_eq!(mpx.inner.transport.0.load(Ordering::SeqCst), 1);
+    }
+}
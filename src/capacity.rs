@@ -0,0 +1,259 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Capacity planning
+//!
+//! Answers whether an additional device with a given power draw fits
+//! onto a branch module or receptacle without reaching any configured
+//! over current warning threshold on the receptacle, the branch module
+//! or the PDU input phase feeding it.
+//!
+//! [`capacity_report`] lists the headroom to the ratings of every input
+//! phase and branch module of a snapshot.
+
+use crate::{InvalidDataError, LineSource, MissingDataError, MPXError, Snapshot};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Element of the power path which may limit the capacity
+pub enum Limit {
+    /// Over current warning threshold of the receptacle
+    Receptacle,
+    /// Over current warning threshold of the branch module
+    Branch,
+    /// Over current warning threshold of the PDU input phase
+    Phase(LineSource),
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Result of a capacity check
+pub struct Verdict {
+    /// The additional load fits without reaching any warning threshold
+    pub fits: bool,
+    /// Additional current of the device in A AC
+    pub required_current: f32,
+    /// Element with the least headroom
+    pub limiting: Limit,
+    /// Headroom of the limiting element after adding the device in A AC
+    /// (negative if it does not fit)
+    pub remaining: f32,
+}
+
+/// Check if a device drawing `watts` (at power factor `power_factor`)
+/// can be added to a branch module or, if given, a specific receptacle
+///
+/// The current is calculated from the measured branch voltage, falling
+/// back to the rated voltage if no voltage is measured.
+pub fn check(snapshot: &Snapshot, branch: u8, receptacle: Option<u8>, watts: f32, power_factor: f32) -> Result<Verdict, MPXError> {
+    let b = snapshot.branch(branch).ok_or_else(MissingDataError::default)?;
+    let voltage = if b.info.status.voltage > 0.0 {
+        b.info.status.voltage
+    } else {
+        b.info.hardware.rated_line_voltage as f32
+    };
+    if voltage <= 0.0 || power_factor <= 0.0 {
+        return Err(MPXError::InvalidDataError(InvalidDataError::default()));
+    }
+    let required_current = watts / (voltage * power_factor);
+
+    let line_source = &b.info.hardware.line_source;
+    let phases = snapshot.info.over_current_warning_margins();
+    let phase = match line_source {
+        LineSource::L1toN => phases[0],
+        LineSource::L2toN => phases[1],
+        LineSource::L3toN => phases[2],
+        _ => return Err(MPXError::InvalidDataError(InvalidDataError::default())),
+    }.ok_or_else(MissingDataError::default)?;
+
+    let mut headroom = vec![
+        (Limit::Phase(line_source.clone()), phase.remaining),
+        (Limit::Branch, b.info.over_current_warning_margin().remaining),
+    ];
+
+    if let Some(receptacle) = receptacle {
+        let r = snapshot.receptacle(branch, receptacle).ok_or_else(MissingDataError::default)?;
+        let margin = r.info.over_current_warning_margin().ok_or_else(MissingDataError::default)?;
+        headroom.push((Limit::Receptacle, margin.remaining));
+    }
+
+    let (limiting, remaining) = headroom.into_iter()
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .ok_or_else(MissingDataError::default)?;
+    let remaining = remaining - required_current;
+
+    Ok(Verdict {
+        fits: remaining > 0.0,
+        required_current,
+        limiting,
+        remaining,
+    })
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Headroom to the rated current
+pub struct Headroom {
+    /// Rated current in A AC
+    pub rated_current: f32,
+    /// Present current in A AC
+    pub current: f32,
+    /// Current left until the rating is reached in A AC (negative if exceeded)
+    pub remaining_current: f32,
+    /// Power left until the rating is reached in W at unity power factor
+    pub remaining_power: f32,
+}
+
+impl Headroom {
+    fn new(rated_current: f32, current: f32, voltage: f32) -> Self {
+        let remaining_current = rated_current - current;
+        Headroom {
+            rated_current,
+            current,
+            remaining_current,
+            remaining_power: remaining_current * voltage,
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Headroom of a PDU input phase
+pub struct PhaseCapacity {
+    pub line_source: LineSource,
+    pub headroom: Headroom,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Headroom of a branch module
+pub struct BranchCapacity {
+    /// Branch number (usually 1-6)
+    pub branch: u8,
+    pub line_source: LineSource,
+    pub headroom: Headroom,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Headroom of a PDU, its input phases and its branch modules
+pub struct CapacityReport {
+    /// Rated power of all phases in W at unity power factor
+    pub rated_power: f32,
+    /// Power left on all phases in W at unity power factor
+    pub remaining_power: f32,
+    /// L1, or L1, L2 and L3 for three phase PDUs
+    pub phases: Vec<PhaseCapacity>,
+    pub branches: Vec<BranchCapacity>,
+}
+
+/// Measured voltage, falling back to the rated voltage
+fn voltage(measured: f32, rated: u32) -> f32 {
+    if measured > 0.0 {
+        measured
+    } else {
+        rated as f32
+    }
+}
+
+/// Headroom to the rated currents of all phases and branch modules
+///
+/// Powers are calculated from the measured voltages, falling back to
+/// the rated voltage if no voltage is measured.
+pub fn capacity_report(snapshot: &Snapshot) -> CapacityReport {
+    let (status, hardware) = (&snapshot.info.status, &snapshot.info.hardware);
+    let rated_current = hardware.rated_input_current as f32;
+    let phases = [
+        (LineSource::L1toN, Some(status.current_l1), Some(status.voltage_l1_n)),
+        (LineSource::L2toN, status.current_l2, status.voltage_l2_n),
+        (LineSource::L3toN, status.current_l3, status.voltage_l3_n),
+    ];
+
+    let mut rated_power = 0.0;
+    let mut remaining_power = 0.0;
+    let mut phase_capacity = Vec::new();
+    for (line_source, current, measured) in phases.iter() {
+        if let (Some(current), Some(measured)) = (current, measured) {
+            let voltage = voltage(*measured, hardware.rated_input_voltage);
+            let headroom = Headroom::new(rated_current, *current, voltage);
+            rated_power += rated_current * voltage;
+            remaining_power += headroom.remaining_power;
+            phase_capacity.push(PhaseCapacity { line_source: line_source.clone(), headroom });
+        }
+    }
+
+    let branches = snapshot.branches.iter().map(|b| {
+        let (status, hardware) = (&b.info.status, &b.info.hardware);
+        let voltage = voltage(status.voltage, hardware.rated_line_voltage);
+        BranchCapacity {
+            branch: b.branch,
+            line_source: hardware.line_source.clone(),
+            headroom: Headroom::new(hardware.rated_line_current as f32, status.current, voltage),
+        }
+    }).collect();
+
+    CapacityReport {
+        rated_power,
+        remaining_power,
+        phases: phase_capacity,
+        branches,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::tests::snapshot;
+
+    #[test]
+    fn test_capacity_check() {
+        let mut snapshot = snapshot();
+        snapshot.branches[0].info.status.voltage = 230.0;
+
+        // receptacle: 10 A rated, 90% warning threshold
+        let verdict = check(&snapshot, 1, Some(1), 1150.0, 1.0).unwrap();
+        assert!(verdict.fits);
+        assert_eq!(verdict.required_current, 5.0);
+        assert_eq!(verdict.limiting, Limit::Receptacle);
+        assert!((verdict.remaining - 4.0).abs() < 0.01);
+
+        // branch: 20 A rated, 90% warning threshold
+        let verdict = check(&snapshot, 1, None, 4600.0, 1.0).unwrap();
+        assert!(!verdict.fits);
+        assert_eq!(verdict.limiting, Limit::Branch);
+
+        // phase L3: 32 A rated, 75% warning threshold
+        snapshot.info.status.current_l3 = Some(20.0);
+        let verdict = check(&snapshot, 1, None, 1150.0, 1.0).unwrap();
+        assert_eq!(verdict.limiting, Limit::Phase(LineSource::L3toN));
+        assert!(!verdict.fits);
+
+        assert!(check(&snapshot, 9, None, 100.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_capacity_report() {
+        let mut snapshot = snapshot();
+        snapshot.info.hardware.rated_input_current = 32;
+        snapshot.info.status.voltage_l1_n = 230.0;
+        snapshot.info.status.current_l1 = 12.0;
+        snapshot.branches[0].info.status.voltage = 0.0;
+        snapshot.branches[0].info.status.current = 5.0;
+
+        let report = capacity_report(&snapshot);
+        assert_eq!(report.phases.len(), 3);
+        assert_eq!(report.phases[0].headroom.remaining_current, 20.0);
+        assert_eq!(report.phases[0].headroom.remaining_power, 4600.0);
+        let rated_voltage = snapshot.branches[0].info.hardware.rated_line_voltage as f32;
+        assert_eq!(report.branches[0].headroom.remaining_power, 15.0 * rated_voltage);
+        let sum: f32 = report.phases.iter().map(|p| p.headroom.remaining_power).sum();
+        assert_eq!(report.remaining_power, sum);
+
+        snapshot.info.status.current_l2 = None;
+        snapshot.info.status.current_l3 = None;
+        assert_eq!(capacity_report(&snapshot).phases.len(), 1);
+    }
+}
@@ -0,0 +1,250 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Guard-railed chaos testing
+//!
+//! Randomly reboots receptacles to test the resilience of lab
+//! infrastructure. Only receptacles on the allow-list are touched,
+//! only within the configured time windows and never more often than
+//! the rate limits allow. Every reboot (and every failure) is written
+//! to the audit log.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::chaos::{Chaos, ChaosConfig, TimeWindow};
+//! use std::time::Duration;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     let config = ChaosConfig {
+//!         allow_list: vec![(1, 2, 1), (1, 2, 2)],
+//!         windows: vec![TimeWindow::new(9, 0, 16, 0)],
+//!         ..ChaosConfig::default()
+//!     };
+//!     let mut chaos = Chaos::new(config).with_audit_log(Box::new(std::io::stderr()));
+//!     async {
+//!         loop {
+//!             chaos.step(&pdu).await.unwrap();
+//!             tokio::time::sleep(Duration::from_secs(60)).await;
+//!         }
+//!     };
+//! }
+//! ```
+
+use crate::{MPX, MPXError};
+use crate::metadata::Address;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+/// Daily time window in UTC, may wrap around midnight
+pub struct TimeWindow {
+    /// start in seconds after midnight
+    pub start: u32,
+    /// end in seconds after midnight (exclusive)
+    pub end: u32,
+}
+
+impl TimeWindow {
+    /// Window from `start_hour:start_minute` to `end_hour:end_minute` UTC
+    pub fn new(start_hour: u32, start_minute: u32, end_hour: u32, end_minute: u32) -> Self {
+        TimeWindow {
+            start: (start_hour * 60 + start_minute) * 60,
+            end: (end_hour * 60 + end_minute) * 60,
+        }
+    }
+
+    /// Check if `time` lies within the window
+    pub fn contains(&self, time: SystemTime) -> bool {
+        let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let of_day = (secs % 86400) as u32;
+        if self.start <= self.end {
+            of_day >= self.start && of_day < self.end
+        } else {
+            of_day >= self.start || of_day < self.end
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// Guard rails for chaos testing
+pub struct ChaosConfig {
+    /// Receptacles which may be rebooted
+    pub allow_list: Vec<Address>,
+    /// Time windows in which reboots are allowed; empty means never
+    pub windows: Vec<TimeWindow>,
+    /// Probability of a reboot per step (0-1)
+    pub probability: f32,
+    /// Maximum number of reboots within one hour
+    pub max_per_hour: usize,
+    /// Minimum time between two reboots
+    pub min_interval: Duration,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        ChaosConfig {
+            allow_list: Vec::new(),
+            windows: Vec::new(),
+            probability: 0.1,
+            max_per_hour: 2,
+            min_interval: Duration::from_secs(600),
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// Outcome of a chaos step
+pub enum Decision {
+    /// Reboot the receptacle
+    Reboot(Address),
+    /// Outside of all time windows
+    OutsideWindow,
+    /// Rate limit reached
+    RateLimited,
+    /// Random draw decided against a reboot
+    Skipped,
+    /// Allow-list is empty
+    NothingAllowed,
+}
+
+/// Chaos test runner
+pub struct Chaos {
+    config: ChaosConfig,
+    history: VecDeque<SystemTime>,
+    rng: u64,
+    audit: Option<Box<dyn Write + Send>>,
+}
+
+impl Chaos {
+    pub fn new(config: ChaosConfig) -> Self {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+        Self::with_seed(config, seed)
+    }
+
+    /// Runner with a fixed random seed, for reproducible test runs
+    pub fn with_seed(config: ChaosConfig, seed: u64) -> Self {
+        Chaos {
+            config,
+            history: VecDeque::new(),
+            rng: seed | 1,
+            audit: None,
+        }
+    }
+
+    /// Write audit records (one line per reboot attempt) to `log`
+    pub fn with_audit_log(mut self, log: Box<dyn Write + Send>) -> Self {
+        self.audit = Some(log);
+        self
+    }
+
+    /// xorshift64*, good enough to pick receptacles
+    fn random(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Decide what to do at `now` without touching the device
+    pub fn decide(&mut self, now: SystemTime) -> Decision {
+        if self.config.allow_list.is_empty() {
+            return Decision::NothingAllowed;
+        }
+        if !self.config.windows.iter().any(|w| w.contains(now)) {
+            return Decision::OutsideWindow;
+        }
+
+        let hour = Duration::from_secs(3600);
+        while self.history.front().is_some_and(|t| now.duration_since(*t).unwrap_or_default() >= hour) {
+            self.history.pop_front();
+        }
+        if self.history.len() >= self.config.max_per_hour {
+            return Decision::RateLimited;
+        }
+        if let Some(last) = self.history.back() {
+            if now.duration_since(*last).unwrap_or_default() < self.config.min_interval {
+                return Decision::RateLimited;
+            }
+        }
+
+        let draw = (self.random() >> 11) as f64 / (1u64 << 53) as f64;
+        if draw >= self.config.probability as f64 {
+            return Decision::Skipped;
+        }
+
+        let index = (self.random() % self.config.allow_list.len() as u64) as usize;
+        self.history.push_back(now);
+        Decision::Reboot(self.config.allow_list[index])
+    }
+
+    fn audit(&mut self, now: SystemTime, address: Address, result: &Result<(), MPXError>) {
+        if let Some(log) = self.audit.as_mut() {
+            let time = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            let (pdu, branch, receptacle) = address;
+            let outcome = match result {
+                Ok(()) => "ok".to_string(),
+                Err(e) => format!("failed: {}", e),
+            };
+            // the audit log must not stop the test run
+            let _ = writeln!(log, "{} chaos reboot {}-{}-{} {}", time, pdu, branch, receptacle, outcome);
+        }
+    }
+
+    /// Run a single chaos step, rebooting a receptacle if the guard rails allow it
+    pub async fn step(&mut self, mpx: &MPX) -> Result<Decision, MPXError> {
+        let now = SystemTime::now();
+        let decision = self.decide(now);
+
+        if let Decision::Reboot(address) = decision {
+            let (pdu, branch, receptacle) = address;
+            let result = mpx.receptacle_reboot((pdu, branch, receptacle)).await;
+            self.audit(now, address, &result);
+            result?;
+        }
+
+        Ok(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_window() {
+        let noon = UNIX_EPOCH + Duration::from_secs(12 * 3600);
+        let night = UNIX_EPOCH + Duration::from_secs(23 * 3600 + 30 * 60);
+        assert!(TimeWindow::new(9, 0, 16, 0).contains(noon));
+        assert!(!TimeWindow::new(9, 0, 16, 0).contains(night));
+        assert!(TimeWindow::new(22, 0, 6, 0).contains(night));
+        assert!(!TimeWindow::new(22, 0, 6, 0).contains(noon));
+    }
+
+    #[test]
+    fn test_guard_rails() {
+        let config = ChaosConfig {
+            allow_list: vec![(1, 2, 1), (1, 2, 2)],
+            windows: vec![TimeWindow::new(0, 0, 23, 59)],
+            probability: 1.0,
+            max_per_hour: 2,
+            min_interval: Duration::from_secs(600),
+        };
+        let mut chaos = Chaos::with_seed(config.clone(), 42);
+        let start = UNIX_EPOCH + Duration::from_secs(8 * 3600);
+
+        assert!(matches!(chaos.decide(start), Decision::Reboot(a) if config.allow_list.contains(&a)));
+        assert_eq!(chaos.decide(start + Duration::from_secs(60)), Decision::RateLimited);
+        assert!(matches!(chaos.decide(start + Duration::from_secs(700)), Decision::Reboot(_)));
+        assert_eq!(chaos.decide(start + Duration::from_secs(1400)), Decision::RateLimited);
+        assert!(matches!(chaos.decide(start + Duration::from_secs(3700)), Decision::Reboot(_)));
+
+        let mut chaos = Chaos::with_seed(ChaosConfig { windows: vec![TimeWindow::new(9, 0, 10, 0)], ..config.clone() }, 42);
+        assert_eq!(chaos.decide(start), Decision::OutsideWindow);
+
+        let mut chaos = Chaos::with_seed(ChaosConfig { probability: 0.0, ..config }, 42);
+        assert_eq!(chaos.decide(start), Decision::Skipped);
+    }
+}
@@ -0,0 +1,65 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! ANSI color helpers for terminal output
+//!
+//! Maps event levels and current utilization to colored strings, so
+//! terminal tools built on this crate present PDU state consistently.
+//! Callers are responsible for checking whether the output actually is
+//! a color capable terminal.
+
+use crate::EventLevel;
+use owo_colors::{OwoColorize, Style};
+
+/// Terminal style for an event level
+pub fn level_style(level: EventLevel) -> Style {
+    match level {
+        EventLevel::OK => Style::new().green(),
+        EventLevel::INFO => Style::new().blue(),
+        EventLevel::WARNING => Style::new().yellow(),
+        EventLevel::ALARM => Style::new().red().bold(),
+    }
+}
+
+/// Event level name, colored according to its severity
+pub fn level(level: EventLevel) -> String {
+    text_for_level(&level.to_string(), level)
+}
+
+/// Arbitrary text, colored according to the given event level
+pub fn text_for_level(text: &str, level: EventLevel) -> String {
+    text.style(level_style(level)).to_string()
+}
+
+/// Event level of a utilization value given the configured warning
+/// and alarm thresholds (all in %)
+pub fn utilization_level(percent: f32, warning_threshold: u32, alarm_threshold: u32) -> EventLevel {
+    if percent >= alarm_threshold as f32 {
+        EventLevel::ALARM
+    } else if percent >= warning_threshold as f32 {
+        EventLevel::WARNING
+    } else {
+        EventLevel::OK
+    }
+}
+
+/// Utilization percentage (e.g. "42 %"), colored by the configured thresholds
+pub fn utilization(percent: f32, warning_threshold: u32, alarm_threshold: u32) -> String {
+    text_for_level(&format!("{:.0} %", percent), utilization_level(percent, warning_threshold, alarm_threshold))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utilization_level() {
+        assert_eq!(utilization_level(10.0, 60, 80), EventLevel::OK);
+        assert_eq!(utilization_level(60.0, 60, 80), EventLevel::WARNING);
+        assert_eq!(utilization_level(95.0, 60, 80), EventLevel::ALARM);
+
+        assert_eq!(utilization(95.0, 60, 80), "\x1b[31;1m95 %\x1b[0m");
+        assert!(level(EventLevel::OK).starts_with("\x1b[32m"));
+    }
+}
@@ -0,0 +1,360 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Firmware compatibility information
+//!
+//! Lists which crate features have been verified against which PEM/BRM
+//! firmware versions, so operators can assess the risk of enabling
+//! control features on a device before doing so.
+//!
+//! Firmware revisions with different table labels or form field names
+//! are supported with a [`FirmwareProfile`]. The profile is selected by
+//! the PEM firmware version, which is detected from the PDU information
+//! page or configured with
+//! [`MPXBuilder::firmware`](crate::MPXBuilder::firmware). The verified
+//! firmware versions need no profile.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::compat::FirmwareProfile;
+//! use liebert::FWVersion;
+//!
+//! fn main() {
+//!     liebert::compat::register_profile(FirmwareProfile::new(FWVersion { p0: 0, p1: 4, p2: 0, p3: 0 })
+//!         .label("PDU Total Power", "PDU Total Input Power")
+//!         .field("ecThresholdHiAlmL1", "ecThresholdHighAlarmL1"));
+//! }
+//! ```
+
+use crate::{FWVersion, MPX, MPXError};
+use crate::partial::parse_pdu_info_partial;
+use crate::transport::{HttpTransport, Request};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::sync::{OnceLock, RwLock};
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Crate feature areas with individual firmware dependencies
+pub enum Feature {
+    /// Reading status/settings/hardware information
+    ReadInfo,
+    /// Reading the active event list
+    Events,
+    /// Receptacle enable/disable/reboot/identify
+    ReceptacleControl,
+    /// Writing PDU/branch/receptacle settings
+    Settings,
+    /// Resetting accumulated energy counters
+    EnergyReset,
+}
+
+/// All features, in report order
+pub const FEATURES: [Feature; 5] = [
+    Feature::ReadInfo,
+    Feature::Events,
+    Feature::ReceptacleControl,
+    Feature::Settings,
+    Feature::EnergyReset,
+];
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Support level of a feature for a given firmware version
+pub enum Support {
+    KnownGood,
+    KnownBroken,
+    Untested,
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Module type a firmware version belongs to
+pub enum Module {
+    /// Power entry module
+    PEM,
+    /// Branch receptacle module
+    BRM,
+}
+
+impl Feature {
+    /// Module types involved in providing the feature
+    pub fn modules(&self) -> &'static [Module] {
+        match self {
+            Feature::Events => &[Module::PEM],
+            Feature::ReceptacleControl => &[Module::BRM],
+            _ => &[Module::PEM, Module::BRM],
+        }
+    }
+}
+
+/// Support levels of the features verified on a firmware version
+type FeatureSupport = &'static [(Feature, Support)];
+
+/// Known firmware versions: module, version, features and their support level
+const KNOWN_FIRMWARE: &[(Module, FWVersion, FeatureSupport)] = &[
+    (Module::PEM, FWVersion { p0: 0, p1: 3, p2: 4, p3: 2 }, &[
+        (Feature::ReadInfo, Support::KnownGood),
+        (Feature::Events, Support::KnownGood),
+        (Feature::Settings, Support::KnownGood),
+        (Feature::EnergyReset, Support::KnownGood),
+    ]),
+    (Module::BRM, FWVersion { p0: 0, p1: 3, p2: 4, p3: 0 }, &[
+        (Feature::ReadInfo, Support::KnownGood),
+        (Feature::ReceptacleControl, Support::KnownGood),
+        (Feature::Settings, Support::KnownGood),
+        (Feature::EnergyReset, Support::KnownGood),
+    ]),
+];
+
+/// Support level of `feature` on a module running firmware `version`
+pub fn support(module: Module, version: FWVersion, feature: Feature) -> Support {
+    KNOWN_FIRMWARE.iter()
+        .filter(|(m, v, _)| *m == module && *v == version)
+        .flat_map(|(_, _, features)| features.iter())
+        .find(|(f, _)| *f == feature)
+        .map(|(_, s)| *s)
+        .unwrap_or(Support::Untested)
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Firmware version of a single module
+pub struct ModuleFirmware {
+    pub module: Module,
+    pub pdu: u8,
+    /// Branch number for BRMs
+    pub branch: Option<u8>,
+    pub version: FWVersion,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Result of `MPX::check_compatibility()`
+pub struct CompatibilityReport {
+    /// Firmware versions of all inspected modules
+    pub modules: Vec<ModuleFirmware>,
+    /// Combined support level per feature (worst of all modules)
+    pub features: Vec<(Feature, Support)>,
+}
+
+impl CompatibilityReport {
+    /// Build a report from the module firmware versions
+    pub fn new(modules: Vec<ModuleFirmware>) -> Self {
+        let features = FEATURES.iter().map(|feature| {
+            let levels: Vec<Support> = modules.iter()
+                .filter(|m| feature.modules().contains(&m.module))
+                .map(|m| support(m.module, m.version, *feature))
+                .collect();
+            let combined = if levels.contains(&Support::KnownBroken) {
+                Support::KnownBroken
+            } else if levels.is_empty() || levels.contains(&Support::Untested) {
+                Support::Untested
+            } else {
+                Support::KnownGood
+            };
+            (*feature, combined)
+        }).collect();
+
+        CompatibilityReport { modules, features }
+    }
+
+    /// Support level of a single feature
+    pub fn support(&self, feature: Feature) -> Support {
+        self.features.iter()
+            .find(|(f, _)| *f == feature)
+            .map(|(_, s)| *s)
+            .unwrap_or(Support::Untested)
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// Row label and form field differences of a firmware revision range
+///
+/// A profile applies from `since` up to the next registered profile.
+pub struct FirmwareProfile {
+    /// First PEM firmware version with these labels and fields
+    pub since: FWVersion,
+    /// Row labels of the firmware and the labels expected by the parsers
+    pub labels: Vec<(String, String)>,
+    /// Form field names used by this crate and those of the firmware
+    pub fields: Vec<(String, String)>,
+}
+
+impl FirmwareProfile {
+    pub fn new(since: FWVersion) -> Self {
+        FirmwareProfile { since, labels: Vec::new(), fields: Vec::new() }
+    }
+
+    /// Map the firmware row `label` to the row label `key` expected by the parsers
+    pub fn label(mut self, label: &str, key: &str) -> Self {
+        self.labels.push((label.to_string(), key.to_string()));
+        self
+    }
+
+    /// Send the form field `field` as `name`
+    pub fn field(mut self, field: &str, name: &str) -> Self {
+        self.fields.push((field.to_string(), name.to_string()));
+        self
+    }
+}
+
+fn profiles() -> &'static RwLock<Vec<FirmwareProfile>> {
+    static PROFILES: OnceLock<RwLock<Vec<FirmwareProfile>>> = OnceLock::new();
+    PROFILES.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Register a profile, replacing one for the same `since` version
+pub fn register_profile(profile: FirmwareProfile) {
+    let mut profiles = profiles().write().unwrap();
+    profiles.retain(|p| p.since != profile.since);
+    profiles.push(profile);
+}
+
+/// Remove all registered profiles
+pub fn clear_profiles() {
+    profiles().write().unwrap().clear();
+}
+
+/// Profile for a PEM firmware version: the registered profile with the
+/// newest `since` version not newer than `version`
+pub fn profile(version: FWVersion) -> Option<FirmwareProfile> {
+    profiles().read().unwrap().iter()
+        .filter(|p| p.since <= version)
+        .max_by_key(|p| p.since)
+        .cloned()
+}
+
+impl<H> MPX<H> {
+    /// Detected or configured PEM firmware version
+    pub fn firmware(&self) -> Option<FWVersion> {
+        *self.inner.firmware.lock().unwrap()
+    }
+
+    /// Take the firmware version from a PDU information page, unless
+    /// it has been configured
+    pub(crate) fn detect_firmware(&self, html: &str) {
+        if self.inner.firmware_pinned {
+            return;
+        }
+        if let Ok(Some(hardware)) = parse_pdu_info_partial(html.to_string()).map(|info| info.hardware) {
+            *self.inner.firmware.lock().unwrap() = Some(hardware.fw_version);
+        }
+    }
+
+    /// Profile of the device firmware, if one is registered
+    pub(crate) fn firmware_profile(&self) -> Option<FirmwareProfile> {
+        self.firmware().and_then(profile)
+    }
+
+    /// Rename the form fields of a POST for the device firmware
+    pub(crate) fn form_fields(&self, mut request: Request) -> Request {
+        if let Some(profile) = self.firmware_profile() {
+            for (name, _) in request.form.iter_mut() {
+                if let Some((_, renamed)) = profile.fields.iter().find(|(field, _)| field == name) {
+                    *name = renamed.clone();
+                }
+            }
+        }
+        request
+    }
+}
+
+impl<H: HttpTransport> MPX<H> {
+    /// Read the PEM and BRM firmware versions of a PDU and report which
+    /// crate features are known to work with them
+    ///
+    /// The management card (agent) firmware version is not part of the
+    /// pages parsed by this crate and is therefore not taken into account.
+    pub async fn check_compatibility(&self, pdu: u8) -> Result<CompatibilityReport, MPXError> {
+        let mut modules = Vec::new();
+
+        let info = self.get_info_pdu(pdu).await?;
+        modules.push(ModuleFirmware {
+            module: Module::PEM,
+            pdu,
+            branch: None,
+            version: info.hardware.fw_version,
+        });
+
+        let mut branches: Vec<u8> = self.get_receptacles().await?.iter()
+            .filter(|r| r.pdu == pdu)
+            .map(|r| r.branch)
+            .collect();
+        branches.sort_unstable();
+        branches.dedup();
+
+        for branch in branches {
+            let info = self.get_info_branch((pdu, branch)).await?;
+            modules.push(ModuleFirmware {
+                module: Module::BRM,
+                pdu,
+                branch: Some(branch),
+                version: info.hardware.fw_version,
+            });
+        }
+
+        Ok(CompatibilityReport::new(modules))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compatibility_report() {
+        let pem = ModuleFirmware { module: Module::PEM, pdu: 1, branch: None, version: FWVersion { p0: 0, p1: 3, p2: 4, p3: 2 } };
+        let brm = ModuleFirmware { module: Module::BRM, pdu: 1, branch: Some(1), version: FWVersion { p0: 0, p1: 3, p2: 4, p3: 0 } };
+        let new_brm = ModuleFirmware { branch: Some(2), version: FWVersion { p0: 0, p1: 4, p2: 0, p3: 0 }, ..brm.clone() };
+
+        let report = CompatibilityReport::new(vec![pem.clone(), brm.clone()]);
+        assert_eq!(report.support(Feature::Settings), Support::KnownGood);
+        assert_eq!(report.support(Feature::ReceptacleControl), Support::KnownGood);
+
+        let report = CompatibilityReport::new(vec![pem, brm, new_brm]);
+        assert_eq!(report.support(Feature::Settings), Support::Untested);
+        assert_eq!(report.support(Feature::Events), Support::KnownGood);
+    }
+
+    #[tokio::test]
+    async fn test_firmware_profile() {
+        use crate::transport::{Method, Response};
+        use std::sync::Mutex;
+
+        struct Device(Mutex<Vec<(String, String)>>);
+
+        impl HttpTransport for Device {
+            async fn send(&self, request: Request) -> Result<Response, MPXError> {
+                if request.method == Method::Post {
+                    *self.0.lock().unwrap() = request.form.clone();
+                }
+                let body = include_str!("../testdata/pdu-info.htm")
+                    .replace(">0-3-4-2<", ">0-9-1-0<")
+                    .replace(">PDU Total Input Power<", ">PDU Total Power<");
+                Ok(Response { status: 200, url: request.url, headers: Vec::new(), body })
+            }
+        }
+
+        register_profile(FirmwareProfile::new(FWVersion { p0: 0, p1: 9, p2: 0, p3: 0 })
+            .label("PDU Total Power", "PDU Total Input Power")
+            .field("ecThresholdHiAlmL1", "ecThresholdHighAlarmL1"));
+        assert_eq!(profile(FWVersion { p0: 0, p1: 3, p2: 4, p3: 2 }), None);
+
+        let mpx = MPX::builder("pdu1").build_with_transport(Device(Mutex::new(Vec::new())));
+        let info = mpx.get_info_pdu(1).await.unwrap();
+        assert_eq!(mpx.firmware(), Some(FWVersion { p0: 0, p1: 9, p2: 1, p3: 0 }));
+        assert_eq!(info.status.input_power, 9.6);
+
+        mpx.set_pdu_settings(1, &info.settings).await.unwrap();
+        let form = mpx.inner.transport.0.lock().unwrap().clone();
+        assert!(form.iter().any(|(name, _)| name == "ecThresholdHighAlarmL1"));
+        assert!(!form.iter().any(|(name, _)| name == "ecThresholdHiAlmL1"));
+
+        // a configured firmware version is not overridden by detection
+        let mpx = MPX::builder("pdu1").firmware(FWVersion { p0: 0, p1: 3, p2: 4, p3: 2 }).build_with_transport(Device(Mutex::new(Vec::new())));
+        assert!(mpx.get_info_pdu(1).await.is_err());
+        assert_eq!(mpx.firmware(), Some(FWVersion { p0: 0, p1: 3, p2: 4, p3: 2 }));
+    }
+}
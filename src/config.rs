@@ -0,0 +1,334 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Client configuration from environment variables or a config file
+//!
+//! Connection options can be read from `MPX_*` environment variables
+//! ([`MPX::from_env`]) or from a small TOML file
+//! ([`MPX::from_config_file`]). The file only supports top-level keys
+//! with string, integer, boolean and string array values:
+//!
+//! ```text
+//! # /etc/liebert-mpx.toml
+//! host = "192.168.23.42"
+//! username = "Liebert"
+//! password_file = "/run/secrets/pdu"
+//! scheme = "https"
+//! timeout = 10
+//! fallback_hosts = ["10.0.23.42"]
+//! ```
+//!
+//! | key               | environment variable  | meaning                          |
+//! |-------------------|-----------------------|----------------------------------|
+//! | `host`            | `MPX_HOST`            | host name or address (required)  |
+//! | `username`        | `MPX_USERNAME`        | login name                       |
+//! | `password`        | `MPX_PASSWORD`        | password                         |
+//! | `password_file`   | `MPX_PASSWORD_FILE`   | file containing the password     |
+//! | `scheme`          | `MPX_SCHEME`          | `http` or `https`                |
+//! | `port`            | `MPX_PORT`            | TCP port                         |
+//! | `connect_timeout` | `MPX_CONNECT_TIMEOUT` | connect timeout in seconds       |
+//! | `timeout`         | `MPX_TIMEOUT`         | request timeout in seconds       |
+//! | `deadline`        | `MPX_DEADLINE`        | operation deadline in seconds    |
+//! | `user_agent`      | `MPX_USER_AGENT`      | User-Agent header                |
+//! | `fallback_hosts`  | `MPX_FALLBACK_HOSTS`  | secondary addresses (env: comma separated) |
+
+use crate::{MPX, MPXBuilder};
+use std::convert::TryFrom;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Clone,Default,PartialEq)]
+/// Connection options of a [`MPX`]
+pub struct Config {
+    pub host: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub password_file: Option<String>,
+    pub scheme: Option<String>,
+    pub port: Option<u16>,
+    /// Seconds
+    pub connect_timeout: Option<u64>,
+    /// Seconds
+    pub timeout: Option<u64>,
+    /// Seconds
+    pub deadline: Option<u64>,
+    pub user_agent: Option<String>,
+    pub fallback_hosts: Vec<String>,
+}
+
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Config")
+            .field("host", &self.host)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("password_file", &self.password_file)
+            .field("scheme", &self.scheme)
+            .field("port", &self.port)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("timeout", &self.timeout)
+            .field("deadline", &self.deadline)
+            .field("user_agent", &self.user_agent)
+            .field("fallback_hosts", &self.fallback_hosts)
+            .finish()
+    }
+}
+
+fn invalid(line: usize, msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("line {}: {}", line, msg))
+}
+
+#[derive(Clone,Debug,PartialEq)]
+enum Value {
+    String(String),
+    Integer(u64),
+    Boolean(bool),
+    Array(Vec<String>),
+}
+
+/// Parse a basic string starting at `input`, returns the string and the rest
+fn parse_string(input: &str) -> Option<(String, &str)> {
+    let mut result = String::new();
+    let mut chars = input.strip_prefix('"')?.char_indices();
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '"' => return Some((result, &input[pos + 2..])),
+            '\\' => match chars.next()?.1 {
+                'n' => result.push('\n'),
+                't' => result.push('\t'),
+                c @ ('"' | '\\') => result.push(c),
+                _ => return None,
+            },
+            c => result.push(c),
+        }
+    }
+    None
+}
+
+fn strip_comment(rest: &str) -> Option<()> {
+    let rest = rest.trim();
+    if rest.is_empty() || rest.starts_with('#') {
+        Some(())
+    } else {
+        None
+    }
+}
+
+fn parse_value(input: &str) -> Option<Value> {
+    let input = input.trim();
+    if input.starts_with('"') {
+        let (value, rest) = parse_string(input)?;
+        strip_comment(rest)?;
+        return Some(Value::String(value));
+    }
+
+    if let Some(mut rest) = input.strip_prefix('[') {
+        let mut values = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(rest) = rest.strip_prefix(']') {
+                strip_comment(rest)?;
+                return Some(Value::Array(values));
+            }
+            let (value, tail) = parse_string(rest)?;
+            values.push(value);
+            rest = tail.trim_start();
+            rest = rest.strip_prefix(',').unwrap_or(rest);
+        }
+    }
+
+    let value = input.split('#').next()?.trim();
+    match value {
+        "true" => Some(Value::Boolean(true)),
+        "false" => Some(Value::Boolean(false)),
+        _ => value.replace('_', "").parse().ok().map(Value::Integer),
+    }
+}
+
+impl Config {
+    /// Read the options from `MPX_*` environment variables
+    pub fn from_env() -> std::io::Result<Self> {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Read the options from `MPX_*` variables of `vars`
+    pub fn from_vars<I: IntoIterator<Item = (String, String)>>(vars: I) -> std::io::Result<Self> {
+        let mut config = Config::default();
+        let number = |key: &str, value: &str| value.parse::<u64>()
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{}: invalid number", key)));
+
+        for (key, value) in vars {
+            match key.as_str() {
+                "MPX_HOST" => config.host = Some(value),
+                "MPX_USERNAME" => config.username = Some(value),
+                "MPX_PASSWORD" => config.password = Some(value),
+                "MPX_PASSWORD_FILE" => config.password_file = Some(value),
+                "MPX_SCHEME" => config.scheme = Some(value),
+                "MPX_PORT" => config.port = Some(value.parse()
+                    .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "MPX_PORT: invalid port"))?),
+                "MPX_CONNECT_TIMEOUT" => config.connect_timeout = Some(number(&key, &value)?),
+                "MPX_TIMEOUT" => config.timeout = Some(number(&key, &value)?),
+                "MPX_DEADLINE" => config.deadline = Some(number(&key, &value)?),
+                "MPX_USER_AGENT" => config.user_agent = Some(value),
+                "MPX_FALLBACK_HOSTS" => config.fallback_hosts = value.split(',')
+                    .map(|h| h.trim())
+                    .filter(|h| !h.is_empty())
+                    .map(String::from)
+                    .collect(),
+                _ => {},
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Read the options from a TOML file
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse the options from a TOML document
+    pub fn from_toml(input: &str) -> std::io::Result<Self> {
+        let mut config = Config::default();
+
+        for (no, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let pos = line.find('=').ok_or_else(|| invalid(no + 1, "expected key = value"))?;
+            let key = line[..pos].trim();
+            let value = parse_value(&line[pos + 1..]).ok_or_else(|| invalid(no + 1, "invalid value"))?;
+
+            let string = || match &value {
+                Value::String(s) => Ok(s.clone()),
+                _ => Err(invalid(no + 1, &format!("{} must be a string", key))),
+            };
+            let integer = || match &value {
+                Value::Integer(i) => Ok(*i),
+                _ => Err(invalid(no + 1, &format!("{} must be an integer", key))),
+            };
+
+            match key {
+                "host" => config.host = Some(string()?),
+                "username" => config.username = Some(string()?),
+                "password" => config.password = Some(string()?),
+                "password_file" => config.password_file = Some(string()?),
+                "scheme" => config.scheme = Some(string()?),
+                "port" => config.port = Some(u16::try_from(integer()?).map_err(|_| invalid(no + 1, "invalid port"))?),
+                "connect_timeout" => config.connect_timeout = Some(integer()?),
+                "timeout" => config.timeout = Some(integer()?),
+                "deadline" => config.deadline = Some(integer()?),
+                "user_agent" => config.user_agent = Some(string()?),
+                "fallback_hosts" => match &value {
+                    Value::Array(hosts) => config.fallback_hosts = hosts.clone(),
+                    _ => return Err(invalid(no + 1, "fallback_hosts must be an array of strings")),
+                },
+                _ => return Err(invalid(no + 1, &format!("unknown key {}", key))),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Builder with all configured options applied
+    pub fn builder(&self) -> std::io::Result<MPXBuilder> {
+        let host = self.host.as_ref()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "no host configured"))?;
+
+        let password = match (&self.password, &self.password_file) {
+            (_, Some(file)) => std::fs::read_to_string(file)?.trim_end_matches(['\r', '\n']).to_string(),
+            (Some(password), None) => password.clone(),
+            (None, None) => String::new(),
+        };
+
+        let mut builder = MPX::builder(host).credentials(self.username.as_deref().unwrap_or(""), &password);
+        if let Some(scheme) = &self.scheme {
+            builder = builder.scheme(scheme);
+        }
+        if let Some(port) = self.port {
+            builder = builder.port(port);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(Duration::from_secs(timeout));
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        if let Some(deadline) = self.deadline {
+            builder = builder.deadline(Duration::from_secs(deadline));
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        for host in &self.fallback_hosts {
+            builder = builder.fallback_host(host);
+        }
+        Ok(builder)
+    }
+
+    /// Create a client with the configured options
+    pub fn build(&self) -> std::io::Result<MPX> {
+        self.builder()?.build().map_err(|e| std::io::Error::other(format!("{}", e)))
+    }
+}
+
+impl MPX {
+    /// Create a client configured by `MPX_*` environment variables, see
+    /// [`config`](crate::config)
+    pub fn from_env() -> std::io::Result<Self> {
+        Config::from_env()?.build()
+    }
+
+    /// Create a client configured by a TOML file, see [`config`](crate::config)
+    pub fn from_config_file<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Config::open(path)?.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let config = Config::from_toml(r#"
+            # PDU in rack 4
+            host = "192.168.23.42"
+            username = "Liebert"
+            password = "se\"cret" # inline comment
+            port = 8_443
+            timeout = 10
+            fallback_hosts = ["10.0.23.42", "10.1.23.42"]
+        "#).unwrap();
+        assert_eq!(config.host.as_deref(), Some("192.168.23.42"));
+        assert_eq!(config.password.as_deref(), Some("se\"cret"));
+        assert_eq!(config.port, Some(8443));
+        assert_eq!(config.timeout, Some(10));
+        assert_eq!(config.fallback_hosts, vec!["10.0.23.42", "10.1.23.42"]);
+        assert!(!format!("{:?}", config).contains("cret"));
+
+        assert!(Config::from_toml("host = 42").is_err());
+        assert!(Config::from_toml("hots = \"pdu1\"").is_err());
+        assert!(Config::from_toml("host = \"pdu1").is_err());
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let vars = vec![
+            ("MPX_HOST".to_string(), "pdu1".to_string()),
+            ("MPX_SCHEME".to_string(), "https".to_string()),
+            ("MPX_FALLBACK_HOSTS".to_string(), "pdu1-b, pdu1-c".to_string()),
+            ("HOME".to_string(), "/root".to_string()),
+        ];
+        let config = Config::from_vars(vars).unwrap();
+        assert_eq!(config.host.as_deref(), Some("pdu1"));
+        assert_eq!(config.fallback_hosts, vec!["pdu1-b", "pdu1-c"]);
+        assert_eq!(config.build().unwrap().url("/x"), "https://pdu1/x");
+
+        assert!(Config::default().build().is_err());
+        assert!(Config::from_vars(vec![("MPX_PORT".to_string(), "http".to_string())]).is_err());
+    }
+}
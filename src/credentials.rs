@@ -0,0 +1,155 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Credential providers
+//!
+//! By default a [`MPX`] uses the static credentials given to
+//! [`MPX::new`] or [`MPXBuilder::credentials`](crate::MPXBuilder::credentials).
+//! With [`MPXBuilder::credential_provider`](crate::MPXBuilder::credential_provider)
+//! the credentials are fetched from a [`CredentialProvider`] for every
+//! request instead, e.g. from a secret store with rotating passwords.
+//! Providers are expected to cache credentials themselves.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::MPXError;
+//! use liebert::credentials::{CredentialProvider, Credentials, CredentialsFuture};
+//!
+//! /// Password rotated by an external agent
+//! struct PasswordFile(String);
+//!
+//! impl CredentialProvider for PasswordFile {
+//!     fn credentials(&self) -> CredentialsFuture<'_> {
+//!         Box::pin(async move {
+//!             let password = std::fs::read_to_string(&self.0).map_err(|_| MPXError::InvalidDataError(liebert::InvalidDataError::default()))?;
+//!             Ok(Credentials::new("Liebert", password.trim()))
+//!         })
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::builder("192.168.23.42")
+//!         .credential_provider(PasswordFile("/run/secrets/pdu".to_string()))
+//!         .build()
+//!         .unwrap();
+//! }
+//! ```
+
+use crate::{MPX, MPXError};
+use std::future::Future;
+use std::pin::Pin;
+
+#[derive(Clone,Default,PartialEq)]
+/// Password, redacted in `Debug` output and zeroized on drop
+pub(crate) struct Secret(zeroize::Zeroizing<String>);
+
+impl Secret {
+    pub(crate) fn new(secret: &str) -> Self {
+        Secret(zeroize::Zeroizing::new(secret.to_string()))
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "<redacted>")
+    }
+}
+
+#[derive(Clone,Debug,Default,PartialEq)]
+/// Username and password of the web interface
+pub struct Credentials {
+    username: String,
+    password: Secret,
+}
+
+impl Credentials {
+    pub fn new(username: &str, password: &str) -> Self {
+        Credentials {
+            username: username.to_string(),
+            password: Secret::new(password),
+        }
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn password(&self) -> &str {
+        self.password.as_str()
+    }
+}
+
+/// Future returned by [`CredentialProvider::credentials`]
+pub type CredentialsFuture<'a> = Pin<Box<dyn Future<Output = Result<Credentials, MPXError>> + Send + 'a>>;
+
+/// Source of the credentials used for requests
+pub trait CredentialProvider: Send + Sync {
+    /// Credentials for the next request
+    fn credentials(&self) -> CredentialsFuture<'_>;
+}
+
+impl CredentialProvider for Credentials {
+    fn credentials(&self) -> CredentialsFuture<'_> {
+        Box::pin(async move { Ok(self.clone()) })
+    }
+}
+
+/// Credential provider of a [`MPX`]
+pub(crate) struct Provider(pub(crate) Box<dyn CredentialProvider>);
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "CredentialProvider")
+    }
+}
+
+impl Default for Provider {
+    fn default() -> Self {
+        Provider(Box::new(Credentials::default()))
+    }
+}
+
+impl<H> MPX<H> {
+    /// Credentials for the next request
+    pub(crate) async fn credentials(&self) -> Result<Credentials, MPXError> {
+        self.inner.credentials.0.credentials().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Hands out a new password on every call
+    struct Rotating(AtomicU32);
+
+    impl CredentialProvider for Rotating {
+        fn credentials(&self) -> CredentialsFuture<'_> {
+            let n = self.0.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move { Ok(Credentials::new("Liebert", &format!("secret{}", n))) })
+        }
+    }
+
+    #[test]
+    fn test_credentials_debug() {
+        let credentials = Credentials::new("Liebert", "hunter2");
+        assert_eq!(format!("{:?}", credentials), r#"Credentials { username: "Liebert", password: <redacted> }"#);
+        assert_eq!(credentials.password(), "hunter2");
+    }
+
+    #[tokio::test]
+    async fn test_provider() {
+        let mpx = MPX::builder("pdu1").credential_provider(Rotating(AtomicU32::new(0))).build().unwrap();
+        assert_eq!(mpx.credentials().await.unwrap().password(), "secret0");
+        assert_eq!(mpx.credentials().await.unwrap().password(), "secret1");
+
+        let mpx = MPX::new("pdu1", "Liebert", "Liebert");
+        assert_eq!(mpx.credentials().await.unwrap(), Credentials::new("Liebert", "Liebert"));
+    }
+}
@@ -0,0 +1,85 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Anti-CSRF tokens on control forms
+//!
+//! Some firmware versions put a hidden token into the `rpcControl*`
+//! forms and silently ignore POSTs which do not echo it back. With
+//! [`MPXBuilder::csrf_token_field`](crate::MPXBuilder::csrf_token_field)
+//! the form page is fetched before every command or settings write and
+//! the token is submitted along with the other parameters.
+//!
+//! The form page is derived from the POST target by dropping the
+//! `Command`/`Setting` suffix, e.g. `rpcControlApsCommand` is
+//! submitted from `rpcControlAps.htm`. If the page has no token the
+//! POST is sent without one.
+
+use crate::{parse_dom, MPX, MPXError};
+use crate::transport::HttpTransport;
+
+/// Form page containing the form posted to `url`, keeping its query
+pub fn form_page(url: &str) -> String {
+    let (path, query) = match url.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (url, None),
+    };
+    let base = path.strip_suffix("Command")
+        .or_else(|| path.strip_suffix("Setting"))
+        .unwrap_or(path);
+    match query {
+        Some(query) => format!("{}.htm?{}", base, query),
+        None => format!("{}.htm", base),
+    }
+}
+
+fn find_input(nodes: &[html_parser::Node], field: &str) -> Option<String> {
+    for node in nodes {
+        if let html_parser::Node::Element(element) = node {
+            let attr = |name: &str| element.attributes.get(name).cloned().flatten();
+            if element.name.eq_ignore_ascii_case("input") && attr("name").as_deref() == Some(field) {
+                return Some(attr("value").unwrap_or_default());
+            }
+            if let Some(value) = find_input(&element.children, field) {
+                return Some(value);
+            }
+        }
+    }
+    None
+}
+
+/// Extract the value of the (hidden) input `field` from a form page
+pub fn extract_token(html: &str, field: &str) -> Option<String> {
+    let dom = parse_dom(html).ok()?;
+    find_input(&dom.children, field)
+}
+
+impl<H: HttpTransport> MPX<H> {
+    /// Fetch the token for a POST to `url`, if the form page has one
+    pub(crate) async fn csrf_token(&self, url: &str, field: &str) -> Result<Option<String>, MPXError> {
+        let html = self.get_page_inner(form_page(url)).await?;
+        Ok(extract_token(&html, field))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_form_page() {
+        assert_eq!(form_page("http://pdu/dp/std:1.0.0_0.0.0/rpc/rpcControlApsCommand"), "http://pdu/dp/std:1.0.0_0.0.0/rpc/rpcControlAps.htm");
+        assert_eq!(form_page("http://pdu/rpc/rpcControlRemSetting"), "http://pdu/rpc/rpcControlRem.htm");
+        assert_eq!(form_page("http://pdu/rpc/rpcControlReceptacleCommand?rcp=1-1-2"), "http://pdu/rpc/rpcControlReceptacle.htm?rcp=1-1-2");
+    }
+
+    #[test]
+    fn test_extract_token() {
+        let html = r#"<html><body><form method="POST" action="rpcControlApsCommand">
+            <input type="hidden" name="nonce" value="a1b2c3">
+            <input type="submit" name="testEvent" value="Send">
+            </form></body></html>"#;
+        assert_eq!(extract_token(html, "nonce"), Some("a1b2c3".to_string()));
+        assert_eq!(extract_token(html, "csrf"), None);
+    }
+}
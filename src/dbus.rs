@@ -0,0 +1,255 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! D-Bus service
+//!
+//! Exports the PDUs and receptacles of a device as D-Bus objects below
+//! `/io/mainframe/LiebertMPX`, so lab tooling and systemd units can read
+//! power values and switch outlets without any HTTP glue:
+//!
+//! ```text
+//! busctl --user call io.mainframe.LiebertMPX \
+//!     /io/mainframe/LiebertMPX/pdu1/branch2/receptacle3 \
+//!     io.mainframe.LiebertMPX.Receptacle1 Reboot
+//! ```
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use std::sync::Arc;
+//!
+//! fn main() {
+//!     let pdu = Arc::new(liebert::MPX::new("192.168.23.42", "Liebert", "Liebert"));
+//!     async {
+//!         let _connection = liebert::dbus::serve(pdu, liebert::dbus::Bus::Session).await.unwrap();
+//!         std::future::pending::<()>().await;
+//!     };
+//! }
+//! ```
+
+use crate::{MPX, MPXError, ReceptacleCmd, ReceptacleInfo};
+use std::sync::Arc;
+use zbus::fdo;
+
+/// Well-known bus name requested by the service
+pub const BUS_NAME: &str = "io.mainframe.LiebertMPX";
+/// Object path prefix of all exported objects
+pub const BASE_PATH: &str = "/io/mainframe/LiebertMPX";
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+/// Message bus to register the service on
+pub enum Bus {
+    Session,
+    System,
+}
+
+fn to_fdo(e: MPXError) -> fdo::Error {
+    match e {
+        MPXError::NotSupported(e) => fdo::Error::NotSupported(format!("{}", e)),
+        MPXError::InvalidAddress(e) => fdo::Error::InvalidArgs(format!("{}", e)),
+        MPXError::InvalidSetting(e) => fdo::Error::InvalidArgs(format!("{}", e)),
+        e => fdo::Error::Failed(format!("{}", e)),
+    }
+}
+
+/// Receptacle measurement, not available on elementary and branch monitored modules
+fn measured(value: Option<f32>) -> fdo::Result<f64> {
+    value.map(f64::from).ok_or_else(|| fdo::Error::NotSupported("receptacle is not measured".to_string()))
+}
+
+/// Object path of a PDU
+pub fn pdu_path(pdu: u8) -> String {
+    format!("{}/pdu{}", BASE_PATH, pdu)
+}
+
+/// Object path of a receptacle
+pub fn receptacle_path(pdu: u8, branch: u8, receptacle: u8) -> String {
+    format!("{}/pdu{}/branch{}/receptacle{}", BASE_PATH, pdu, branch, receptacle)
+}
+
+/// D-Bus object for a PDU input module
+pub struct PduObject {
+    mpx: Arc<MPX>,
+    pdu: u8,
+}
+
+#[zbus::interface(name = "io.mainframe.LiebertMPX.Pdu1")]
+impl PduObject {
+    /// Reset the accumulated energy counter
+    async fn reset_energy(&self) -> fdo::Result<()> {
+        self.mpx.pdu_reset_energy(self.pdu).await.map_err(to_fdo)
+    }
+
+    /// Send a test event
+    async fn test_event(&self) -> fdo::Result<()> {
+        self.mpx.pdu_test_event(self.pdu).await.map_err(to_fdo)
+    }
+
+    #[zbus(property)]
+    async fn label(&self) -> fdo::Result<String> {
+        Ok(self.mpx.get_info_pdu(self.pdu).await.map_err(to_fdo)?.settings.label)
+    }
+
+    #[zbus(property)]
+    async fn serial_number(&self) -> fdo::Result<String> {
+        Ok(self.mpx.get_info_pdu(self.pdu).await.map_err(to_fdo)?.hardware.serial_number)
+    }
+
+    /// Input power in W
+    #[zbus(property)]
+    async fn input_power(&self) -> fdo::Result<f64> {
+        Ok(self.mpx.get_info_pdu(self.pdu).await.map_err(to_fdo)?.status.input_power.into())
+    }
+
+    /// Accumulated energy in kWh
+    #[zbus(property)]
+    async fn accumulated_energy(&self) -> fdo::Result<f64> {
+        Ok(self.mpx.get_info_pdu(self.pdu).await.map_err(to_fdo)?.status.accumulated_energy.into())
+    }
+
+    /// Voltages L1-N, L2-N, L3-N in V AC (only L1-N on single phase PDUs)
+    #[zbus(property)]
+    async fn voltages(&self) -> fdo::Result<Vec<f64>> {
+        let status = self.mpx.get_info_pdu(self.pdu).await.map_err(to_fdo)?.status;
+        Ok([Some(status.voltage_l1_n), status.voltage_l2_n, status.voltage_l3_n].iter().flatten().map(|v| f64::from(*v)).collect())
+    }
+
+    /// Currents L1, L2, L3 in A AC (only L1 on single phase PDUs)
+    #[zbus(property)]
+    async fn currents(&self) -> fdo::Result<Vec<f64>> {
+        let status = self.mpx.get_info_pdu(self.pdu).await.map_err(to_fdo)?.status;
+        Ok([Some(status.current_l1), status.current_l2, status.current_l3].iter().flatten().map(|v| f64::from(*v)).collect())
+    }
+}
+
+/// D-Bus object for a receptacle
+pub struct ReceptacleObject {
+    mpx: Arc<MPX>,
+    pdu: u8,
+    branch: u8,
+    receptacle: u8,
+}
+
+impl ReceptacleObject {
+    async fn info(&self) -> fdo::Result<ReceptacleInfo> {
+        self.mpx.get_info_receptacle((self.pdu, self.branch, self.receptacle)).await.map_err(to_fdo)
+    }
+
+    async fn command(&self, cmd: ReceptacleCmd) -> fdo::Result<()> {
+        self.mpx.receptacle_command((self.pdu, self.branch, self.receptacle), cmd).await.map_err(to_fdo)
+    }
+}
+
+#[zbus::interface(name = "io.mainframe.LiebertMPX.Receptacle1")]
+impl ReceptacleObject {
+    /// Switch the receptacle on
+    async fn enable(&self) -> fdo::Result<()> {
+        self.command(ReceptacleCmd::Enable).await
+    }
+
+    /// Switch the receptacle off
+    async fn disable(&self) -> fdo::Result<()> {
+        self.command(ReceptacleCmd::Disable).await
+    }
+
+    /// Power cycle the receptacle
+    async fn reboot(&self) -> fdo::Result<()> {
+        self.command(ReceptacleCmd::Reboot).await
+    }
+
+    /// Blink the receptacle LED
+    async fn identify(&self) -> fdo::Result<()> {
+        self.command(ReceptacleCmd::Identify).await
+    }
+
+    /// Reset the accumulated energy counter
+    async fn reset_energy(&self) -> fdo::Result<()> {
+        self.command(ReceptacleCmd::ResetEnergy).await
+    }
+
+    #[zbus(property)]
+    async fn label(&self) -> fdo::Result<String> {
+        Ok(self.info().await?.settings.label)
+    }
+
+    #[zbus(property)]
+    async fn enabled(&self) -> fdo::Result<bool> {
+        Ok(self.info().await?.settings.power_state)
+    }
+
+    #[zbus(property)]
+    async fn locked(&self) -> fdo::Result<bool> {
+        Ok(self.info().await?.settings.control_lock_state)
+    }
+
+    /// Voltage in V AC
+    #[zbus(property)]
+    async fn voltage(&self) -> fdo::Result<f64> {
+        measured(self.info().await?.status.voltage)
+    }
+
+    /// Current in A AC
+    #[zbus(property)]
+    async fn current(&self) -> fdo::Result<f64> {
+        measured(self.info().await?.status.current)
+    }
+
+    /// Power in W
+    #[zbus(property)]
+    async fn power(&self) -> fdo::Result<f64> {
+        measured(self.info().await?.status.power)
+    }
+
+    /// Accumulated energy in kWh
+    #[zbus(property)]
+    async fn accumulated_energy(&self) -> fdo::Result<f64> {
+        measured(self.info().await?.status.accumulated_energy)
+    }
+}
+
+/// Export all PDUs and receptacles of `mpx` on the given bus
+///
+/// The objects stay registered as long as the returned connection is
+/// kept alive.
+pub async fn serve(mpx: Arc<MPX>, bus: Bus) -> Result<zbus::Connection, zbus::Error> {
+    let receptacles = mpx.get_receptacles().await.map_err(|e| zbus::Error::Failure(format!("{}", e)))?;
+
+    let builder = match bus {
+        Bus::Session => zbus::connection::Builder::session()?,
+        Bus::System => zbus::connection::Builder::system()?,
+    };
+    let mut builder = builder.name(BUS_NAME)?;
+
+    let mut pdus: Vec<u8> = receptacles.iter().map(|r| r.pdu).collect();
+    pdus.sort_unstable();
+    pdus.dedup();
+
+    for pdu in pdus {
+        builder = builder.serve_at(pdu_path(pdu), PduObject { mpx: mpx.clone(), pdu })?;
+    }
+
+    for r in receptacles.iter() {
+        let object = ReceptacleObject {
+            mpx: mpx.clone(),
+            pdu: r.pdu,
+            branch: r.branch,
+            receptacle: r.receptacle,
+        };
+        builder = builder.serve_at(receptacle_path(r.pdu, r.branch, r.receptacle), object)?;
+    }
+
+    builder.build().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_object_paths() {
+        assert_eq!(pdu_path(1), "/io/mainframe/LiebertMPX/pdu1");
+        assert_eq!(receptacle_path(1, 2, 3), "/io/mainframe/LiebertMPX/pdu1/branch2/receptacle3");
+        assert!(zbus::zvariant::ObjectPath::try_from(receptacle_path(1, 2, 3)).is_ok());
+    }
+}
@@ -0,0 +1,148 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Diagnostics for parse failures
+//!
+//! Parse errors only name the page, table and key. For bug reports the
+//! raw HTML is needed as well, which a [`ParseFailureSink`] configured
+//! with [`MPXBuilder::parse_failure_sink`](crate::MPXBuilder::parse_failure_sink)
+//! receives for every page that could not be parsed. [`DirectorySink`]
+//! stores the pages as files, closures can be used as sink as well.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::diagnostics::DirectorySink;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::builder("192.168.23.42")
+//!         .credentials("Liebert", "Liebert")
+//!         .parse_failure_sink(DirectorySink::new("/var/lib/pdu-exporter/failures"))
+//!         .build()
+//!         .unwrap();
+//! }
+//! ```
+
+use crate::{labels, ErrorKind, MPX, MPXError};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Receives pages which could not be parsed
+pub trait ParseFailureSink: Send + Sync {
+    /// Called with the page URL, the raw HTML and the parse error
+    fn parse_failure(&self, url: &str, html: &str, error: &MPXError);
+}
+
+impl<F: Fn(&str, &str, &MPXError) + Send + Sync> ParseFailureSink for F {
+    fn parse_failure(&self, url: &str, html: &str, error: &MPXError) {
+        self(url, html, error)
+    }
+}
+
+#[derive(Clone,Debug)]
+/// Stores each unparseable page as `<unix time>-<page>.htm` in a
+/// directory, with URL and error in a leading HTML comment
+pub struct DirectorySink {
+    dir: PathBuf,
+}
+
+impl DirectorySink {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        DirectorySink { dir: dir.into() }
+    }
+}
+
+/// File name component for a page URL, e.g. `rpc_rpcAps.htm` for
+/// `http://pdu1/dp/std:1.0.0_0.0.0/rpc/rpcAps.htm`
+pub(crate) fn page_name(url: &str) -> String {
+    let path = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let path = path.split_once('/').map(|(_, path)| path).unwrap_or("");
+    let path = path.split('?').next().unwrap_or(path);
+    let name: String = path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    match name.trim_matches('_') {
+        "" => "index".to_string(),
+        name => name.to_string(),
+    }
+}
+
+impl ParseFailureSink for DirectorySink {
+    fn parse_failure(&self, url: &str, html: &str, error: &MPXError) {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let path = self.dir.join(format!("{}-{}", time, page_name(url)));
+        let content = format!("<!-- url: {}\n     error: {} -->\n{}", url, error.to_string().replace("--", "- -"), html);
+        let _ = std::fs::create_dir_all(&self.dir);
+        if let Err(_e) = std::fs::write(&path, content) {
+            #[cfg(feature = "log")]
+            log::warn!(target: "liebert_mpx", error:% = _e; "could not store unparseable page {}: {}", path.display(), _e);
+        }
+    }
+}
+
+/// Parse failure sink of a [`MPX`]
+pub(crate) struct Sink(pub(crate) Box<dyn ParseFailureSink>);
+
+impl std::fmt::Debug for Sink {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "ParseFailureSink")
+    }
+}
+
+impl<H> MPX<H> {
+    /// Parse a fetched page, handing it to the parse failure sink if it
+    /// cannot be parsed
+    pub(crate) fn parse_page<T>(&self, url: &str, html: String, parse: fn(String) -> Result<T, MPXError>) -> Result<T, MPXError> {
+        let labels = self.firmware_profile().map(|profile| profile.labels).unwrap_or_default();
+        let parse = |html| labels::scoped(&labels, || parse(html));
+        let sink = match &self.inner.parse_failure_sink {
+            Some(sink) => sink,
+            None => return parse(html).map_err(|e| e.at_url(url)),
+        };
+
+        parse(html.clone()).map_err(|e| {
+            let e = e.at_url(url);
+            if e.kind() == ErrorKind::Parse {
+                sink.0.parse_failure(url, &html, &e);
+            }
+            e
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{HttpTransport, Request, Response};
+    use std::sync::{Arc, Mutex};
+
+    struct Page(&'static str);
+
+    impl HttpTransport for Page {
+        async fn send(&self, request: Request) -> Result<Response, MPXError> {
+            Ok(Response { status: 200, url: request.url, headers: Vec::new(), body: self.0.to_string() })
+        }
+    }
+
+    #[test]
+    fn test_page_name() {
+        assert_eq!(page_name("http://pdu1/dp/std:1.0.0_0.0.0/rpc/rpcAps.htm"), "dp_std_1.0.0_0.0.0_rpc_rpcAps.htm");
+        assert_eq!(page_name("http://pdu1/rpc/rpcActiveAlarms.htm?x=1"), "rpc_rpcActiveAlarms.htm");
+        assert_eq!(page_name("http://pdu1"), "index");
+    }
+
+    #[tokio::test]
+    async fn test_parse_failure_sink() {
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let recorded = failures.clone();
+        let mpx = MPX::builder("pdu1")
+            .parse_failure_sink(move |url: &str, html: &str, _: &MPXError| recorded.lock().unwrap().push((url.to_string(), html.to_string())))
+            .build_with_transport(Page("<html><body>maintenance</body></html>"));
+
+        assert!(mpx.get_info_pdu(1).await.is_err());
+        assert_eq!(*failures.lock().unwrap(), vec![(
+            "http://pdu1/dp/std:1.0.0_0.0.0/rpc/rpcAps.htm".to_string(),
+            "<html><body>maintenance</body></html>".to_string(),
+        )]);
+    }
+}
@@ -0,0 +1,360 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Snapshot diffing
+//!
+//! Compares two [`Snapshot`]s of the same PDU and lists what changed in
+//! between: receptacle power/lock state flips, readings which moved more
+//! than a configurable deadband, settings changes and events which were
+//! raised or cleared, e.g. to report label changes for a change audit:
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         let before = pdu.get_snapshot(1).await.unwrap();
+//!         let after = pdu.get_snapshot(1).await.unwrap();
+//!         for change in before.diff(&after) {
+//!             if let liebert::diff::ChangeKind::Setting { field: "label", old, new } = change.kind {
+//!                 println!("{:?}: {} -> {}", change.location, old, new);
+//!             }
+//!         }
+//!     };
+//! }
+//! ```
+
+use crate::{Event, Snapshot};
+use crate::snapshot::{BranchSnapshot, ReceptacleSnapshot};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Part of the PDU a change belongs to
+pub enum Location {
+    /// Power entry module
+    Pdu,
+    /// Branch module
+    Branch(u8),
+    /// Receptacle (branch, receptacle)
+    Receptacle(u8, u8),
+}
+
+impl Location {
+    /// Location referenced by an event
+    pub fn of_event(event: &Event) -> Self {
+        match (event.branch, event.receptacle) {
+            (0, _) => Location::Pdu,
+            (branch, 0) => Location::Branch(branch),
+            (branch, receptacle) => Location::Receptacle(branch, receptacle),
+        }
+    }
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Physical quantity of a reading, selects the deadband
+pub enum Quantity {
+    /// V AC
+    Voltage,
+    /// A AC
+    Current,
+    /// W or VA
+    Power,
+    /// kWh
+    Energy,
+    /// Hz
+    Frequency,
+    /// %
+    Utilization,
+    /// power factor or crest factor
+    Factor,
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Minimum change of a reading before it is reported
+pub struct Deadbands {
+    /// in V AC
+    pub voltage: f32,
+    /// in A AC
+    pub current: f32,
+    /// in W / VA
+    pub power: f32,
+    /// in kWh
+    pub energy: f32,
+    /// in Hz
+    pub frequency: f32,
+    /// in %
+    pub utilization: f32,
+    /// absolute change of power/crest factor
+    pub factor: f32,
+}
+
+impl Default for Deadbands {
+    fn default() -> Self {
+        Deadbands {
+            voltage: 2.0,
+            current: 0.2,
+            power: 20.0,
+            energy: 1.0,
+            frequency: 0.1,
+            utilization: 2.0,
+            factor: 0.05,
+        }
+    }
+}
+
+impl Deadbands {
+    /// Deadband for a quantity
+    pub fn get(&self, quantity: Quantity) -> f32 {
+        match quantity {
+            Quantity::Voltage => self.voltage,
+            Quantity::Current => self.current,
+            Quantity::Power => self.power,
+            Quantity::Energy => self.energy,
+            Quantity::Frequency => self.frequency,
+            Quantity::Utilization => self.utilization,
+            Quantity::Factor => self.factor,
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Kind of a change
+pub enum ChangeKind {
+    /// Branch module or receptacle appeared
+    Added,
+    /// Branch module or receptacle disappeared
+    Removed,
+    /// Receptacle switched on or off
+    PowerState { enabled: bool },
+    /// Receptacle control has been locked or unlocked
+    LockState { locked: bool },
+    /// Reading changed more than its deadband
+    Reading { field: &'static str, quantity: Quantity, old: f32, new: f32 },
+    /// Setting changed (values formatted for display)
+    Setting { field: &'static str, old: String, new: String },
+    /// Event became active
+    EventRaised(Event),
+    /// Event is no longer active
+    EventCleared(Event),
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Single change between two snapshots
+pub struct Change {
+    pub location: Location,
+    pub kind: ChangeKind,
+}
+
+/// List of changes between two snapshots
+pub type ChangeSet = Vec<Change>;
+
+impl Snapshot {
+    /// Changes from this snapshot to a newer one of the same PDU, using
+    /// the default deadbands
+    pub fn diff(&self, newer: &Snapshot) -> ChangeSet {
+        diff(self, newer)
+    }
+}
+
+struct Differ<'a> {
+    deadbands: &'a Deadbands,
+    changes: ChangeSet,
+}
+
+impl Differ<'_> {
+    fn reading(&mut self, location: Location, field: &'static str, quantity: Quantity, old: f32, new: f32) {
+        if (new - old).abs() >= self.deadbands.get(quantity) {
+            self.changes.push(Change { location, kind: ChangeKind::Reading { field, quantity, old, new } });
+        }
+    }
+
+    /// Reading which is missing on single phase PDUs (L2/L3) or on
+    /// receptacles without measurement
+    fn optional_reading(&mut self, location: Location, field: &'static str, quantity: Quantity, old: Option<f32>, new: Option<f32>) {
+        if let (Some(old), Some(new)) = (old, new) {
+            self.reading(location, field, quantity, old, new);
+        }
+    }
+
+    fn setting<T: PartialEq + std::fmt::Debug>(&mut self, location: Location, field: &'static str, old: &T, new: &T) {
+        if old != new {
+            let kind = ChangeKind::Setting { field, old: format!("{:?}", old), new: format!("{:?}", new) };
+            self.changes.push(Change { location, kind });
+        }
+    }
+
+    fn pdu(&mut self, old: &Snapshot, new: &Snapshot) {
+        let loc = Location::Pdu;
+        let (o, n) = (&old.info.status, &new.info.status);
+        self.reading(loc, "accumulated_energy", Quantity::Energy, o.accumulated_energy, n.accumulated_energy);
+        self.reading(loc, "input_power", Quantity::Power, o.input_power, n.input_power);
+        self.reading(loc, "voltage_l1_n", Quantity::Voltage, o.voltage_l1_n, n.voltage_l1_n);
+        self.optional_reading(loc, "voltage_l2_n", Quantity::Voltage, o.voltage_l2_n, n.voltage_l2_n);
+        self.optional_reading(loc, "voltage_l3_n", Quantity::Voltage, o.voltage_l3_n, n.voltage_l3_n);
+        self.reading(loc, "current_l1", Quantity::Current, o.current_l1, n.current_l1);
+        self.optional_reading(loc, "current_l2", Quantity::Current, o.current_l2, n.current_l2);
+        self.optional_reading(loc, "current_l3", Quantity::Current, o.current_l3, n.current_l3);
+        self.reading(loc, "current_n", Quantity::Current, o.current_n, n.current_n);
+        self.reading(loc, "current_utilization_l1", Quantity::Utilization, o.current_utilization_l1, n.current_utilization_l1);
+        self.optional_reading(loc, "current_utilization_l2", Quantity::Utilization, o.current_utilization_l2, n.current_utilization_l2);
+        self.optional_reading(loc, "current_utilization_l3", Quantity::Utilization, o.current_utilization_l3, n.current_utilization_l3);
+        self.reading(loc, "line_frequency", Quantity::Frequency, o.line_frequency, n.line_frequency);
+
+        let (o, n) = (&old.info.settings, &new.info.settings);
+        self.setting(loc, "label", &o.label, &n.label);
+        self.setting(loc, "asset_tag_1", &o.asset_tag_1, &n.asset_tag_1);
+        self.setting(loc, "asset_tag_2", &o.asset_tag_2, &n.asset_tag_2);
+        self.setting(loc, "n_over_current_alarm_threshold", &o.n_over_current_alarm_threshold, &n.n_over_current_alarm_threshold);
+        self.setting(loc, "n_over_current_warning_threshold", &o.n_over_current_warning_threshold, &n.n_over_current_warning_threshold);
+        self.setting(loc, "l1_low_current_alarm_threshold", &o.l1_low_current_alarm_threshold, &n.l1_low_current_alarm_threshold);
+        self.setting(loc, "l1_over_current_alarm_threshold", &o.l1_over_current_alarm_threshold, &n.l1_over_current_alarm_threshold);
+        self.setting(loc, "l1_over_current_warning_threshold", &o.l1_over_current_warning_threshold, &n.l1_over_current_warning_threshold);
+        self.setting(loc, "l2_low_current_alarm_threshold", &o.l2_low_current_alarm_threshold, &n.l2_low_current_alarm_threshold);
+        self.setting(loc, "l2_over_current_alarm_threshold", &o.l2_over_current_alarm_threshold, &n.l2_over_current_alarm_threshold);
+        self.setting(loc, "l2_over_current_warning_threshold", &o.l2_over_current_warning_threshold, &n.l2_over_current_warning_threshold);
+        self.setting(loc, "l3_low_current_alarm_threshold", &o.l3_low_current_alarm_threshold, &n.l3_low_current_alarm_threshold);
+        self.setting(loc, "l3_over_current_alarm_threshold", &o.l3_over_current_alarm_threshold, &n.l3_over_current_alarm_threshold);
+        self.setting(loc, "l3_over_current_warning_threshold", &o.l3_over_current_warning_threshold, &n.l3_over_current_warning_threshold);
+    }
+
+    fn branch(&mut self, old: &BranchSnapshot, new: &BranchSnapshot) {
+        let loc = Location::Branch(new.branch);
+        let (o, n) = (&old.info.status, &new.info.status);
+        self.reading(loc, "accumulated_energy", Quantity::Energy, o.accumulated_energy, n.accumulated_energy);
+        self.reading(loc, "voltage", Quantity::Voltage, o.voltage, n.voltage);
+        self.reading(loc, "current", Quantity::Current, o.current, n.current);
+        self.reading(loc, "current_utilization", Quantity::Utilization, o.current_utilization, n.current_utilization);
+        self.reading(loc, "power", Quantity::Power, o.power, n.power);
+        self.reading(loc, "apparent_power", Quantity::Power, o.apparent_power, n.apparent_power);
+        self.reading(loc, "power_factor", Quantity::Factor, o.power_factor, n.power_factor);
+
+        let (o, n) = (&old.info.settings, &new.info.settings);
+        self.setting(loc, "label", &o.label, &n.label);
+        self.setting(loc, "asset_tag_1", &o.asset_tag_1, &n.asset_tag_1);
+        self.setting(loc, "asset_tag_2", &o.asset_tag_2, &n.asset_tag_2);
+        self.setting(loc, "over_current_alarm_threshold", &o.over_current_alarm_threshold, &n.over_current_alarm_threshold);
+        self.setting(loc, "over_current_warning_threshold", &o.over_current_warning_threshold, &n.over_current_warning_threshold);
+        self.setting(loc, "low_current_alarm_threshold", &o.low_current_alarm_threshold, &n.low_current_alarm_threshold);
+    }
+
+    fn receptacle(&mut self, old: &ReceptacleSnapshot, new: &ReceptacleSnapshot) {
+        let loc = Location::Receptacle(new.branch, new.receptacle);
+        let (o, n) = (&old.info.settings, &new.info.settings);
+        if o.power_state != n.power_state {
+            self.changes.push(Change { location: loc, kind: ChangeKind::PowerState { enabled: n.power_state } });
+        }
+        if o.control_lock_state != n.control_lock_state {
+            self.changes.push(Change { location: loc, kind: ChangeKind::LockState { locked: n.control_lock_state } });
+        }
+        self.setting(loc, "label", &o.label, &n.label);
+        self.setting(loc, "asset_tag_1", &o.asset_tag_1, &n.asset_tag_1);
+        self.setting(loc, "asset_tag_2", &o.asset_tag_2, &n.asset_tag_2);
+        self.setting(loc, "over_current_alarm_threshold", &o.over_current_alarm_threshold, &n.over_current_alarm_threshold);
+        self.setting(loc, "over_current_warning_threshold", &o.over_current_warning_threshold, &n.over_current_warning_threshold);
+        self.setting(loc, "low_current_alarm_threshold", &o.low_current_alarm_threshold, &n.low_current_alarm_threshold);
+        self.setting(loc, "power_on_delay", &o.power_on_delay, &n.power_on_delay);
+
+        let (o, n) = (&old.info.status, &new.info.status);
+        self.optional_reading(loc, "accumulated_energy", Quantity::Energy, o.accumulated_energy, n.accumulated_energy);
+        self.optional_reading(loc, "voltage", Quantity::Voltage, o.voltage, n.voltage);
+        self.optional_reading(loc, "current", Quantity::Current, o.current, n.current);
+        self.optional_reading(loc, "current_utilization", Quantity::Utilization, o.current_utilization, n.current_utilization);
+        self.optional_reading(loc, "power", Quantity::Power, o.power, n.power);
+        self.optional_reading(loc, "apparent_power", Quantity::Power, o.apparent_power, n.apparent_power);
+        self.optional_reading(loc, "power_factor", Quantity::Factor, o.power_factor, n.power_factor);
+        self.optional_reading(loc, "current_crest_factor", Quantity::Factor, o.current_crest_factor, n.current_crest_factor);
+    }
+
+    fn events(&mut self, old: &[Event], new: &[Event]) {
+        for event in new.iter().filter(|e| !old.contains(e)) {
+            self.changes.push(Change { location: Location::of_event(event), kind: ChangeKind::EventRaised(event.clone()) });
+        }
+        for event in old.iter().filter(|e| !new.contains(e)) {
+            self.changes.push(Change { location: Location::of_event(event), kind: ChangeKind::EventCleared(event.clone()) });
+        }
+    }
+}
+
+/// Compare two snapshots using the default deadbands
+pub fn diff(old: &Snapshot, new: &Snapshot) -> ChangeSet {
+    diff_with(old, new, &Deadbands::default())
+}
+
+/// Compare two snapshots, ignoring reading changes below `deadbands`
+pub fn diff_with(old: &Snapshot, new: &Snapshot, deadbands: &Deadbands) -> ChangeSet {
+    let mut differ = Differ { deadbands, changes: Vec::new() };
+
+    differ.pdu(old, new);
+
+    for n in new.branches.iter() {
+        match old.branch(n.branch) {
+            Some(o) => differ.branch(o, n),
+            None => differ.changes.push(Change { location: Location::Branch(n.branch), kind: ChangeKind::Added }),
+        }
+    }
+    for o in old.branches.iter().filter(|o| new.branch(o.branch).is_none()) {
+        differ.changes.push(Change { location: Location::Branch(o.branch), kind: ChangeKind::Removed });
+    }
+
+    for n in new.receptacles.iter() {
+        match old.receptacle(n.branch, n.receptacle) {
+            Some(o) => differ.receptacle(o, n),
+            None => differ.changes.push(Change { location: Location::Receptacle(n.branch, n.receptacle), kind: ChangeKind::Added }),
+        }
+    }
+    for o in old.receptacles.iter().filter(|o| new.receptacle(o.branch, o.receptacle).is_none()) {
+        differ.changes.push(Change { location: Location::Receptacle(o.branch, o.receptacle), kind: ChangeKind::Removed });
+    }
+
+    differ.events(&old.events, &new.events);
+
+    differ.changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::tests::snapshot;
+    use crate::{EventLevel, EventType};
+
+    #[test]
+    fn test_diff() {
+        let old = snapshot();
+        assert!(diff(&old, &old).is_empty());
+
+        let mut new = old.clone();
+        new.info.status.input_power += 5.0;
+        new.info.status.current_l2 = new.info.status.current_l2.map(|current| current + 1.0);
+        new.receptacles[1].info.settings.power_state = !old.receptacles[1].info.settings.power_state;
+        new.receptacles[1].info.settings.label = "db-02".to_string();
+        new.receptacles.remove(0);
+        new.events.push(Event { level: EventLevel::ALARM, pdu: 1, branch: 1, receptacle: 0, event: EventType::BranchBreakerOpen });
+
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 5);
+        assert!(changes.contains(&Change {
+            location: Location::Pdu,
+            kind: ChangeKind::Reading { field: "current_l2", quantity: Quantity::Current, old: old.info.status.current_l2.unwrap(), new: new.info.status.current_l2.unwrap() },
+        }));
+        assert!(changes.contains(&Change { location: Location::Receptacle(1, 2), kind: ChangeKind::PowerState { enabled: new.receptacles[0].info.settings.power_state } }));
+        assert!(changes.contains(&Change {
+            location: Location::Receptacle(1, 2),
+            kind: ChangeKind::Setting { field: "label", old: format!("{:?}", old.receptacles[1].info.settings.label), new: "\"db-02\"".to_string() },
+        }));
+        assert!(changes.contains(&Change { location: Location::Receptacle(1, 1), kind: ChangeKind::Removed }));
+        assert!(matches!(changes.last().unwrap().kind, ChangeKind::EventRaised(_)));
+        assert_eq!(changes.last().unwrap().location, Location::Branch(1));
+
+        assert_eq!(old.diff(&new), changes);
+        assert!(new.diff(&new).is_empty());
+        assert_eq!(new.diff(&old).iter().filter(|c| matches!(c.kind, ChangeKind::EventCleared(_))).count(), 1);
+    }
+}
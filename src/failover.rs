@@ -0,0 +1,193 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Failover between management addresses
+//!
+//! Cards with two uplinks can be reached via several addresses. Hosts
+//! added with [`MPXBuilder::fallback_host`](crate::MPXBuilder::fallback_host)
+//! are tried in order when a request to the active address fails with a
+//! transport error (connection failure, timeout). POST requests only fail
+//! over if the connection could not be established, since a timed out
+//! command may still have been executed by the device. After a failover
+//! the primary address is probed again once `failback_after` has passed
+//! and used again as soon as it answers.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::builder("192.168.23.42")
+//!         .credentials("Liebert", "Liebert")
+//!         .fallback_host("10.0.23.42")
+//!         .build()
+//!         .unwrap();
+//! }
+//! ```
+
+use crate::{MPX, MPXError, ResponseTooLargeError};
+use crate::transport::{HttpTransport, Method, Request, Response};
+use std::time::{Duration, Instant};
+
+#[derive(Clone,Debug)]
+/// Failover state of a [`MPX`]
+pub(crate) struct Failover {
+    /// scheme, host and port of all addresses, primary first
+    bases: Vec<String>,
+    active: usize,
+    /// Time of the last switch away from the primary address
+    since: Option<Instant>,
+    failback_after: Duration,
+}
+
+impl Failover {
+    pub(crate) fn new(bases: Vec<String>, failback_after: Duration) -> Self {
+        Failover {
+            bases,
+            active: 0,
+            since: None,
+            failback_after,
+        }
+    }
+
+    /// Order in which the addresses should be tried at `now`
+    fn candidates(&self, now: Instant) -> Vec<usize> {
+        let mut result = Vec::new();
+        if self.probe_due(now) {
+            result.push(0);
+        }
+        for index in std::iter::once(self.active).chain(0..self.bases.len()) {
+            if !result.contains(&index) {
+                result.push(index);
+            }
+        }
+        result
+    }
+
+    fn probe_due(&self, now: Instant) -> bool {
+        matches!(self.since, Some(since) if now.duration_since(since) >= self.failback_after)
+    }
+
+    fn select(&mut self, index: usize, now: Instant) {
+        if index == 0 {
+            self.since = None;
+        } else if index != self.active || self.probe_due(now) {
+            // failed over, or the primary has been probed unsuccessfully
+            self.since = Some(now);
+        }
+        self.active = index;
+    }
+
+    /// Rewrite a URL of the primary address to address `index`
+    fn route(&self, url: &str, index: usize) -> String {
+        match url.strip_prefix(self.bases[0].as_str()) {
+            Some(path) => format!("{}{}", self.bases[index], path),
+            None => url.to_string(),
+        }
+    }
+}
+
+/// Check if a failed request may be sent to the next address
+fn may_fail_over(request: &Request, error: &MPXError) -> bool {
+    match (&request.method, error) {
+        (Method::Post, MPXError::Reqwest(e)) => e.is_connect(),
+        (Method::Post, _) => false,
+        _ => error.is_retryable(),
+    }
+}
+
+impl<H> MPX<H> {
+    /// Host name or address currently used for requests
+    pub fn active_host(&self) -> String {
+        let failover = self.inner.failover.lock().unwrap();
+        let base = &failover.bases[failover.active];
+        base.split_once("://").map(|(_, authority)| authority).unwrap_or(base).to_string()
+    }
+}
+
+impl<H: HttpTransport> MPX<H> {
+    /// Send a request to the active address, failing over to the other
+    /// addresses on transport errors
+    pub(crate) async fn exchange(&self, request: Request) -> Result<Response, MPXError> {
+        let _permit = self.throttle().await;
+        let candidates = self.inner.failover.lock().unwrap().candidates(Instant::now());
+
+        let mut last_error = None;
+        for index in candidates {
+            let mut routed = request.clone();
+            routed.url = self.inner.failover.lock().unwrap().route(&request.url, index);
+
+            match self.inner.transport.send(routed).await {
+                Err(e) if may_fail_over(&request, &e) => {
+                    #[cfg(feature = "log")]
+                    log::debug!(target: "liebert_mpx", device = self.inner.host.as_str(), error:% = e, outcome = "failover";
+                        "request to {} failed, trying next address", request.url);
+                    last_error = Some(e);
+                },
+                result => {
+                    self.inner.failover.lock().unwrap().select(index, Instant::now());
+                    return match (result, self.inner.max_response_size) {
+                        (Ok(response), Some(limit)) if response.body.len() > limit => Err(ResponseTooLargeError { limit }.into()),
+                        (result, _) => result,
+                    };
+                },
+            }
+        }
+
+        // there is always at least the primary address
+        Err(last_error.expect("no address tried"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failover() {
+        let mut failover = Failover::new(vec!["http://a".to_string(), "http://b".to_string(), "http://c".to_string()], Duration::from_secs(60));
+        let now = Instant::now();
+        assert_eq!(failover.candidates(now), vec![0, 1, 2]);
+        assert_eq!(failover.route("http://a/rpc/x.htm", 2), "http://c/rpc/x.htm");
+
+        failover.select(1, now);
+        assert_eq!(failover.candidates(now + Duration::from_secs(10)), vec![1, 0, 2]);
+
+        // failback probe, primary still down
+        let later = now + Duration::from_secs(60);
+        assert_eq!(failover.candidates(later), vec![0, 1, 2]);
+        failover.select(1, later);
+        assert_eq!(failover.candidates(later + Duration::from_secs(1)), vec![1, 0, 2]);
+
+        failover.select(0, later + Duration::from_secs(120));
+        assert_eq!(failover.candidates(later + Duration::from_secs(120)), vec![0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_post_timeout() {
+        use crate::TimeoutError;
+        use std::sync::Mutex;
+
+        struct Unreachable {
+            hosts: Mutex<Vec<String>>,
+        }
+
+        impl HttpTransport for Unreachable {
+            async fn send(&self, request: Request) -> Result<Response, MPXError> {
+                self.hosts.lock().unwrap().push(request.url);
+                Err(TimeoutError { deadline: None }.into())
+            }
+        }
+
+        let mpx = MPX::builder("a").fallback_host("b")
+            .build_with_transport(Unreachable { hosts: Mutex::new(Vec::new()) });
+
+        let result = mpx.exchange(Request::post(&mpx.url("/rpc/rpcControlReceptacleCommand"), &[("cmd", "off")])).await;
+        assert!(matches!(result, Err(MPXError::Timeout(_))));
+        assert_eq!(*mpx.inner.transport.hosts.lock().unwrap(), vec!["http://a/rpc/rpcControlReceptacleCommand"]);
+
+        mpx.inner.transport.hosts.lock().unwrap().clear();
+        assert!(mpx.exchange(Request::get(&mpx.url("/rpc/rpcAps.htm"))).await.is_err());
+        assert_eq!(*mpx.inner.transport.hosts.lock().unwrap(), vec!["http://a/rpc/rpcAps.htm", "http://b/rpc/rpcAps.htm"]);
+    }
+}
@@ -0,0 +1,402 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Fence agent for Pacemaker and other HA cluster managers
+//!
+//! Implements the standard fence-agent interface, i.e. options are
+//! taken from the command line (`--action=off --plug=db-01` or
+//! `-o off -n db-01`) or as `key=value` lines on stdin, and the result
+//! is reported via the process exit code. Plugs can be given as receptacle address
+//! (`1-2-3`) or as receptacle label. The `fence_liebert_mpx` binary
+//! is a thin wrapper around [`main`].
+
+use crate::{MPX, MPXError, ReceptacleListEntry};
+use std::io::BufRead;
+use std::time::Duration;
+
+/// Exit code for success (or plug powered on for `status`)
+pub const EXIT_SUCCESS: i32 = 0;
+/// Exit code for failures
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit code of `status` for a powered off plug
+pub const EXIT_OFF: i32 = 2;
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+/// Fence agent action
+pub enum FenceAction {
+    On,
+    Off,
+    Reboot,
+    Status,
+    Monitor,
+    List,
+    Metadata,
+    ValidateAll,
+}
+
+impl std::str::FromStr for FenceAction {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<FenceAction, Self::Err> {
+        match input {
+            "on" | "enable" => Ok(FenceAction::On),
+            "off" | "disable" => Ok(FenceAction::Off),
+            "reboot" => Ok(FenceAction::Reboot),
+            "status" => Ok(FenceAction::Status),
+            "monitor" => Ok(FenceAction::Monitor),
+            "list" => Ok(FenceAction::List),
+            "metadata" => Ok(FenceAction::Metadata),
+            "validate-all" => Ok(FenceAction::ValidateAll),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// Options passed to the fence agent
+pub struct FenceOptions {
+    pub action: FenceAction,
+    pub ip: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub plug: Option<String>,
+    /// Seconds to wait for the receptacle to reach the requested state
+    pub power_timeout: u64,
+}
+
+impl Default for FenceOptions {
+    fn default() -> Self {
+        FenceOptions {
+            action: FenceAction::Reboot,
+            ip: None,
+            username: None,
+            password: None,
+            plug: None,
+            power_timeout: 20,
+        }
+    }
+}
+
+impl FenceOptions {
+    /// Apply a single option, returns false for unknown options
+    fn apply(&mut self, key: &str, value: &str) -> Result<bool, String> {
+        match key {
+            "action" | "option" => {
+                self.action = value.parse().map_err(|_| format!("unknown action: {}", value))?;
+            },
+            "ip" | "ipaddr" => self.ip = Some(value.to_string()),
+            "username" | "login" => self.username = Some(value.to_string()),
+            "password" | "passwd" => self.password = Some(value.to_string()),
+            "plug" | "port" => self.plug = Some(value.to_string()),
+            "power_timeout" => {
+                self.power_timeout = value.parse().map_err(|_| format!("invalid power_timeout: {}", value))?;
+            },
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Apply a single option
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match self.apply(key, value)? {
+            true => Ok(()),
+            false => Err(format!("unknown option: {}", key)),
+        }
+    }
+
+    /// Parse `--key=value` / `--key value` and `-o value` command line
+    /// arguments; unknown options are rejected
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = FenceOptions::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (key, value) = if let Some(long) = arg.strip_prefix("--") {
+                match long.find('=') {
+                    Some(pos) => (long[..pos].replace('-', "_"), Some(long[pos + 1..].to_string())),
+                    None => (long.replace('-', "_"), None),
+                }
+            } else if let Some(short) = arg.strip_prefix('-') {
+                let key = match short.chars().next() {
+                    Some('o') => "action",
+                    Some('a') => "ip",
+                    Some('l') => "username",
+                    Some('p') => "password",
+                    Some('n') => "plug",
+                    _ => return Err(format!("unknown option: {}", arg)),
+                };
+                let value = &short[1..];
+                (key.to_string(), if value.is_empty() { None } else { Some(value.to_string()) })
+            } else {
+                return Err(format!("unexpected argument: {}", arg));
+            };
+
+            let value = match value {
+                Some(value) => value,
+                None => args.next().ok_or(format!("missing value for {}", arg))?,
+            };
+            options.set(&key, &value)?;
+        }
+
+        Ok(options)
+    }
+
+    /// Parse `key=value` lines as passed on stdin by the cluster manager;
+    /// unknown options are ignored like in other fence agents
+    pub fn from_lines<R: BufRead>(input: R) -> Result<Self, String> {
+        let mut options = FenceOptions::default();
+
+        for line in input.lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let pos = line.find('=').ok_or(format!("invalid option line: {}", line))?;
+            options.apply(line[..pos].trim(), line[pos + 1..].trim())?;
+        }
+
+        Ok(options)
+    }
+}
+
+/// Find the receptacle for a plug given as "pdu-branch-receptacle" or label
+pub fn find_plug<'a>(receptacles: &'a [ReceptacleListEntry], plug: &str) -> Option<&'a ReceptacleListEntry> {
+    receptacles.iter()
+        .find(|r| format!("{}-{}-{}", r.pdu, r.branch, r.receptacle) == plug)
+        .or_else(|| receptacles.iter().find(|r| r.label == plug))
+}
+
+/// Resource agent metadata as expected by `stonith_admin --metadata`
+pub fn metadata() -> String {
+    r#"<?xml version="1.0" ?>
+<resource-agent name="fence_liebert_mpx" shortdesc="Fence agent for Liebert MPX PDUs">
+<longdesc>fence_liebert_mpx switches receptacles of Liebert MPX power distribution units via the web interface of the management card. Plugs are given as pdu-branch-receptacle address (e.g. 1-2-3) or as receptacle label.</longdesc>
+<parameters>
+	<parameter name="action" unique="0" required="1">
+		<getopt mixed="-o, --action=[action]" />
+		<content type="string" default="reboot" />
+		<shortdesc lang="en">Fencing action</shortdesc>
+	</parameter>
+	<parameter name="ip" unique="0" required="1">
+		<getopt mixed="-a, --ip=[ip]" />
+		<content type="string" />
+		<shortdesc lang="en">IP address or hostname of fencing device</shortdesc>
+	</parameter>
+	<parameter name="username" unique="0" required="1">
+		<getopt mixed="-l, --username=[name]" />
+		<content type="string" />
+		<shortdesc lang="en">Login name</shortdesc>
+	</parameter>
+	<parameter name="password" unique="0" required="0">
+		<getopt mixed="-p, --password=[password]" />
+		<content type="string" />
+		<shortdesc lang="en">Login password or passphrase</shortdesc>
+	</parameter>
+	<parameter name="plug" unique="0" required="1">
+		<getopt mixed="-n, --plug=[id]" />
+		<content type="string" />
+		<shortdesc lang="en">Receptacle address (pdu-branch-receptacle) or label</shortdesc>
+	</parameter>
+	<parameter name="power_timeout" unique="0" required="0">
+		<getopt mixed="--power-timeout=[seconds]" />
+		<content type="second" default="20" />
+		<shortdesc lang="en">Test X seconds for status change after ON/OFF</shortdesc>
+	</parameter>
+</parameters>
+<actions>
+	<action name="on" automatic="0"/>
+	<action name="off" />
+	<action name="reboot" />
+	<action name="status" />
+	<action name="list" />
+	<action name="monitor" />
+	<action name="metadata" />
+	<action name="validate-all" />
+</actions>
+</resource-agent>
+"#.to_string()
+}
+
+async fn plug_state(mpx: &MPX, plug: &str) -> Result<bool, String> {
+    let receptacles = mpx.get_receptacles().await.map_err(|e| format!("{}", e))?;
+    let entry = find_plug(&receptacles, plug).ok_or(format!("unknown plug: {}", plug))?;
+    Ok(entry.enabled)
+}
+
+async fn set_plug_state(mpx: &MPX, plug: &str, enabled: bool, timeout: u64) -> Result<(), String> {
+    let receptacles = mpx.get_receptacles().await.map_err(|e| format!("{}", e))?;
+    let r = find_plug(&receptacles, plug).ok_or(format!("unknown plug: {}", plug))?;
+
+    let result: Result<(), MPXError> = if enabled {
+        mpx.receptacle_enable((r.pdu, r.branch, r.receptacle)).await
+    } else {
+        mpx.receptacle_disable((r.pdu, r.branch, r.receptacle)).await
+    };
+    result.map_err(|e| format!("{}", e))?;
+
+    for _ in 0..timeout {
+        if plug_state(mpx, plug).await? == enabled {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    Err(format!("timed out waiting for plug {} to switch {}", plug, if enabled { "on" } else { "off" }))
+}
+
+/// Execute the fence action, printing results to stdout and errors to
+/// stderr, and return the process exit code
+pub async fn run(options: &FenceOptions) -> i32 {
+    if options.action == FenceAction::Metadata {
+        print!("{}", metadata());
+        return EXIT_SUCCESS;
+    }
+
+    let ip = match options.ip.as_ref() {
+        Some(ip) => ip,
+        None => {
+            eprintln!("failed: missing ip");
+            return EXIT_FAILURE;
+        },
+    };
+    let mpx = MPX::new(ip, options.username.as_deref().unwrap_or(""), options.password.as_deref().unwrap_or(""));
+
+    let needs_plug = !matches!(options.action, FenceAction::List | FenceAction::Monitor | FenceAction::ValidateAll);
+    let plug = options.plug.as_deref().unwrap_or("");
+    if needs_plug && plug.is_empty() {
+        eprintln!("failed: missing plug");
+        return EXIT_FAILURE;
+    }
+
+    let result = match options.action {
+        FenceAction::ValidateAll => Ok(EXIT_SUCCESS),
+        FenceAction::Monitor => mpx.get_receptacles().await
+            .map(|_| EXIT_SUCCESS)
+            .map_err(|e| format!("{}", e)),
+        FenceAction::List => mpx.get_receptacles().await
+            .map(|receptacles| {
+                for r in receptacles.iter() {
+                    println!("{}-{}-{},{}", r.pdu, r.branch, r.receptacle, r.label);
+                }
+                EXIT_SUCCESS
+            })
+            .map_err(|e| format!("{}", e)),
+        FenceAction::Status => plug_state(&mpx, plug).await.map(|on| {
+            println!("Status: {}", if on { "ON" } else { "OFF" });
+            if on { EXIT_SUCCESS } else { EXIT_OFF }
+        }),
+        FenceAction::On => set_plug_state(&mpx, plug, true, options.power_timeout).await.map(|_| EXIT_SUCCESS),
+        FenceAction::Off => set_plug_state(&mpx, plug, false, options.power_timeout).await.map(|_| EXIT_SUCCESS),
+        FenceAction::Reboot => {
+            match set_plug_state(&mpx, plug, false, options.power_timeout).await {
+                Ok(()) => set_plug_state(&mpx, plug, true, options.power_timeout).await.map(|_| EXIT_SUCCESS),
+                Err(e) => Err(e),
+            }
+        },
+        FenceAction::Metadata => unreachable!(),
+    };
+
+    match result {
+        Ok(code) => {
+            if matches!(options.action, FenceAction::On | FenceAction::Off | FenceAction::Reboot) {
+                println!("Success: {}", match options.action {
+                    FenceAction::On => "Powered ON",
+                    FenceAction::Off => "Powered OFF",
+                    _ => "Rebooted",
+                });
+            }
+            code
+        },
+        Err(e) => {
+            eprintln!("failed: {}", e);
+            EXIT_FAILURE
+        },
+    }
+}
+
+/// Entry point of the fence agent binary
+///
+/// Options are read from the command line if any are given, otherwise
+/// from stdin.
+pub async fn main() -> i32 {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = if args.is_empty() {
+        FenceOptions::from_lines(std::io::stdin().lock())
+    } else {
+        FenceOptions::from_args(args)
+    };
+
+    match options {
+        Ok(options) => run(&options).await,
+        Err(e) => {
+            eprintln!("failed: {}", e);
+            EXIT_FAILURE
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_from_args() {
+        let args = vec!["--action=off", "--ip", "192.168.23.42", "--username=admin", "--plug=1-2-3", "--power-timeout=5"];
+        let options = FenceOptions::from_args(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(options.action, FenceAction::Off);
+        assert_eq!(options.ip.as_deref(), Some("192.168.23.42"));
+        assert_eq!(options.username.as_deref(), Some("admin"));
+        assert_eq!(options.plug.as_deref(), Some("1-2-3"));
+        assert_eq!(options.power_timeout, 5);
+    }
+
+    #[test]
+    fn test_short_options_from_args() {
+        let args = vec!["-o", "off", "-n", "1-2-3", "-a", "192.168.23.42", "-lLiebert", "-p", "secret"];
+        let options = FenceOptions::from_args(args.into_iter().map(String::from)).unwrap();
+        assert_eq!(options.action, FenceAction::Off);
+        assert_eq!(options.plug.as_deref(), Some("1-2-3"));
+        assert_eq!(options.ip.as_deref(), Some("192.168.23.42"));
+        assert_eq!(options.username.as_deref(), Some("Liebert"));
+        assert_eq!(options.password.as_deref(), Some("secret"));
+
+        assert!(FenceOptions::from_args(vec!["-x".to_string(), "off".to_string()]).is_err());
+        assert!(FenceOptions::from_args(vec!["--verbose=1".to_string()]).is_err());
+        assert!(FenceOptions::from_args(vec!["action=off".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_options_from_stdin() {
+        let input = "# comment\naction=status\nipaddr=pdu1\nlogin=Liebert\npasswd=secret\nport=db-01\n";
+        let options = FenceOptions::from_lines(input.as_bytes()).unwrap();
+        assert_eq!(options.action, FenceAction::Status);
+        assert_eq!(options.ip.as_deref(), Some("pdu1"));
+        assert_eq!(options.password.as_deref(), Some("secret"));
+        assert_eq!(options.plug.as_deref(), Some("db-01"));
+
+        assert!(FenceOptions::from_lines("action=explode\n".as_bytes()).is_err());
+        assert!(FenceOptions::from_lines("action=off\nverbose=1\n".as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_find_plug() {
+        let entry = |branch, receptacle, label: &str| ReceptacleListEntry {
+            pdu: 1,
+            branch,
+            receptacle,
+            enabled: true,
+            locked: false,
+            status: crate::EventLevel::OK,
+            label: label.to_string(),
+        };
+        let receptacles = vec![entry(1, 1, "db-01"), entry(2, 3, "1-1-1")];
+
+        assert_eq!(find_plug(&receptacles, "db-01").map(|r| r.receptacle), Some(1));
+        assert_eq!(find_plug(&receptacles, "1-2-3").map(|r| &r.label[..]), Some("1-1-1"));
+        assert_eq!(find_plug(&receptacles, "1-1-1").map(|r| &r.label[..]), Some("db-01"));
+        assert!(find_plug(&receptacles, "9-9-9").is_none());
+    }
+}
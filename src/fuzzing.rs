@@ -0,0 +1,57 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Entry points for fuzzing the page parsers
+//!
+//! Available with the `fuzzing` feature. The parsers must return an
+//! error for any malformed device output, never panic. The `fuzz`
+//! directory contains a `cargo fuzz` target feeding arbitrary input to
+//! [`parse_all`]:
+//!
+//! ```sh
+//! cargo +nightly fuzz run parse_pages
+//! ```
+//!
+//! [`parse_info_tables`] exposes the generic table parser of the
+//! information pages, which is otherwise only reachable through the
+//! typed structs.
+
+use crate::{get_info_tables, MPXError};
+use crate::{parse_branch_info, parse_events, parse_pdu_info, parse_receptacle_info, parse_receptacles};
+
+/// Rows of the status, events, settings and hardware tables of an
+/// information page as `(key, value, unit)`, sorted by key
+pub fn parse_info_tables(html: &str) -> Result<Vec<(String, String, String)>, MPXError> {
+    let tables = get_info_tables(html.to_string())?;
+    let mut rows: Vec<(String, String, String)> = [tables.status, tables.events, tables.settings, tables.hardware].iter()
+        .flat_map(|table| table.values())
+        .map(|v| (v.key.clone(), v.value.clone(), v.unit.clone()))
+        .collect();
+    rows.sort();
+    Ok(rows)
+}
+
+/// Run every page parser on `data`, interpreted as (lossy) UTF-8
+pub fn parse_all(data: &[u8]) {
+    let html = String::from_utf8_lossy(data).into_owned();
+    let _ = parse_info_tables(&html);
+    let _ = parse_pdu_info(html.clone());
+    let _ = parse_branch_info(html.clone());
+    let _ = parse_receptacle_info(html.clone());
+    let _ = parse_events(html.clone());
+    let _ = parse_receptacles(html);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_info_tables() {
+        let rows = parse_info_tables(include_str!("../testdata/receptacle-info.htm")).unwrap();
+        assert!(rows.contains(&("Receptacle Voltage".to_string(), "236.2".to_string(), "VAC".to_string())));
+        parse_all(include_bytes!("../testdata/events-test.htm"));
+        parse_all(&[0xff, b'<', b'h']);
+    }
+}
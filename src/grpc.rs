@@ -0,0 +1,262 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! gRPC control service
+//!
+//! Implements the `liebert_mpx.v1.PduControl` service from
+//! `proto/liebert_mpx.proto`, so services written in other languages
+//! can read PDU state, follow events and switch receptacles through a
+//! typed API.
+//!
+//! The service acts with the credentials of the `MPX` handle, so
+//! `ReceptacleCommand` is only served if a bearer token is configured,
+//! which all calls then have to send in the `authorization` metadata:
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use std::sync::Arc;
+//!
+//! fn main() {
+//!     let pdu = Arc::new(liebert::MPX::new("192.168.23.42", "Liebert", "Liebert"));
+//!     async {
+//!         liebert::grpc::serve(pdu, "127.0.0.1:50051".parse().unwrap(), Some("secret")).await.unwrap();
+//!     };
+//! }
+//! ```
+
+use crate::{Event, EventLevel, MPX, MPXError, PDUInfo, ReceptacleCmd, ReceptacleListEntry};
+use std::convert::TryFrom;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+/// Generated protocol buffer types
+pub mod pb {
+    tonic::include_proto!("liebert_mpx.v1");
+}
+
+use pb::pdu_control_server::{PduControl, PduControlServer};
+
+impl From<EventLevel> for pb::EventLevel {
+    fn from(level: EventLevel) -> Self {
+        match level {
+            EventLevel::OK => pb::EventLevel::Ok,
+            EventLevel::INFO => pb::EventLevel::Info,
+            EventLevel::WARNING => pb::EventLevel::Warning,
+            EventLevel::ALARM => pb::EventLevel::Alarm,
+        }
+    }
+}
+
+impl From<&Event> for pb::Event {
+    fn from(event: &Event) -> Self {
+        pb::Event {
+            level: pb::EventLevel::from(event.level) as i32,
+            pdu: event.pdu.into(),
+            branch: event.branch.into(),
+            receptacle: event.receptacle.into(),
+            event: format!("{:?}", event.event),
+        }
+    }
+}
+
+impl From<&ReceptacleListEntry> for pb::Receptacle {
+    fn from(entry: &ReceptacleListEntry) -> Self {
+        pb::Receptacle {
+            pdu: entry.pdu.into(),
+            branch: entry.branch.into(),
+            receptacle: entry.receptacle.into(),
+            enabled: entry.enabled,
+            locked: entry.locked,
+            status: pb::EventLevel::from(entry.status) as i32,
+            label: entry.label.clone(),
+        }
+    }
+}
+
+impl From<&PDUInfo> for pb::PduStatus {
+    fn from(info: &PDUInfo) -> Self {
+        let status = &info.status;
+        pb::PduStatus {
+            accumulated_energy: status.accumulated_energy,
+            input_power: status.input_power,
+            voltage: [Some(status.voltage_l1_n), status.voltage_l2_n, status.voltage_l3_n].iter().flatten().copied().collect(),
+            current: [Some(status.current_l1), status.current_l2, status.current_l3].iter().flatten().copied().collect(),
+            current_n: status.current_n,
+            line_frequency: status.line_frequency,
+        }
+    }
+}
+
+fn to_status(e: MPXError) -> Status {
+    Status::unavailable(format!("{}", e))
+}
+
+fn to_u8(value: u32, name: &str) -> Result<u8, Status> {
+    u8::try_from(value).map_err(|_| Status::invalid_argument(format!("{} out of range", name)))
+}
+
+fn check_token(expected: &str, request: Request<()>) -> Result<Request<()>, Status> {
+    match request.metadata().get("authorization") {
+        Some(value) if value.as_bytes() == expected.as_bytes() => Ok(request),
+        _ => Err(Status::unauthenticated("invalid or missing token")),
+    }
+}
+
+/// gRPC service backed by a single `MPX` handle
+pub struct Service {
+    mpx: Arc<MPX>,
+    control: bool,
+}
+
+impl Service {
+    /// Read-only service, `ReceptacleCommand` is refused
+    pub fn new(mpx: Arc<MPX>) -> Self {
+        Service { mpx, control: false }
+    }
+
+    /// Serve `ReceptacleCommand`; only use behind authentication
+    pub fn with_control(mut self) -> Self {
+        self.control = true;
+        self
+    }
+}
+
+#[tonic::async_trait]
+impl PduControl for Service {
+    async fn get_snapshot(&self, request: Request<pb::GetSnapshotRequest>) -> Result<Response<pb::Snapshot>, Status> {
+        let pdu = to_u8(request.into_inner().pdu, "pdu")?;
+        let info = self.mpx.get_info_pdu(pdu).await.map_err(to_status)?;
+        let receptacles = self.mpx.get_receptacles().await.map_err(to_status)?;
+        let events = self.mpx.get_events().await.map_err(to_status)?;
+
+        Ok(Response::new(pb::Snapshot {
+            serial_number: info.hardware.serial_number.clone(),
+            status: Some(pb::PduStatus::from(&info)),
+            receptacles: receptacles.iter().filter(|r| r.pdu == pdu).map(pb::Receptacle::from).collect(),
+            events: events.iter().filter(|e| e.pdu == pdu).map(pb::Event::from).collect(),
+        }))
+    }
+
+    type StreamEventsStream = ReceiverStream<Result<pb::Event, Status>>;
+
+    async fn stream_events(&self, request: Request<pb::StreamEventsRequest>) -> Result<Response<Self::StreamEventsStream>, Status> {
+        let interval = match request.into_inner().poll_interval_seconds {
+            0 => Duration::from_secs(10),
+            secs => Duration::from_secs(secs.into()),
+        };
+        let mpx = self.mpx.clone();
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+
+        tokio::spawn(async move {
+            let mut active: Vec<Event> = Vec::new();
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+                let events = match mpx.get_events().await {
+                    Ok(events) => events,
+                    Err(e) => {
+                        if tx.send(Err(to_status(e))).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    },
+                };
+
+                for event in events.iter().filter(|e| !active.contains(e)) {
+                    if tx.send(Ok(pb::Event::from(event))).await.is_err() {
+                        return;
+                    }
+                }
+
+                active = events;
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn receptacle_command(&self, request: Request<pb::ReceptacleCommandRequest>) -> Result<Response<pb::ReceptacleCommandResponse>, Status> {
+        if !self.control {
+            return Err(Status::permission_denied("receptacle control is disabled"));
+        }
+
+        let request = request.into_inner();
+        let pdu = to_u8(request.pdu, "pdu")?;
+        let branch = to_u8(request.branch, "branch")?;
+        let receptacle = to_u8(request.receptacle, "receptacle")?;
+        let cmd = match pb::ReceptacleCommand::try_from(request.command) {
+            Ok(pb::ReceptacleCommand::Enable) => ReceptacleCmd::Enable,
+            Ok(pb::ReceptacleCommand::Disable) => ReceptacleCmd::Disable,
+            Ok(pb::ReceptacleCommand::Reboot) => ReceptacleCmd::Reboot,
+            Ok(pb::ReceptacleCommand::Identify) => ReceptacleCmd::Identify,
+            Ok(pb::ReceptacleCommand::ResetEnergy) => ReceptacleCmd::ResetEnergy,
+            Err(_) => return Err(Status::invalid_argument("unknown command")),
+        };
+
+        self.mpx.receptacle_command((pdu, branch, receptacle), cmd).await.map_err(to_status)?;
+        Ok(Response::new(pb::ReceptacleCommandResponse {}))
+    }
+}
+
+/// Serve the gRPC service for `mpx` on `addr` until an error occurs
+///
+/// Without `token` the service is read-only. With `token` every call
+/// must carry `authorization: Bearer <token>` and receptacles can be
+/// switched.
+pub async fn serve(mpx: Arc<MPX>, addr: SocketAddr, token: Option<&str>) -> Result<(), tonic::transport::Error> {
+    let mut builder = tonic::transport::Server::builder();
+
+    match token {
+        Some(token) => {
+            let expected = format!("Bearer {}", token);
+            let service = PduControlServer::with_interceptor(Service::new(mpx).with_control(), move |request| check_token(&expected, request));
+            builder.add_service(service).serve(addr).await
+        },
+        None => builder.add_service(PduControlServer::new(Service::new(mpx))).serve(addr).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_events;
+
+    #[test]
+    fn test_event_conversion() {
+        let html = include_str!("../testdata/events-test.htm").to_string();
+        let events = parse_events(html).unwrap();
+
+        for event in events.iter() {
+            let converted = pb::Event::from(event);
+            assert_eq!(converted.pdu, u32::from(event.pdu));
+            assert_eq!(converted.event, format!("{:?}", event.event));
+            assert_eq!(converted.level, pb::EventLevel::from(event.level) as i32);
+        }
+    }
+
+    #[test]
+    fn test_check_token() {
+        let mut request = Request::new(());
+        assert_eq!(check_token("Bearer secret", request).unwrap_err().code(), tonic::Code::Unauthenticated);
+
+        request = Request::new(());
+        request.metadata_mut().insert("authorization", "Bearer wrong".parse().unwrap());
+        assert!(check_token("Bearer secret", request).is_err());
+
+        request = Request::new(());
+        request.metadata_mut().insert("authorization", "Bearer secret".parse().unwrap());
+        assert!(check_token("Bearer secret", request).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_read_only() {
+        let service = Service::new(Arc::new(MPX::new("192.0.2.1", "Liebert", "Liebert")));
+        let request = Request::new(pb::ReceptacleCommandRequest { pdu: 1, branch: 1, receptacle: 1, command: pb::ReceptacleCommand::Disable as i32 });
+        assert_eq!(service.receptacle_command(request).await.unwrap_err().code(), tonic::Code::PermissionDenied);
+    }
+}
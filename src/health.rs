@@ -0,0 +1,118 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Connection health tracking
+//!
+//! Every page fetch, command and settings write updates the health
+//! state of the [`MPX`] handle, so long-running daemons can report the
+//! reachability of a device without their own bookkeeping.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         let _ = pdu.get_events().await;
+//!         if !pdu.is_healthy() {
+//!             println!("pdu unreachable: {:?}", pdu.last_error());
+//!         }
+//!     };
+//! }
+//! ```
+
+use crate::{MPX, MPXError};
+use std::time::SystemTime;
+
+/// Consecutive failures after which a device is considered unhealthy
+const UNHEALTHY_AFTER: u32 = 3;
+
+#[derive(Clone,Debug,PartialEq)]
+/// A failed request
+pub struct Failure {
+    pub time: SystemTime,
+    /// Error message
+    pub error: String,
+}
+
+#[derive(Clone,Debug,Default)]
+/// Health state of a [`MPX`]
+pub(crate) struct HealthState {
+    last_success: Option<SystemTime>,
+    last_error: Option<Failure>,
+    failures: u32,
+}
+
+impl HealthState {
+    fn record<T>(&mut self, result: &Result<T, MPXError>, now: SystemTime) {
+        match result {
+            Ok(_) => {
+                self.last_success = Some(now);
+                self.failures = 0;
+            },
+            Err(e) => {
+                self.last_error = Some(Failure { time: now, error: e.to_string() });
+                self.failures += 1;
+            },
+        }
+    }
+
+    fn healthy(&self) -> bool {
+        self.last_success.is_some() && self.failures < UNHEALTHY_AFTER
+    }
+}
+
+impl<H> MPX<H> {
+    pub(crate) fn record_health<T>(&self, result: &Result<T, MPXError>) {
+        self.inner.health.lock().unwrap().record(result, SystemTime::now());
+    }
+
+    /// Time of the last successful request
+    pub fn last_success(&self) -> Option<SystemTime> {
+        self.inner.health.lock().unwrap().last_success
+    }
+
+    /// Last failed request, even if later requests succeeded
+    pub fn last_error(&self) -> Option<Failure> {
+        self.inner.health.lock().unwrap().last_error.clone()
+    }
+
+    /// Number of failed requests since the last successful one
+    pub fn consecutive_failures(&self) -> u32 {
+        self.inner.health.lock().unwrap().failures
+    }
+
+    /// Heuristic reachability: at least one request succeeded and fewer
+    /// than three requests failed in a row since then
+    pub fn is_healthy(&self) -> bool {
+        self.inner.health.lock().unwrap().healthy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InvalidDataError;
+
+    #[test]
+    fn test_health_state() {
+        let mut state = HealthState::default();
+        let now = SystemTime::now();
+        let failed: Result<(), MPXError> = Err(MPXError::InvalidDataError(InvalidDataError::default()));
+        assert!(!state.healthy());
+
+        state.record(&Ok(()), now);
+        assert!(state.healthy());
+        state.record(&failed, now);
+        state.record(&failed, now);
+        assert!(state.healthy());
+        state.record(&failed, now);
+        assert!(!state.healthy());
+        assert_eq!(state.last_error.as_ref().unwrap().error, "malformed data in PDU response");
+
+        state.record(&Ok(()), now);
+        assert!(state.healthy());
+        assert!(state.last_error.is_some());
+    }
+}
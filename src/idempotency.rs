@@ -0,0 +1,135 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Deduplication of mutating commands
+//!
+//! Orchestration steps are often retried as a whole after a network
+//! error, which must not toggle or reboot a receptacle a second time.
+//! Wrapping the mutating call in [`MPX::once`] with a caller chosen
+//! idempotency key runs it only once; the key is remembered for a
+//! limited time (10 minutes by default, see
+//! [`MPX::set_idempotency_ttl`]).
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         let executed = pdu.once("maintenance-4711/reboot-db-01", || pdu.receptacle_reboot((1, 1, 2))).await.unwrap();
+//!         if !executed {
+//!             println!("reboot has already been done");
+//!         }
+//!     };
+//! }
+//! ```
+
+use crate::{MPX, MPXError};
+use crate::transport::HttpTransport;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+/// Recently used idempotency keys of a [`MPX`]
+pub(crate) struct IdempotencyKeys {
+    ttl: Duration,
+    keys: HashMap<String, Instant>,
+}
+
+impl Default for IdempotencyKeys {
+    fn default() -> Self {
+        IdempotencyKeys {
+            ttl: Duration::from_secs(600),
+            keys: HashMap::new(),
+        }
+    }
+}
+
+impl IdempotencyKeys {
+    /// Reserve `key`, returns `false` if it has been used within the TTL
+    fn reserve(&mut self, key: &str, now: Instant) -> bool {
+        let ttl = self.ttl;
+        self.keys.retain(|_, used| now.duration_since(*used) < ttl);
+        if self.keys.contains_key(key) {
+            return false;
+        }
+        self.keys.insert(key.to_string(), now);
+        true
+    }
+
+    fn release(&mut self, key: &str) {
+        self.keys.remove(key);
+    }
+}
+
+impl<H: HttpTransport> MPX<H> {
+    /// Run a mutating `operation` unless `key` has been used recently
+    ///
+    /// Returns `true` if the operation has been executed. The key is
+    /// reserved while the operation runs, so concurrent calls with the
+    /// same key are deduplicated as well. If the operation fails the key
+    /// is released again, so the caller can retry it.
+    pub async fn once<F, Fut>(&self, key: &str, operation: F) -> Result<bool, MPXError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(), MPXError>>,
+    {
+        if !self.inner.idempotency.lock().unwrap().reserve(key, Instant::now()) {
+            return Ok(false);
+        }
+
+        match operation().await {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                self.inner.idempotency.lock().unwrap().release(key);
+                Err(e)
+            },
+        }
+    }
+
+    /// Time for which idempotency keys are remembered
+    pub fn set_idempotency_ttl(&self, ttl: Duration) {
+        self.inner.idempotency.lock().unwrap().ttl = ttl;
+    }
+
+    /// Forget an idempotency key, allowing the operation to run again
+    pub fn forget_idempotency_key(&self, key: &str) {
+        self.inner.idempotency.lock().unwrap().release(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InvalidDataError;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_reserve() {
+        let mut keys = IdempotencyKeys::default();
+        let now = Instant::now();
+        assert!(keys.reserve("a", now));
+        assert!(!keys.reserve("a", now + Duration::from_secs(10)));
+        assert!(keys.reserve("b", now));
+        assert!(keys.reserve("a", now + Duration::from_secs(601)));
+    }
+
+    #[tokio::test]
+    async fn test_once() {
+        let mpx = MPX::new("127.0.0.1", "", "");
+        let calls = Cell::new(0);
+
+        let failing = || async { calls.set(calls.get() + 1); Err(MPXError::InvalidDataError(InvalidDataError::default())) };
+        assert!(mpx.once("k", failing).await.is_err());
+        let working = || async { calls.set(calls.get() + 1); Ok(()) };
+        assert!(mpx.once("k", working).await.unwrap());
+        assert!(!mpx.once("k", working).await.unwrap());
+        assert_eq!(calls.get(), 2);
+
+        mpx.forget_idempotency_key("k");
+        assert!(mpx.once("k", working).await.unwrap());
+        assert_eq!(calls.get(), 3);
+    }
+}
@@ -0,0 +1,159 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Asset inventory
+//!
+//! [`MPX::inventory`] collects model, serial number, firmware version,
+//! label and asset tags of every PDU, branch module and receptacle into
+//! a flat list, e.g. for an import into an asset management system:
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         for item in pdu.inventory().await.unwrap() {
+//!             println!("{} {}-{}-{} {} {}", item.kind, item.pdu, item.branch, item.receptacle, item.model, item.serial_number);
+//!         }
+//!     };
+//! }
+//! ```
+
+use crate::Snapshot;
+#[cfg(feature = "client")]
+use crate::{MPX, MPXError};
+#[cfg(feature = "client")]
+use crate::transport::HttpTransport;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Kind of an inventory item
+pub enum ModuleKind {
+    /// PDU input module
+    PDU,
+    /// Branch module
+    Branch,
+    Receptacle,
+}
+
+impl std::fmt::Display for ModuleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ModuleKind::PDU => write!(f, "PDU"),
+            ModuleKind::Branch => write!(f, "Branch"),
+            ModuleKind::Receptacle => write!(f, "Receptacle"),
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Identification of a single module
+pub struct InventoryItem {
+    pub kind: ModuleKind,
+    /// PDU number (usually 1)
+    pub pdu: u8,
+    /// Branch number, 0 for PDU input modules
+    pub branch: u8,
+    /// Receptacle number, 0 for all modules except receptacles
+    pub receptacle: u8,
+    /// Model description (receptacle type for receptacles)
+    pub model: String,
+    /// Serial number, empty for receptacles
+    pub serial_number: String,
+    /// Firmware version, empty for receptacles
+    pub firmware_version: String,
+    /// User label
+    pub label: String,
+    pub asset_tag_1: String,
+    pub asset_tag_2: String,
+}
+
+/// Inventory items in PDU, branch, receptacle order
+pub type Inventory = Vec<InventoryItem>;
+
+/// Inventory of the PDU, branch modules and receptacles of a snapshot
+pub fn snapshot_inventory(snapshot: &Snapshot) -> Inventory {
+    let info = &snapshot.info;
+    let mut items = vec![InventoryItem {
+        kind: ModuleKind::PDU,
+        pdu: snapshot.pdu,
+        branch: 0,
+        receptacle: 0,
+        model: info.hardware.pem_model.to_string(),
+        serial_number: info.hardware.serial_number.clone(),
+        firmware_version: info.hardware.fw_version.to_string(),
+        label: info.settings.label.clone(),
+        asset_tag_1: info.settings.asset_tag_1.clone(),
+        asset_tag_2: info.settings.asset_tag_2.clone(),
+    }];
+
+    for b in snapshot.branches.iter() {
+        items.push(InventoryItem {
+            kind: ModuleKind::Branch,
+            pdu: snapshot.pdu,
+            branch: b.branch,
+            receptacle: 0,
+            model: b.info.hardware.brm_model.to_string(),
+            serial_number: b.info.hardware.serial_number.clone(),
+            firmware_version: b.info.hardware.fw_version.to_string(),
+            label: b.info.settings.label.clone(),
+            asset_tag_1: b.info.settings.asset_tag_1.clone(),
+            asset_tag_2: b.info.settings.asset_tag_2.clone(),
+        });
+    }
+
+    for r in snapshot.receptacles.iter() {
+        items.push(InventoryItem {
+            kind: ModuleKind::Receptacle,
+            pdu: snapshot.pdu,
+            branch: r.branch,
+            receptacle: r.receptacle,
+            model: r.info.hardware.receptacle_type.to_string(),
+            serial_number: String::new(),
+            firmware_version: String::new(),
+            label: r.info.settings.label.clone(),
+            asset_tag_1: r.info.settings.asset_tag_1.clone(),
+            asset_tag_2: r.info.settings.asset_tag_2.clone(),
+        });
+    }
+
+    items
+}
+
+#[cfg(feature = "client")]
+impl<H: HttpTransport + 'static> MPX<H> {
+    /// Collect the inventory of all PDUs
+    pub async fn inventory(&self) -> Result<Inventory, MPXError> {
+        let mut items = Vec::new();
+
+        for entry in self.get_pdus().await? {
+            items.extend(snapshot_inventory(&self.get_snapshot(entry.pdu).await?));
+        }
+
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::tests::snapshot;
+
+    #[test]
+    fn test_snapshot_inventory() {
+        let snapshot = snapshot();
+        let items = snapshot_inventory(&snapshot);
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[0].kind, ModuleKind::PDU);
+        assert_eq!(items[0].serial_number, snapshot.info.hardware.serial_number);
+        assert_eq!(items[1].kind, ModuleKind::Branch);
+        assert_eq!(items[1].label, snapshot.branches[0].info.settings.label);
+        assert_eq!((items[3].kind, items[3].branch, items[3].receptacle), (ModuleKind::Receptacle, 1, 2));
+        assert_eq!(items[3].model, snapshot.receptacles[1].info.hardware.receptacle_type.to_string());
+    }
+}
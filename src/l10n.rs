@@ -0,0 +1,87 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Localization of display strings
+//!
+//! `Display` implementations of descriptive enums (e.g. [`EventType`]
+//! or [`EventLevel`]) and the texts of [`report`] are looked up here
+//! before being printed. The English text is used as message key and
+//! is returned unchanged unless a translation has been registered:
+//!
+//! ```
+//! extern crate liebert_mpx as liebert;
+//!
+//! liebert::l10n::register("Branch Breaker Open", "Abzweig-Sicherung ausgelöst");
+//! assert_eq!(liebert::EventType::BranchBreakerOpen.to_string(), "Abzweig-Sicherung ausgelöst");
+//! ```
+//!
+//! Identifiers like model names, firmware versions, receptacle types
+//! and line sources are not translated.
+//!
+//! [`EventType`]: crate::EventType
+//! [`EventLevel`]: crate::EventLevel
+//! [`report`]: crate::report
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+fn translations() -> &'static RwLock<HashMap<String, String>> {
+    static TRANSLATIONS: OnceLock<RwLock<HashMap<String, String>>> = OnceLock::new();
+    TRANSLATIONS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Register a translation for the English text `text`
+pub fn register(text: &str, translation: &str) {
+    translations().write().unwrap().insert(text.to_string(), translation.to_string());
+}
+
+/// Register multiple translations, e.g. loaded from a catalog file
+pub fn register_all<I, K, V>(catalog: I)
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<String>,
+{
+    let mut table = translations().write().unwrap();
+    for (text, translation) in catalog {
+        table.insert(text.into(), translation.into());
+    }
+}
+
+/// Remove the translation for `text`, so that the English default is used again
+pub fn unregister(text: &str) {
+    translations().write().unwrap().remove(text);
+}
+
+/// Remove all registered translations
+pub fn clear() {
+    translations().write().unwrap().clear();
+}
+
+/// Translate an English display string
+pub fn tr(text: &str) -> String {
+    match translations().read().unwrap().get(text) {
+        Some(translation) => translation.clone(),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WiringType;
+
+    #[test]
+    fn test_translation() {
+        assert_eq!(tr("Untranslated Test Text"), "Untranslated Test Text");
+
+        register("3-Phase", "trifásico");
+        assert_eq!(WiringType::ThreePhase.to_string(), "trifásico");
+        unregister("3-Phase");
+        assert_eq!(WiringType::ThreePhase.to_string(), "3-Phase");
+
+        register_all(vec![("Test Text A", "A"), ("Test Text B", "B")]);
+        assert_eq!(tr("Test Text B"), "B");
+    }
+}
@@ -0,0 +1,194 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Row labels of translated web interfaces
+//!
+//! The information pages are parsed by their English row labels. Cards
+//! with another web interface language emit translated labels, which
+//! are mapped back to the English ones registered here before the
+//! typed structs are built. Tables for some languages are included, but
+//! not active by default:
+//!
+//! ```
+//! extern crate liebert_mpx as liebert;
+//!
+//! liebert::labels::register_all(liebert::labels::GERMAN.iter().copied());
+//! liebert::labels::register("PDU Gesamteingangsleistung", "PDU Total Input Power");
+//! ```
+//!
+//! The included tables only cover the status rows (measurements, label
+//! and serial number) and may differ between firmware versions; labels
+//! missing for a card can be added with [`register`].
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// German labels of the status rows
+pub const GERMAN: &[(&str, &str)] = &[
+    ("PDU Gesamtenergie", "PDU Accumulated Energy"),
+    ("PDU Gesamtleistung", "PDU Total Input Power"),
+    ("PDU Spannung L1-N", "PDU Voltage L1-N"),
+    ("PDU Spannung L2-N", "PDU Voltage L2-N"),
+    ("PDU Spannung L3-N", "PDU Voltage L3-N"),
+    ("PDU Strom L1", "PDU Current L1"),
+    ("PDU Strom L2", "PDU Current L2"),
+    ("PDU Strom L3", "PDU Current L3"),
+    ("PDU Neutralleiterstrom", "PDU Neutral Current Measurement"),
+    ("PDU Verfügbarer Strom L1 bis Alarm", "PDU Available L1 Current Until Alarm"),
+    ("PDU Verfügbarer Strom L2 bis Alarm", "PDU Available L2 Current Until Alarm"),
+    ("PDU Verfügbarer Strom L3 bis Alarm", "PDU Available L3 Current Until Alarm"),
+    ("PDU Stromauslastung L1 in Prozent", "PDU Percent L1 Current Utilization"),
+    ("PDU Stromauslastung L2 in Prozent", "PDU Percent L2 Current Utilization"),
+    ("PDU Stromauslastung L3 in Prozent", "PDU Percent L3 Current Utilization"),
+    ("PEM Netzfrequenz", "PEM Line Frequency"),
+    ("PDU Benutzerdefinierte Bezeichnung", "PDU User Assigned Label"),
+    ("PEM Seriennummer", "PEM Serial Number"),
+    ("Abzweig Gesamtenergie", "Branch Accumulated Energy"),
+    ("Abzweig Spannung", "Branch Voltage"),
+    ("Abzweig Strom", "Branch Current"),
+    ("Abzweig Verfügbarer Strom bis Alarm", "Branch Available Current Until Alarm"),
+    ("Abzweig Stromauslastung in Prozent", "Branch Percent Current Utilization"),
+    ("Abzweig Wirkleistung", "Branch Power"),
+    ("Abzweig Scheinleistung", "Branch Apparent Power"),
+    ("Abzweig Leistungsfaktor", "Branch Power Factor"),
+    ("Abzweig Benutzerdefinierte Bezeichnung", "Branch User Assigned Label"),
+    ("Abzweig Seriennummer", "Branch Serial Number"),
+    ("Steckdose Gesamtenergie", "Receptacle Accumulated Energy"),
+    ("Steckdose Spannung", "Receptacle Voltage"),
+    ("Steckdose Strom", "Receptacle Current"),
+    ("Steckdose Verfügbarer Strom bis Alarm", "Receptacle Available Current Until Alarm"),
+    ("Steckdose Stromauslastung in Prozent", "Receptacle Percent Current Utilization"),
+    ("Steckdose Wirkleistung", "Receptacle Power"),
+    ("Steckdose Scheinleistung", "Receptacle Apparent Power"),
+    ("Steckdose Leistungsfaktor", "Receptacle Power Factor"),
+    ("Steckdose Crestfaktor Strom", "Receptacle Current Crest Factor"),
+    ("Steckdose Benutzerdefinierte Bezeichnung", "Receptacle User Assigned Label"),
+];
+
+/// French labels of the status rows
+pub const FRENCH: &[(&str, &str)] = &[
+    ("PDU Énergie cumulée", "PDU Accumulated Energy"),
+    ("PDU Puissance d'entrée totale", "PDU Total Input Power"),
+    ("PDU Tension L1-N", "PDU Voltage L1-N"),
+    ("PDU Tension L2-N", "PDU Voltage L2-N"),
+    ("PDU Tension L3-N", "PDU Voltage L3-N"),
+    ("PDU Courant L1", "PDU Current L1"),
+    ("PDU Courant L2", "PDU Current L2"),
+    ("PDU Courant L3", "PDU Current L3"),
+    ("PDU Mesure du courant de neutre", "PDU Neutral Current Measurement"),
+    ("PDU Courant L1 disponible avant alarme", "PDU Available L1 Current Until Alarm"),
+    ("PDU Courant L2 disponible avant alarme", "PDU Available L2 Current Until Alarm"),
+    ("PDU Courant L3 disponible avant alarme", "PDU Available L3 Current Until Alarm"),
+    ("PDU Pourcentage d'utilisation du courant L1", "PDU Percent L1 Current Utilization"),
+    ("PDU Pourcentage d'utilisation du courant L2", "PDU Percent L2 Current Utilization"),
+    ("PDU Pourcentage d'utilisation du courant L3", "PDU Percent L3 Current Utilization"),
+    ("PEM Fréquence du réseau", "PEM Line Frequency"),
+    ("PDU Libellé attribué par l'utilisateur", "PDU User Assigned Label"),
+    ("PEM Numéro de série", "PEM Serial Number"),
+    ("Branche Énergie cumulée", "Branch Accumulated Energy"),
+    ("Branche Tension", "Branch Voltage"),
+    ("Branche Courant", "Branch Current"),
+    ("Branche Courant disponible avant alarme", "Branch Available Current Until Alarm"),
+    ("Branche Pourcentage d'utilisation du courant", "Branch Percent Current Utilization"),
+    ("Branche Puissance", "Branch Power"),
+    ("Branche Puissance apparente", "Branch Apparent Power"),
+    ("Branche Facteur de puissance", "Branch Power Factor"),
+    ("Branche Libellé attribué par l'utilisateur", "Branch User Assigned Label"),
+    ("Branche Numéro de série", "Branch Serial Number"),
+    ("Prise Énergie cumulée", "Receptacle Accumulated Energy"),
+    ("Prise Tension", "Receptacle Voltage"),
+    ("Prise Courant", "Receptacle Current"),
+    ("Prise Courant disponible avant alarme", "Receptacle Available Current Until Alarm"),
+    ("Prise Pourcentage d'utilisation du courant", "Receptacle Percent Current Utilization"),
+    ("Prise Puissance", "Receptacle Power"),
+    ("Prise Puissance apparente", "Receptacle Apparent Power"),
+    ("Prise Facteur de puissance", "Receptacle Power Factor"),
+    ("Prise Facteur de crête du courant", "Receptacle Current Crest Factor"),
+    ("Prise Libellé attribué par l'utilisateur", "Receptacle User Assigned Label"),
+];
+
+thread_local! {
+    /// Labels of the firmware profile of the page currently parsed
+    static SCOPED: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn labels() -> &'static RwLock<HashMap<String, String>> {
+    static LABELS: OnceLock<RwLock<HashMap<String, String>>> = OnceLock::new();
+    LABELS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Register the English row label `key` for the translated `label`
+pub fn register(label: &str, key: &str) {
+    labels().write().unwrap().insert(label.to_string(), key.to_string());
+}
+
+/// Register multiple labels, e.g. [`GERMAN`] or a map loaded from a file
+pub fn register_all<I, K, V>(map: I)
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<String>,
+{
+    let mut table = labels().write().unwrap();
+    for (label, key) in map {
+        table.insert(label.into(), key.into());
+    }
+}
+
+/// Remove the mapping for the translated `label`
+pub fn unregister(label: &str) {
+    labels().write().unwrap().remove(label);
+}
+
+/// Remove all registered labels
+pub fn clear() {
+    labels().write().unwrap().clear();
+}
+
+/// Parse with the `labels` of a firmware profile in addition to the
+/// registered ones, see [`compat`](crate::compat)
+#[cfg(feature = "client")]
+pub(crate) fn scoped<T>(labels: &[(String, String)], parse: impl FnOnce() -> T) -> T {
+    SCOPED.with(|scoped| *scoped.borrow_mut() = labels.to_vec());
+    let result = parse();
+    SCOPED.with(|scoped| scoped.borrow_mut().clear());
+    result
+}
+
+/// English row label for a (possibly translated) label
+pub(crate) fn canonical(label: String) -> String {
+    let scoped = SCOPED.with(|scoped| scoped.borrow().iter().find(|(l, _)| *l == label).map(|(_, key)| key.clone()));
+    if let Some(key) = scoped {
+        return key;
+    }
+
+    match labels().read().unwrap().get(&label) {
+        Some(key) => key.clone(),
+        None => label,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_receptacle_info;
+
+    #[test]
+    fn test_translated_labels() {
+        let html = include_str!("../testdata/receptacle-info.htm")
+            .replace(">Receptacle Current<", ">Prise Courant<")
+            .replace(">Receptacle Power Factor<", ">Prise Facteur de puissance<");
+        assert!(parse_receptacle_info(html.clone()).is_err());
+
+        register_all(FRENCH.iter().copied());
+        let info = parse_receptacle_info(html).unwrap();
+        assert_eq!(info.status.power_factor, Some(0.0));
+
+        for (label, _) in FRENCH {
+            unregister(label);
+        }
+        assert_eq!(canonical("Prise Courant".to_string()), "Prise Courant");
+    }
+}
@@ -47,12 +47,12 @@
 //! fn main() {
 //!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
 //!     async {
-//!         let receptacle = pdu.get_info_receptacle(1, 2, 3).await.unwrap();
+//!         let receptacle = pdu.get_info_receptacle((1, 2, 3)).await.unwrap();
 //!         let settings = liebert::ReceptacleSettings {
 //!             label: "Low Power Light".to_string(),
 //!             ..receptacle.settings
 //!         };
-//!         pdu.set_receptacle_settings(1, 2, 3, &settings).await.unwrap();
+//!         pdu.set_receptacle_settings((1, 2, 3), &settings).await.unwrap();
 //!     };
 //! }
 //! ```
@@ -64,98 +64,433 @@
 //! fn main() {
 //!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
 //!     async {
-//!         pdu.receptacle_identify(1, 1, 1).await.unwrap();
-//!         pdu.receptacle_disable(1, 1, 2).await.unwrap();
-//!         pdu.receptacle_enable(1, 1, 3).await.unwrap();
-//!         pdu.receptacle_reboot(1, 1, 4).await.unwrap();
+//!         pdu.receptacle_identify((1, 1, 1)).await.unwrap();
+//!         pdu.receptacle_disable((1, 1, 2)).await.unwrap();
+//!         pdu.receptacle_enable((1, 1, 3)).await.unwrap();
+//!         pdu.receptacle_reboot((1, 1, 4)).await.unwrap();
 //!     };
 //! }
 //! ```
 
+#[cfg(feature = "serde")]
 use serde::Serialize;
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::time::Duration;
+#[cfg(feature = "client")]
+use std::sync::{Arc, Mutex};
+
+pub mod address;
+pub mod accounting;
+pub mod annotations;
+#[cfg(feature = "client")]
+pub mod auth;
+pub mod anomaly;
+pub mod balance;
+pub mod capabilities;
+#[cfg(feature = "client")]
+pub mod breaker;
+pub mod capacity;
+#[cfg(feature = "client")]
+pub mod chaos;
+#[cfg(feature = "color")]
+pub mod color;
+#[cfg(feature = "client")]
+pub mod compat;
+#[cfg(feature = "client")]
+pub mod config;
+#[cfg(feature = "client")]
+pub mod credentials;
+#[cfg(feature = "client")]
+pub mod csrf;
+#[cfg(feature = "dbus")]
+pub mod dbus;
+#[cfg(feature = "client")]
+pub mod diagnostics;
+pub mod diff;
+#[cfg(feature = "client")]
+pub mod failover;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+#[cfg(feature = "fence")]
+pub mod fence;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "client")]
+pub mod health;
+#[cfg(feature = "client")]
+pub mod idempotency;
+pub mod inventory;
+pub mod l10n;
+pub mod labels;
+pub mod margin;
+pub mod metadata;
+#[cfg(feature = "nut")]
+pub mod nut;
+pub mod partial;
+pub mod raw;
+#[cfg(feature = "redfish")]
+pub mod redfish;
+#[cfg(feature = "client")]
+pub mod ratelimit;
+#[cfg(feature = "client")]
+pub mod record;
+pub mod report;
+#[cfg(feature = "client")]
+pub mod retry;
+pub mod rollup;
+pub mod search;
+#[cfg(feature = "client")]
+pub mod session;
+pub mod snapshot;
+#[cfg(all(feature = "systemd", unix))]
+pub mod systemd;
+pub mod topology;
+#[cfg(feature = "client")]
+pub mod transport;
+pub mod validation;
+#[cfg(feature = "client")]
+pub mod workflow;
+
+pub use address::{BranchAddress, BranchId, PduId, ReceptacleAddress, ReceptacleId};
+pub use snapshot::Snapshot;
 
 type RawDataTable = HashMap<String, TableValue>;
 pub type EnumParseError = ();
 pub type EventList = Vec<Event>;
+pub type BranchList = Vec<BranchListEntry>;
+pub type PDUList = Vec<PDUListEntry>;
 pub type ReceptacleList = Vec<ReceptacleListEntry>;
 
-#[derive(Debug, Clone)]
-/// Parsing Error - PDU did not provide required information
-pub struct MissingDataError;
+/// Describe where in a PDU response a parse error occurred
+fn describe_location(url: &Option<String>, table: &Option<String>, key: &Option<String>) -> String {
+    let mut result = String::new();
+    if let Some(key) = key {
+        result.push_str(&format!(" (key '{}')", key));
+    }
+    if let Some(table) = table {
+        result.push_str(&format!(" in {} table", table));
+    }
+    if let Some(url) = url {
+        result.push_str(&format!(" of {}", url));
+    }
+    result
+}
 
-impl std::fmt::Display for MissingDataError {
+#[derive(Debug, Clone, Default, PartialEq, thiserror::Error)]
+#[error("required data missing in PDU response{}", describe_location(url, table, key))]
+/// Parsing Error - PDU did not provide required information
+pub struct MissingDataError {
+    /// Page which was parsed
+    pub url: Option<String>,
+    /// Table section of the page (`status`, `events`, `settings`, `hardware`)
+    pub table: Option<String>,
+    /// Missing key
+    pub key: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+/// What was wrong with the data in an [`InvalidDataError`]
+pub enum InvalidData {
+    /// Value which could not be parsed
+    #[default]
+    Malformed,
+    /// HTML structure differs from the expected page layout
+    UnexpectedStructure {
+        /// Element which was expected, e.g. `RpcStatusArea` or `event row`
+        location: String,
+    },
+    /// Value with an unexpected unit
+    UnitMismatch {
+        expected: String,
+        got: String,
+    },
+    /// Event or receptacle identifier which is not `<pdu>-<branch>-<receptacle>`
+    BadIdentifier {
+        raw: String,
+    },
+    /// Unexpected HTTP status code of a form submission
+    UnexpectedStatus {
+        status: u16,
+    },
+}
+
+impl std::fmt::Display for InvalidData {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "could not find required data")
+        match self {
+            InvalidData::Malformed => Ok(()),
+            InvalidData::UnexpectedStructure { location } => write!(f, ": unexpected structure at {}", location),
+            InvalidData::UnitMismatch { expected, got } => write!(f, ": unit '{}' instead of '{}'", got, expected),
+            InvalidData::BadIdentifier { raw } => write!(f, ": bad identifier '{}'", raw),
+            InvalidData::UnexpectedStatus { status } => write!(f, ": HTTP status {}", status),
+        }
     }
 }
 
-impl std::error::Error for MissingDataError {}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, thiserror::Error)]
+#[error("malformed data in PDU response{detail}{}", describe_location(url, table, key))]
 /// Parsing Error - PDU provided malformed data
-pub struct InvalidDataError;
+pub struct InvalidDataError {
+    /// Page which was parsed
+    pub url: Option<String>,
+    /// Table section of the page (`status`, `events`, `settings`, `hardware`)
+    pub table: Option<String>,
+    /// Key with the invalid value
+    pub key: Option<String>,
+    /// Kind of problem
+    pub detail: InvalidData,
+}
 
-impl std::fmt::Display for InvalidDataError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "could not find required data")
+impl InvalidDataError {
+    /// HTML structure differs from the expected layout at `location`
+    pub fn structure(location: &str) -> Self {
+        InvalidDataError { detail: InvalidData::UnexpectedStructure { location: location.to_string() }, ..Default::default() }
     }
-}
 
-impl std::error::Error for InvalidDataError {}
+    /// Value with unit `got` instead of `expected`
+    pub fn unit(expected: &str, got: &str) -> Self {
+        InvalidDataError { detail: InvalidData::UnitMismatch { expected: expected.to_string(), got: got.to_string() }, ..Default::default() }
+    }
 
-#[derive(Debug)]
+    /// Unparseable event or receptacle identifier
+    pub fn identifier(raw: &str) -> Self {
+        InvalidDataError { detail: InvalidData::BadIdentifier { raw: raw.to_string() }, ..Default::default() }
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("PDU {pdu} serial number changed from {expected} to {found}")]
+/// Identity Error - PDU reports a different PEM serial number than on first contact
+pub struct IdentityMismatchError {
+    /// PDU number
+    pub pdu: u8,
+    /// PEM serial number recorded on first contact
+    pub expected: String,
+    /// PEM serial number reported now
+    pub found: String,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("device not ready after {} seconds", waited.as_secs())]
+/// Error type for a device which did not become ready in time
+pub struct ReadinessTimeoutError {
+    /// Time waited for the readiness check to succeed
+    pub waited: Duration,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{}", match deadline {
+    Some(deadline) => format!("operation did not finish within {:?}", deadline),
+    None => "request timed out".to_string(),
+})]
+/// Timeout Error - PDU did not answer in time
+pub struct TimeoutError {
+    /// Configured operation deadline, `None` for connect/read timeouts
+    pub deadline: Option<Duration>,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("device unreachable after {failures} failures, next attempt in {} seconds", retry_in.as_secs())]
+/// Circuit Open Error - requests are rejected after repeated failures
+pub struct CircuitOpenError {
+    /// Consecutive failures before the circuit opened
+    pub failures: u32,
+    /// Time until the next probe request is let through
+    pub retry_in: Duration,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("response exceeds the limit of {limit} bytes")]
+/// Response Too Large Error - PDU returned more data than allowed
+pub struct ResponseTooLargeError {
+    /// Configured maximum response size in bytes
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{operation} not supported by {target}")]
+/// Not Supported Error - the module lacks the capability for an operation
+pub struct NotSupportedError {
+    /// Requested operation, e.g. `Reboot`
+    pub operation: String,
+    /// Module or receptacle lacking the capability, e.g. `MPXBRM-EBBC6N3N module`
+    pub target: String,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("invalid address {address}: {reason}")]
+/// Invalid Address Error - PDU, branch or receptacle number of 0 or not present
+pub struct InvalidAddressError {
+    /// Requested address, e.g. `1-9-3`
+    pub address: String,
+    /// Reason for the rejection, e.g. `not present on the device`
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("invalid setting {field}: {reason}")]
+/// Invalid Setting Error - settings value outside of the limits of the card
+pub struct InvalidSettingError {
+    /// Settings field, e.g. `over_current_alarm_threshold`
+    pub field: String,
+    /// Reason for the rejection, e.g. `250 % out of range 0-100 %`
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("authentication failed (HTTP status {status})")]
+/// Authentication Error - PDU rejected the configured credentials
+pub struct AuthenticationError {
+    /// HTTP status code returned by the PDU
+    pub status: u16,
+}
+
+#[derive(Debug, thiserror::Error)]
 /// A collection of all possible errors
 pub enum MPXError {
-    Reqwest(reqwest::Error),
-    HTMLParser(html_parser::Error),
-    ParseIntError(std::num::ParseIntError),
-    ParseFloatError(std::num::ParseFloatError),
+    #[cfg(feature = "client")]
+    #[error("HTTP request failed")]
+    Reqwest(#[source] reqwest::Error),
+    #[error("could not parse HTML")]
+    HTMLParser(#[from] html_parser::Error),
+    #[error("invalid integer in PDU response")]
+    ParseIntError(#[from] std::num::ParseIntError),
+    #[error("invalid number in PDU response")]
+    ParseFloatError(#[from] std::num::ParseFloatError),
+    #[error("unknown value in PDU response")]
     EnumParseError(EnumParseError),
-    MissingDataError(MissingDataError),
-    InvalidDataError(InvalidDataError),
-}
-
+    #[error(transparent)]
+    MissingDataError(#[from] MissingDataError),
+    #[error(transparent)]
+    InvalidDataError(#[from] InvalidDataError),
+    #[error(transparent)]
+    IdentityMismatch(#[from] IdentityMismatchError),
+    #[error(transparent)]
+    ReadinessTimeout(#[from] ReadinessTimeoutError),
+    #[error(transparent)]
+    Authentication(#[from] AuthenticationError),
+    #[error(transparent)]
+    Timeout(#[from] TimeoutError),
+    #[error(transparent)]
+    CircuitOpen(#[from] CircuitOpenError),
+    #[error(transparent)]
+    ResponseTooLarge(#[from] ResponseTooLargeError),
+    #[error(transparent)]
+    NotSupported(#[from] NotSupportedError),
+    #[error(transparent)]
+    InvalidAddress(#[from] InvalidAddressError),
+    #[error(transparent)]
+    InvalidSetting(#[from] InvalidSettingError),
+}
+
+#[cfg(feature = "client")]
 impl From<reqwest::Error> for MPXError {
     fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            return MPXError::Timeout(TimeoutError { deadline: None });
+        }
         MPXError::Reqwest(e)
     }
 }
 
-impl From<html_parser::Error> for MPXError {
-    fn from(e: html_parser::Error) -> Self {
-        MPXError::HTMLParser(e)
+impl From<EnumParseError> for MPXError {
+    fn from(e: EnumParseError) -> Self {
+        MPXError::EnumParseError(e)
     }
 }
 
-impl From<std::num::ParseIntError> for MPXError {
-    fn from(e: std::num::ParseIntError) -> Self {
-        MPXError::ParseIntError(e)
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+#[non_exhaustive]
+/// Coarse category of a [`MPXError`]
+pub enum ErrorKind {
+    /// Connection or HTTP level failure, including timeouts
+    Network,
+    /// The PDU rejected the credentials
+    Authentication,
+    /// The PDU response could not be parsed
+    Parse,
+    /// The device does not match the pinned serial number
+    Identity,
+    /// A deadline or readiness timeout expired
+    Timeout,
+    /// Request refused locally (open circuit breaker, response size limit)
+    Refused,
+    /// The module lacks the capability for the operation
+    NotSupported,
+    /// PDU, branch or receptacle number of 0 or not present
+    InvalidAddress,
+    /// Settings value outside of the limits of the card
+    InvalidSetting,
+}
+
+impl MPXError {
+    /// Category of the error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            #[cfg(feature = "client")]
+            MPXError::Reqwest(_) => ErrorKind::Network,
+            MPXError::HTMLParser(_) |
+            MPXError::ParseIntError(_) |
+            MPXError::ParseFloatError(_) |
+            MPXError::EnumParseError(_) |
+            MPXError::MissingDataError(_) |
+            MPXError::InvalidDataError(_) => ErrorKind::Parse,
+            MPXError::IdentityMismatch(_) => ErrorKind::Identity,
+            MPXError::Authentication(_) => ErrorKind::Authentication,
+            MPXError::Timeout(e) if e.deadline.is_none() => ErrorKind::Network,
+            MPXError::Timeout(_) |
+            MPXError::ReadinessTimeout(_) => ErrorKind::Timeout,
+            MPXError::CircuitOpen(_) |
+            MPXError::ResponseTooLarge(_) => ErrorKind::Refused,
+            MPXError::NotSupported(_) => ErrorKind::NotSupported,
+            MPXError::InvalidAddress(_) => ErrorKind::InvalidAddress,
+            MPXError::InvalidSetting(_) => ErrorKind::InvalidSetting,
+        }
     }
-}
 
-impl From<std::num::ParseFloatError> for MPXError {
-    fn from(e: std::num::ParseFloatError) -> Self {
-        MPXError::ParseFloatError(e)
+    /// Transient failure, retrying the request may succeed: connection
+    /// failures and request timeouts, but not an expired overall deadline
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            #[cfg(feature = "client")]
+            MPXError::Reqwest(e) => e.is_connect() || e.is_request() || e.is_timeout(),
+            MPXError::Timeout(e) => e.deadline.is_none(),
+            _ => false,
+        }
     }
-}
 
-impl From<EnumParseError> for MPXError {
-    fn from(e: EnumParseError) -> Self {
-        MPXError::EnumParseError(e)
+    /// The PDU rejected the credentials
+    pub fn is_auth(&self) -> bool {
+        self.kind() == ErrorKind::Authentication
     }
-}
 
-impl From<MissingDataError> for MPXError {
-    fn from(e: MissingDataError) -> Self {
-        MPXError::MissingDataError(e)
+    /// The PDU response could not be parsed, e.g. due to unsupported firmware
+    pub fn is_parse(&self) -> bool {
+        self.kind() == ErrorKind::Parse
     }
 }
 
-impl From<InvalidDataError> for MPXError {
-    fn from(e: InvalidDataError) -> Self {
-        MPXError::InvalidDataError(e)
+impl MPXError {
+    /// Record the table section a parse error occurred in
+    pub(crate) fn in_table(mut self, table: &str) -> Self {
+        match &mut self {
+            MPXError::MissingDataError(MissingDataError { table: t @ None, .. }) |
+            MPXError::InvalidDataError(InvalidDataError { table: t @ None, .. }) => *t = Some(table.to_string()),
+            _ => {},
+        }
+        self
+    }
+
+    /// Record the page a parse error occurred in, e.g. after calling
+    /// [`parse_events`] on a page fetched by other means
+    pub fn at_url(mut self, url: &str) -> Self {
+        match &mut self {
+            MPXError::MissingDataError(MissingDataError { url: u @ None, .. }) |
+            MPXError::InvalidDataError(InvalidDataError { url: u @ None, .. }) => *u = Some(url.to_string()),
+            _ => {},
+        }
+        self
     }
 }
 
@@ -182,7 +517,8 @@ pub enum BranchCmd {
     ResetEnergy,
 }
 
-#[derive(Copy,Clone,Debug,PartialEq,Serialize)]
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Wiring Type (1-Phase or 3-Phase)
 pub enum WiringType {
     /// 1-Phase / 3 Wire (L, N, PE)
@@ -206,14 +542,17 @@ impl FromStr for WiringType {
 impl std::fmt::Display for WiringType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            WiringType::OnePhase => write!(f, "1-Phase"),
-            WiringType::ThreePhase => write!(f, "3-Phase"),
+            WiringType::OnePhase => write!(f, "{}", l10n::tr("1-Phase")),
+            WiringType::ThreePhase => write!(f, "{}", l10n::tr("3-Phase")),
         }
     }
 }
 
 /// Firmware Version
-#[derive(Copy,Clone,Debug,PartialEq,Serialize)]
+///
+/// Versions are ordered component by component, so `0.3.4.2 < 0.10.0.0`.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,PartialOrd,Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct FWVersion {
     pub p0: u8,
     pub p1: u8,
@@ -221,19 +560,33 @@ pub struct FWVersion {
     pub p3: u8,
 }
 
+impl FWVersion {
+    pub fn new(p0: u8, p1: u8, p2: u8, p3: u8) -> Self {
+        FWVersion { p0, p1, p2, p3 }
+    }
+
+    /// Firmware is `p0.p1.p2.p3` or newer
+    pub fn at_least(&self, p0: u8, p1: u8, p2: u8, p3: u8) -> bool {
+        *self >= FWVersion::new(p0, p1, p2, p3)
+    }
+}
+
 impl FromStr for FWVersion {
     type Err = MPXError;
 
+    /// Parse a dashed (`0-3-4-2`, as reported by the PDU) or dotted
+    /// (`0.3.4.2`) version
     fn from_str(input: &str) -> Result<FWVersion, Self::Err> {
-        let parts: Vec<&str> = input.split("-").collect();
+        let separator = if input.contains('.') { '.' } else { '-' };
+        let parts: Vec<&str> = input.split(separator).collect();
         if parts.len() == 4 {
-            let p0 = parts.get(0).unwrap().parse::<u8>()?;
+            let p0 = parts.first().unwrap().parse::<u8>()?;
             let p1 = parts.get(1).unwrap().parse::<u8>()?;
             let p2 = parts.get(2).unwrap().parse::<u8>()?;
             let p3 = parts.get(3).unwrap().parse::<u8>()?;
-            Ok(FWVersion { p0: p0, p1: p1, p2: p2, p3: p3 })
+            Ok(FWVersion { p0, p1, p2, p3 })
         } else {
-            Err(MPXError::MissingDataError(MissingDataError))
+            Err(MPXError::MissingDataError(MissingDataError::default()))
         }
     }
 }
@@ -244,8 +597,10 @@ impl std::fmt::Display for FWVersion {
     }
 }
 
-#[derive(Copy,Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Receptacle type
+#[non_exhaustive]
 pub enum ReceptacleType {
     /// Receptacle for C13 connector
     C13,
@@ -253,17 +608,21 @@ pub enum ReceptacleType {
     C19,
     /// Receptacle for Schuko connector
     Schuko,
+    /// Not (yet) known to this library, with the raw text from the PDU
+    Unknown(String),
 }
 
 impl FromStr for ReceptacleType {
     type Err = ();
 
     fn from_str(input: &str) -> Result<ReceptacleType, Self::Err> {
+        // Only the C13 string is known from a device (testdata/receptacle-info.htm),
+        // all other types are kept as reported until captures are available.
         match input {
             "IEC 60320 Sheet F C13" => Ok(ReceptacleType::C13),
             "C19" => Ok(ReceptacleType::C19), /* TODO */
             "Schuko" => Ok(ReceptacleType::Schuko), /* TODO */
-            _ => Err(()),
+            _ => Ok(ReceptacleType::Unknown(input.to_string())),
         }
     }
 }
@@ -274,12 +633,15 @@ impl std::fmt::Display for ReceptacleType {
             ReceptacleType::C13 => write!(f, "C13"),
             ReceptacleType::C19 => write!(f, "C19"),
             ReceptacleType::Schuko => write!(f, "Schuko"),
+            ReceptacleType::Unknown(raw) => write!(f, "{}", raw),
         }
     }
 }
 
-#[derive(Copy,Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Liebert MPX PEM model
+#[non_exhaustive]
 pub enum PEMModel {
     /// 1 phase 32A elementary
     EHAEXQ30,
@@ -297,6 +659,8 @@ pub enum PEMModel {
     EHBEXZ30,
     /// 3 phase 63A monitored
     EHBXXZ30,
+    /// Not (yet) known to this library, with the raw text from the PDU
+    Unknown(String),
 }
 
 impl FromStr for PEMModel {
@@ -312,13 +676,24 @@ impl FromStr for PEMModel {
             "MPXPEM-EHAXXR30" => Ok(PEMModel::EHAXXR30),
             "MPXPEM-EHBEXZ30" => Ok(PEMModel::EHBEXZ30),
             "MPXPEM-EHBXXZ30" => Ok(PEMModel::EHBXXZ30),
-            _ => Err(()),
+            _ => Ok(PEMModel::Unknown(input.to_string())),
         }
     }
 }
 
-#[derive(Copy,Clone,Debug,PartialEq,Serialize)]
+impl std::fmt::Display for PEMModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PEMModel::Unknown(raw) => write!(f, "{}", raw),
+            _ => write!(f, "MPXPEM-{:?}", self),
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Liebert MPX BRM model
+#[non_exhaustive]
 pub enum BRMModel {
     /// C13 L1 elementary
     EEBC7N1N,
@@ -374,6 +749,8 @@ pub enum BRMModel {
     ERBC3P2N,
     /// Schuko L3 receptacle-managed
     ERBC3P3N,
+    /// Not (yet) known to this library, with the raw text from the PDU
+    Unknown(String),
 }
 
 impl FromStr for BRMModel {
@@ -408,13 +785,24 @@ impl FromStr for BRMModel {
             "MPXBRM-ERBC3P1N" => Ok(BRMModel::ERBC3P1N),
             "MPXBRM-ERBC3P2N" => Ok(BRMModel::ERBC3P2N),
             "MPXBRM-ERBC3P3N" => Ok(BRMModel::ERBC3P3N),
-            _ => Err(()),
+            _ => Ok(BRMModel::Unknown(input.to_string())),
         }
     }
 }
 
-#[derive(Copy,Clone,Debug,PartialEq,Serialize)]
+impl std::fmt::Display for BRMModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BRMModel::Unknown(raw) => write!(f, "{}", raw),
+            _ => write!(f, "MPXBRM-{:?}", self),
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Event Type
+#[non_exhaustive]
 pub enum EventType {
     ReceptacleOverCurrent,
     ReceptacleLowCurrent,
@@ -435,6 +823,8 @@ pub enum EventType {
     PDUFailure,
     PDUCommunicationFail,
     PDUOverCurrentN,
+    /// Not (yet) known to this library, with the raw text from the PDU
+    Unknown(String),
 }
 
 impl FromStr for EventType {
@@ -461,13 +851,42 @@ impl FromStr for EventType {
             "PDU Failure" => Ok(EventType::PDUFailure),
             "PDU Communication Fail" => Ok(EventType::PDUCommunicationFail),
             "PDU Neutral Over Current" => Ok(EventType::PDUOverCurrentN),
-            _ => Err(()),
+            _ => Ok(EventType::Unknown(input.to_string())),
         }
     }
 }
 
-#[derive(Copy,Clone,Debug,PartialEq,Serialize)]
-/// Event Level (e.g. warning or alarm)
+impl std::fmt::Display for EventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            EventType::ReceptacleOverCurrent => "Receptacle Over Current",
+            EventType::ReceptacleLowCurrent => "Receptacle Low Current",
+            EventType::BranchLowVoltage => "Branch Low Voltage (LN)",
+            EventType::BranchOverCurrent => "Branch Over Current",
+            EventType::BranchLowCurrent => "Branch Low Current",
+            EventType::BranchFailure => "Branch Failure",
+            EventType::BranchBreakerOpen => "Branch Breaker Open",
+            EventType::PDULowVoltageL1 => "PDU Low Voltage L1-N",
+            EventType::PDULowVoltageL2 => "PDU Low Voltage L2-N",
+            EventType::PDULowVoltageL3 => "PDU Low Voltage L3-N",
+            EventType::PDUOverCurrentL1 => "PDU Over Current L1",
+            EventType::PDUOverCurrentL2 => "PDU Over Current L2",
+            EventType::PDUOverCurrentL3 => "PDU Over Current L3",
+            EventType::PDULowCurrentL1 => "PDU Low Current L1",
+            EventType::PDULowCurrentL2 => "PDU Low Current L2",
+            EventType::PDULowCurrentL3 => "PDU Low Current L3",
+            EventType::PDUFailure => "PDU Failure",
+            EventType::PDUCommunicationFail => "PDU Communication Fail",
+            EventType::PDUOverCurrentN => "PDU Neutral Over Current",
+            EventType::Unknown(raw) => return write!(f, "{}", raw),
+        };
+        write!(f, "{}", l10n::tr(name))
+    }
+}
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq,PartialOrd,Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Event Level (e.g. warning or alarm), ordered by severity
 pub enum EventLevel {
     OK,
     INFO,
@@ -475,6 +894,18 @@ pub enum EventLevel {
     ALARM,
 }
 
+impl EventLevel {
+    /// Numeric severity: 0 (OK), 1 (INFO), 2 (WARNING) or 3 (ALARM)
+    pub fn severity(&self) -> u8 {
+        match self {
+            EventLevel::OK => 0,
+            EventLevel::INFO => 1,
+            EventLevel::WARNING => 2,
+            EventLevel::ALARM => 3,
+        }
+    }
+}
+
 impl FromStr for EventLevel {
     type Err = ();
 
@@ -489,7 +920,19 @@ impl FromStr for EventLevel {
     }
 }
 
-#[derive(Debug,PartialEq,Serialize)]
+impl std::fmt::Display for EventLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EventLevel::OK => write!(f, "{}", l10n::tr("OK")),
+            EventLevel::INFO => write!(f, "{}", l10n::tr("Info")),
+            EventLevel::WARNING => write!(f, "{}", l10n::tr("Warning")),
+            EventLevel::ALARM => write!(f, "{}", l10n::tr("Alarm")),
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// PDU Event (e.g. a warning or an alarm)
 pub struct Event {
     pub level: EventLevel,
@@ -499,8 +942,10 @@ pub struct Event {
     pub event: EventType,
 }
 
-#[derive(Copy,Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Line Source (e.g. L1-N)
+#[non_exhaustive]
 pub enum LineSource {
     /// Line Source is L1-N
     L1toN,
@@ -508,6 +953,8 @@ pub enum LineSource {
     L2toN,
     /// Line Source is L3-N
     L3toN,
+    /// Not (yet) known to this library, with the raw text from the PDU
+    Unknown(String),
 }
 
 impl FromStr for LineSource {
@@ -518,7 +965,7 @@ impl FromStr for LineSource {
             "Type L1-N" => Ok(LineSource::L1toN),
             "Type L2-N" => Ok(LineSource::L2toN),
             "Type L3-N" => Ok(LineSource::L3toN),
-            _ => Err(()),
+            _ => Ok(LineSource::Unknown(input.to_string())),
         }
     }
 }
@@ -529,25 +976,44 @@ impl std::fmt::Display for LineSource {
             LineSource::L1toN => write!(f, "L1-N"),
             LineSource::L2toN => write!(f, "L2-N"),
             LineSource::L3toN => write!(f, "L3-N"),
+            LineSource::Unknown(raw) => write!(f, "{}", raw),
         }
     }
 }
 
 
-#[derive(Copy,Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Hardware capabilities (measurement / control)
+#[non_exhaustive]
 pub enum Capability {
     /// Receptacles can be measured and controlled
     MeasureAndControl,
+    /// Not (yet) known to this library, with the raw text from the PDU
+    Unknown(String),
+}
+
+impl Capability {
+    /// Receptacles can be switched; unknown capabilities are not
+    /// assumed to be controllable
+    pub fn can_control(&self) -> bool {
+        matches!(self, Capability::MeasureAndControl)
+    }
+
+    /// Receptacle measurements are reported
+    pub fn can_measure(&self) -> bool {
+        matches!(self, Capability::MeasureAndControl)
+    }
 }
 
 impl FromStr for Capability {
     type Err = ();
 
     fn from_str(input: &str) -> Result<Capability, Self::Err> {
+        // Only this string is known from a device (testdata/receptacle-info.htm)
         match input {
             "All Measurements/Control" => Ok(Capability::MeasureAndControl),
-            _ => Err(()),
+            _ => Ok(Capability::Unknown(input.to_string())),
         }
     }
 }
@@ -555,11 +1021,40 @@ impl FromStr for Capability {
 impl std::fmt::Display for Capability {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Capability::MeasureAndControl => write!(f, "Measure & Control"),
+            Capability::MeasureAndControl => write!(f, "{}", l10n::tr("Measure & Control")),
+            Capability::Unknown(raw) => write!(f, "{}", raw),
         }
     }
 }
 
+#[derive(Clone,Debug)]
+/// Condensed PDU Information
+pub struct PDUListEntry {
+    /// PDU number (usually 1)
+    pub pdu: u8,
+    /// PDU user label
+    pub label: String,
+    /// PDU health status
+    pub status: EventLevel,
+    /// Power entry module model
+    pub model: PEMModel,
+}
+
+#[derive(Clone,Debug)]
+/// Condensed Branch Information
+pub struct BranchListEntry {
+    /// PDU number (usually 1)
+    pub pdu: u8,
+    /// Branch number (usually 1-6)
+    pub branch: u8,
+    /// Branch user label
+    pub label: String,
+    /// Branch health status
+    pub status: EventLevel,
+    /// Branch receptacle module model
+    pub model: BRMModel,
+}
+
 #[derive(Clone,Debug)]
 /// Condensed Receptacle Information
 pub struct ReceptacleListEntry {
@@ -579,9 +1074,43 @@ pub struct ReceptacleListEntry {
     pub label: String,
 }
 
+/// Unit in comparable form
+///
+/// Case, whitespace (including `&nbsp;`) and an `AC` suffix are ignored,
+/// so `kWH` matches `kWh` and `A AC` matches `A`.
+fn normalize_unit(unit: &str) -> String {
+    let unit: String = unit.replace("&nbsp;", "").to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    match unit.strip_suffix("ac") {
+        Some(base) if !base.is_empty() => base.to_string(),
+        _ => unit,
+    }
+}
+
+/// Power of ten converting a value reported in unit `got` into unit
+/// `expected`
+///
+/// Besides formatting differences (see [`normalize_unit`]) a kilo prefix
+/// is converted, e.g. `Wh` into `kWh` or `kW` into `W`.
+fn unit_exponent(got: &str, expected: &str) -> Option<i32> {
+    let got = normalize_unit(got);
+    let expected = normalize_unit(expected);
+
+    if got == expected {
+        Some(0)
+    } else if !expected.is_empty() && got.strip_prefix('k') == Some(expected.as_str()) {
+        Some(3)
+    } else if !got.is_empty() && expected.strip_prefix('k') == Some(got.as_str()) {
+        Some(-3)
+    } else {
+        None
+    }
+}
+
 #[derive(Clone,Debug)]
 /// Internal data structure for a table value with unit
 struct TableValue {
+    /// key (e.g. "PDU Voltage L1-N")
+    key: String,
     /// value (e.g. "23.42", "0.0")
     value: String,
     /// unit (e.g. "kWH", "VAC" or "sec")
@@ -589,20 +1118,69 @@ struct TableValue {
 }
 
 impl TableValue {
+    fn invalid(&self) -> MPXError {
+        MPXError::InvalidDataError(InvalidDataError { key: Some(self.key.clone()), ..Default::default() })
+    }
+
+    /// Power of ten converting the value into `unit`, see [`unit_exponent`]
+    fn check_unit(&self, unit: &str) -> Result<i32,MPXError> {
+        unit_exponent(&self.unit, unit).ok_or_else(|| {
+            MPXError::InvalidDataError(InvalidDataError { key: Some(self.key.clone()), ..InvalidDataError::unit(unit, &self.unit) })
+        })
+    }
+
     fn get_f32(&self, unit: &str) -> Result<f32,MPXError> {
-        if self.unit != unit {
-            return Err(MPXError::InvalidDataError(InvalidDataError))
-        }
+        let exponent = self.check_unit(unit)?;
+        let value = self.value.parse::<f32>().map_err(|_| self.invalid())?;
 
-        Ok(self.value.parse::<f32>()?)
+        match exponent {
+            0 => Ok(value),
+            e if e > 0 => Ok(value * 10f32.powi(e)),
+            e => Ok(value / 10f32.powi(-e)),
+        }
     }
 
     fn get_u32(&self, unit: &str) -> Result<u32,MPXError> {
-        if self.unit != unit {
-            return Err(MPXError::InvalidDataError(InvalidDataError))
+        if self.check_unit(unit)? == 0 {
+            return self.value.parse::<u32>().map_err(|_| self.invalid());
+        }
+
+        match self.get_f32(unit)? {
+            value if value >= 0.0 => Ok(value.round() as u32),
+            _ => Err(self.invalid()),
+        }
+    }
+
+    fn get_duration(&self, unit: &str) -> Result<Duration,MPXError> {
+        let value = self.get_u32(unit)?;
+
+        match unit {
+            "sec" => Ok(Duration::from_secs(value.into())),
+            _ => Err(self.invalid()),
         }
+    }
 
-        Ok(self.value.parse::<u32>()?)
+    /// Parse the value into an enum or version
+    fn parse<T: FromStr>(&self) -> Result<T,MPXError> {
+        self.value.parse::<T>().map_err(|_| self.invalid())
+    }
+}
+
+/// Lookup of table values, failing with the missing key
+trait Field {
+    fn field(&self, key: &str) -> Result<&TableValue,MPXError>;
+
+    /// Row which does not exist on all models (e.g. L2/L3 on single phase PDUs)
+    fn optional(&self, key: &str) -> Option<&TableValue>;
+}
+
+impl Field for RawDataTable {
+    fn field(&self, key: &str) -> Result<&TableValue,MPXError> {
+        self.get(key).ok_or_else(|| MissingDataError { key: Some(key.to_string()), ..Default::default() }.into())
+    }
+
+    fn optional(&self, key: &str) -> Option<&TableValue> {
+        self.get(key)
     }
 }
 
@@ -615,8 +1193,27 @@ struct InfoTables {
     hardware: RawDataTable,
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+impl InfoTables {
+    /// Fail with a `MissingDataError` if one of the `(table, key)` rows is missing
+    fn require(&self, rows: &[(&str, &str)]) -> Result<(), MPXError> {
+        for (table, key) in rows {
+            let rows = match *table {
+                "status" => &self.status,
+                "events" => &self.events,
+                "settings" => &self.settings,
+                _ => &self.hardware,
+            };
+            rows.field(key).map_err(|e| e.in_table(table))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Status from a pem module
+///
+/// L2 and L3 values are `None` on single phase PDUs.
 pub struct PDUStatus {
     /// accumulated energy in kWh
     pub accumulated_energy: f32,
@@ -625,29 +1222,29 @@ pub struct PDUStatus {
     /// voltage L1-N in V AC
     pub voltage_l1_n: f32,
     /// voltage L2-N in V AC
-    pub voltage_l2_n: f32,
+    pub voltage_l2_n: Option<f32>,
     /// voltage L3-N in V AC
-    pub voltage_l3_n: f32,
+    pub voltage_l3_n: Option<f32>,
     /// current L1 in A AC
     pub current_l1: f32,
     /// current L2 in A AC
-    pub current_l2: f32,
+    pub current_l2: Option<f32>,
     /// current L3 in A AC
-    pub current_l3: f32,
+    pub current_l3: Option<f32>,
     /// current N in A AC
     pub current_n: f32,
     /// current available before alarm L1 in A AC
     pub current_available_to_alarm_l1: f32,
     /// current available before alarm L2 in A AC
-    pub current_available_to_alarm_l2: f32,
+    pub current_available_to_alarm_l2: Option<f32>,
     /// current available before alarm L3 in A AC
-    pub current_available_to_alarm_l3: f32,
+    pub current_available_to_alarm_l3: Option<f32>,
     /// line utilization L1 in %
     pub current_utilization_l1: f32,
     /// line utilization L2 in %
-    pub current_utilization_l2: f32,
+    pub current_utilization_l2: Option<f32>,
     /// line utilization L3 in %
-    pub current_utilization_l3: f32,
+    pub current_utilization_l3: Option<f32>,
     /// line frequency in Hz
     pub line_frequency: f32,
 }
@@ -655,28 +1252,31 @@ pub struct PDUStatus {
 impl PDUStatus {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(PDUStatus {
-            accumulated_energy: table.get("PDU Accumulated Energy").ok_or(MissingDataError)?.get_f32("kWH")?,
-            input_power: table.get("PDU Total Input Power").ok_or(MissingDataError)?.get_f32("W")?,
-            voltage_l1_n: table.get("PDU Voltage L1-N").ok_or(MissingDataError)?.get_f32("VAC")?,
-            voltage_l2_n: table.get("PDU Voltage L2-N").ok_or(MissingDataError)?.get_f32("VAC")?,
-            voltage_l3_n: table.get("PDU Voltage L3-N").ok_or(MissingDataError)?.get_f32("VAC")?,
-            current_l1: table.get("PDU Current L1").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_l2: table.get("PDU Current L2").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_l3: table.get("PDU Current L3").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_n: table.get("PDU Neutral Current Measurement").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_available_to_alarm_l1: table.get("PDU Available L1 Current Until Alarm").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_available_to_alarm_l2: table.get("PDU Available L2 Current Until Alarm").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_available_to_alarm_l3: table.get("PDU Available L3 Current Until Alarm").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_utilization_l1: table.get("PDU Percent L1 Current Utilization").ok_or(MissingDataError)?.get_f32("%")?,
-            current_utilization_l2: table.get("PDU Percent L2 Current Utilization").ok_or(MissingDataError)?.get_f32("%")?,
-            current_utilization_l3: table.get("PDU Percent L3 Current Utilization").ok_or(MissingDataError)?.get_f32("%")?,
-            line_frequency: table.get("PEM Line Frequency").ok_or(MissingDataError)?.get_f32("Hz")?,
+            accumulated_energy: table.field("PDU Accumulated Energy")?.get_f32("kWH")?,
+            input_power: table.field("PDU Total Input Power")?.get_f32("W")?,
+            voltage_l1_n: table.field("PDU Voltage L1-N")?.get_f32("VAC")?,
+            voltage_l2_n: table.optional("PDU Voltage L2-N").map(|v| v.get_f32("VAC")).transpose()?,
+            voltage_l3_n: table.optional("PDU Voltage L3-N").map(|v| v.get_f32("VAC")).transpose()?,
+            current_l1: table.field("PDU Current L1")?.get_f32("A AC")?,
+            current_l2: table.optional("PDU Current L2").map(|v| v.get_f32("A AC")).transpose()?,
+            current_l3: table.optional("PDU Current L3").map(|v| v.get_f32("A AC")).transpose()?,
+            current_n: table.field("PDU Neutral Current Measurement")?.get_f32("A AC")?,
+            current_available_to_alarm_l1: table.field("PDU Available L1 Current Until Alarm")?.get_f32("A AC")?,
+            current_available_to_alarm_l2: table.optional("PDU Available L2 Current Until Alarm").map(|v| v.get_f32("A AC")).transpose()?,
+            current_available_to_alarm_l3: table.optional("PDU Available L3 Current Until Alarm").map(|v| v.get_f32("A AC")).transpose()?,
+            current_utilization_l1: table.field("PDU Percent L1 Current Utilization")?.get_f32("%")?,
+            current_utilization_l2: table.optional("PDU Percent L2 Current Utilization").map(|v| v.get_f32("%")).transpose()?,
+            current_utilization_l3: table.optional("PDU Percent L3 Current Utilization").map(|v| v.get_f32("%")).transpose()?,
+            line_frequency: table.field("PEM Line Frequency")?.get_f32("Hz")?,
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Settings from a pem module
+///
+/// L2 and L3 values are `None` on single phase PDUs.
 pub struct PDUSettings {
     /// PDU user label
     pub label: String,
@@ -695,41 +1295,42 @@ pub struct PDUSettings {
     /// L1 over current warning threshold in %
     pub l1_over_current_warning_threshold: u32,
     /// L2 low current alarm threshold in %
-    pub l2_low_current_alarm_threshold: u32,
+    pub l2_low_current_alarm_threshold: Option<u32>,
     /// L2 over current alarm threshold in %
-    pub l2_over_current_alarm_threshold: u32,
+    pub l2_over_current_alarm_threshold: Option<u32>,
     /// L2 over current warning threshold in %
-    pub l2_over_current_warning_threshold: u32,
+    pub l2_over_current_warning_threshold: Option<u32>,
     /// L3 low current alarm threshold in %
-    pub l3_low_current_alarm_threshold: u32,
+    pub l3_low_current_alarm_threshold: Option<u32>,
     /// L3 over current alarm threshold in %
-    pub l3_over_current_alarm_threshold: u32,
+    pub l3_over_current_alarm_threshold: Option<u32>,
     /// L3 over current warning threshold in %
-    pub l3_over_current_warning_threshold: u32,
+    pub l3_over_current_warning_threshold: Option<u32>,
 }
 
 impl PDUSettings {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(PDUSettings {
-            label: table.get("PDU User Assigned Label").ok_or(MissingDataError)?.value.clone(),
-            asset_tag_1: table.get("PDU Asset Tag 01").ok_or(MissingDataError)?.value.clone().replace("&nbsp;", ""),
-            asset_tag_2: table.get("PDU Asset Tag 02").ok_or(MissingDataError)?.value.clone().replace("&nbsp;", ""),
-            n_over_current_alarm_threshold: table.get("Neutral Over Current Alarm Threshold").ok_or(MissingDataError)?.get_u32("%")?,
-            n_over_current_warning_threshold: table.get("Neutral Over Current Warning Threshold").ok_or(MissingDataError)?.get_u32("%")?,
-            l1_over_current_warning_threshold: table.get("Over Current Warn Threshold L1").ok_or(MissingDataError)?.get_u32("%")?,
-            l2_over_current_warning_threshold: table.get("Over Current Warn Threshold L2").ok_or(MissingDataError)?.get_u32("%")?,
-            l3_over_current_warning_threshold: table.get("Over Current Warn Threshold L3").ok_or(MissingDataError)?.get_u32("%")?,
-            l1_over_current_alarm_threshold: table.get("Over Current Alarm Threshold L1").ok_or(MissingDataError)?.get_u32("%")?,
-            l2_over_current_alarm_threshold: table.get("Over Current Alarm Threshold L2").ok_or(MissingDataError)?.get_u32("%")?,
-            l3_over_current_alarm_threshold: table.get("Over Current Alarm Threshold L3").ok_or(MissingDataError)?.get_u32("%")?,
-            l1_low_current_alarm_threshold: table.get("Low Current Alarm Threshold L1").ok_or(MissingDataError)?.get_u32("%")?,
-            l2_low_current_alarm_threshold: table.get("Low Current Alarm Threshold L2").ok_or(MissingDataError)?.get_u32("%")?,
-            l3_low_current_alarm_threshold: table.get("Low Current Alarm Threshold L3").ok_or(MissingDataError)?.get_u32("%")?,
+            label: table.field("PDU User Assigned Label")?.value.clone(),
+            asset_tag_1: table.field("PDU Asset Tag 01")?.value.clone(),
+            asset_tag_2: table.field("PDU Asset Tag 02")?.value.clone(),
+            n_over_current_alarm_threshold: table.field("Neutral Over Current Alarm Threshold")?.get_u32("%")?,
+            n_over_current_warning_threshold: table.field("Neutral Over Current Warning Threshold")?.get_u32("%")?,
+            l1_over_current_warning_threshold: table.field("Over Current Warn Threshold L1")?.get_u32("%")?,
+            l2_over_current_warning_threshold: table.optional("Over Current Warn Threshold L2").map(|v| v.get_u32("%")).transpose()?,
+            l3_over_current_warning_threshold: table.optional("Over Current Warn Threshold L3").map(|v| v.get_u32("%")).transpose()?,
+            l1_over_current_alarm_threshold: table.field("Over Current Alarm Threshold L1")?.get_u32("%")?,
+            l2_over_current_alarm_threshold: table.optional("Over Current Alarm Threshold L2").map(|v| v.get_u32("%")).transpose()?,
+            l3_over_current_alarm_threshold: table.optional("Over Current Alarm Threshold L3").map(|v| v.get_u32("%")).transpose()?,
+            l1_low_current_alarm_threshold: table.field("Low Current Alarm Threshold L1")?.get_u32("%")?,
+            l2_low_current_alarm_threshold: table.optional("Low Current Alarm Threshold L2").map(|v| v.get_u32("%")).transpose()?,
+            l3_low_current_alarm_threshold: table.optional("Low Current Alarm Threshold L3").map(|v| v.get_u32("%")).transpose()?,
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Hardware information from a pem module
 pub struct PDUHardware {
     /// PEM model description
@@ -751,29 +1352,32 @@ pub struct PDUHardware {
 impl PDUHardware {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(PDUHardware {
-            pem_model: PEMModel::from_str(&table.get("PEM Model").ok_or(MissingDataError)?.value)?,
-            wiring_type: WiringType::from_str(&table.get("The PDU input wiring type").ok_or(MissingDataError)?.value)?,
-            rated_input_voltage: table.get("Rated Input Line Voltage").ok_or(MissingDataError)?.get_u32("VAC")?,
-            rated_input_current: table.get("Rated Input Line Current").ok_or(MissingDataError)?.get_u32("A AC")?,
-            rated_input_line_frequency: table.get("Rated Input Line Frequency").ok_or(MissingDataError)?.get_u32("Hz")?,
-            fw_version: FWVersion::from_str(&table.get("Firmware Version").ok_or(MissingDataError)?.value)?,
-            serial_number: table.get("PEM Serial Number").ok_or(MissingDataError)?.value.clone(),
+            pem_model: table.field("PEM Model")?.parse::<PEMModel>()?,
+            wiring_type: table.field("The PDU input wiring type")?.parse::<WiringType>()?,
+            rated_input_voltage: table.field("Rated Input Line Voltage")?.get_u32("VAC")?,
+            rated_input_current: table.field("Rated Input Line Current")?.get_u32("A AC")?,
+            rated_input_line_frequency: table.field("Rated Input Line Frequency")?.get_u32("Hz")?,
+            fw_version: table.field("Firmware Version")?.parse::<FWVersion>()?,
+            serial_number: table.field("PEM Serial Number")?.value.clone(),
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Event information from a pem module
+///
+/// L2 and L3 values are `None` on single phase PDUs.
 pub struct PDUEvents {
     pub low_voltage_l1: EventLevel,
-    pub low_voltage_l2: EventLevel,
-    pub low_voltage_l3: EventLevel,
+    pub low_voltage_l2: Option<EventLevel>,
+    pub low_voltage_l3: Option<EventLevel>,
     pub over_current_l1: EventLevel,
-    pub over_current_l2: EventLevel,
-    pub over_current_l3: EventLevel,
+    pub over_current_l2: Option<EventLevel>,
+    pub over_current_l3: Option<EventLevel>,
     pub low_current_l1: EventLevel,
-    pub low_current_l2: EventLevel,
-    pub low_current_l3: EventLevel,
+    pub low_current_l2: Option<EventLevel>,
+    pub low_current_l3: Option<EventLevel>,
     pub failure: EventLevel,
     pub communication_fail: EventLevel,
     pub over_current_n: EventLevel,
@@ -782,24 +1386,35 @@ pub struct PDUEvents {
 impl PDUEvents {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(PDUEvents {
-            low_voltage_l1: EventLevel::from_str(&table.get("PDU Low Voltage L1-N").ok_or(MissingDataError)?.value)?,
-            low_voltage_l2: EventLevel::from_str(&table.get("PDU Low Voltage L2-N").ok_or(MissingDataError)?.value)?,
-            low_voltage_l3: EventLevel::from_str(&table.get("PDU Low Voltage L3-N").ok_or(MissingDataError)?.value)?,
-            over_current_l1: EventLevel::from_str(&table.get("PDU Over Current L1").ok_or(MissingDataError)?.value)?,
-            over_current_l2: EventLevel::from_str(&table.get("PDU Over Current L2").ok_or(MissingDataError)?.value)?,
-            over_current_l3: EventLevel::from_str(&table.get("PDU Over Current L3").ok_or(MissingDataError)?.value)?,
-            low_current_l1: EventLevel::from_str(&table.get("PDU Low Current L1").ok_or(MissingDataError)?.value)?,
-            low_current_l2: EventLevel::from_str(&table.get("PDU Low Current L2").ok_or(MissingDataError)?.value)?,
-            low_current_l3: EventLevel::from_str(&table.get("PDU Low Current L3").ok_or(MissingDataError)?.value)?,
-            failure: EventLevel::from_str(&table.get("PDU Failure").ok_or(MissingDataError)?.value)?,
-            communication_fail: EventLevel::from_str(&table.get("PDU Communication Fail").ok_or(MissingDataError)?.value)?,
-            over_current_n: EventLevel::from_str(&table.get("PDU Neutral Over Current").ok_or(MissingDataError)?.value)?,
+            low_voltage_l1: table.field("PDU Low Voltage L1-N")?.parse::<EventLevel>()?,
+            low_voltage_l2: table.optional("PDU Low Voltage L2-N").map(|v| v.parse::<EventLevel>()).transpose()?,
+            low_voltage_l3: table.optional("PDU Low Voltage L3-N").map(|v| v.parse::<EventLevel>()).transpose()?,
+            over_current_l1: table.field("PDU Over Current L1")?.parse::<EventLevel>()?,
+            over_current_l2: table.optional("PDU Over Current L2").map(|v| v.parse::<EventLevel>()).transpose()?,
+            over_current_l3: table.optional("PDU Over Current L3").map(|v| v.parse::<EventLevel>()).transpose()?,
+            low_current_l1: table.field("PDU Low Current L1")?.parse::<EventLevel>()?,
+            low_current_l2: table.optional("PDU Low Current L2").map(|v| v.parse::<EventLevel>()).transpose()?,
+            low_current_l3: table.optional("PDU Low Current L3").map(|v| v.parse::<EventLevel>()).transpose()?,
+            failure: table.field("PDU Failure")?.parse::<EventLevel>()?,
+            communication_fail: table.field("PDU Communication Fail")?.parse::<EventLevel>()?,
+            over_current_n: table.field("PDU Neutral Over Current")?.parse::<EventLevel>()?,
         })
     }
+
+    /// Most severe level of all PDU events
+    pub fn level(&self) -> EventLevel {
+        [
+            Some(self.low_voltage_l1), self.low_voltage_l2, self.low_voltage_l3,
+            Some(self.over_current_l1), self.over_current_l2, self.over_current_l3,
+            Some(self.low_current_l1), self.low_current_l2, self.low_current_l3,
+            Some(self.failure), Some(self.communication_fail), Some(self.over_current_n),
+        ].iter().flatten().copied().max().unwrap_or(EventLevel::OK)
+    }
 }
 
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Information about a PDU input module
 pub struct PDUInfo {
     pub status: PDUStatus,
@@ -808,18 +1423,39 @@ pub struct PDUInfo {
     pub hardware: PDUHardware,
 }
 
+/// Rows only present on three phase PDUs
+const THREE_PHASE_ROWS: &[(&str, &str)] = &[
+    ("status", "PDU Voltage L2-N"), ("status", "PDU Voltage L3-N"),
+    ("status", "PDU Current L2"), ("status", "PDU Current L3"),
+    ("status", "PDU Available L2 Current Until Alarm"), ("status", "PDU Available L3 Current Until Alarm"),
+    ("status", "PDU Percent L2 Current Utilization"), ("status", "PDU Percent L3 Current Utilization"),
+    ("events", "PDU Low Voltage L2-N"), ("events", "PDU Low Voltage L3-N"),
+    ("events", "PDU Over Current L2"), ("events", "PDU Over Current L3"),
+    ("events", "PDU Low Current L2"), ("events", "PDU Low Current L3"),
+    ("settings", "Over Current Warn Threshold L2"), ("settings", "Over Current Warn Threshold L3"),
+    ("settings", "Over Current Alarm Threshold L2"), ("settings", "Over Current Alarm Threshold L3"),
+    ("settings", "Low Current Alarm Threshold L2"), ("settings", "Low Current Alarm Threshold L3"),
+];
+
 impl PDUInfo {
     fn from_tables(tables: InfoTables) -> Result<Self,MPXError> {
+        // L2/L3 rows may only be missing on single phase PDUs
+        let three_phase = matches!(tables.hardware.optional("The PDU input wiring type").map(|v| v.parse::<WiringType>()), Some(Ok(WiringType::ThreePhase)));
+        if three_phase {
+            tables.require(THREE_PHASE_ROWS)?;
+        }
+
         Ok(PDUInfo {
-            status: PDUStatus::from_table(tables.status)?,
-            events: PDUEvents::from_table(tables.events)?,
-            settings: PDUSettings::from_table(tables.settings)?,
-            hardware: PDUHardware::from_table(tables.hardware)?,
+            status: PDUStatus::from_table(tables.status).map_err(|e| e.in_table("status"))?,
+            events: PDUEvents::from_table(tables.events).map_err(|e| e.in_table("events"))?,
+            settings: PDUSettings::from_table(tables.settings).map_err(|e| e.in_table("settings"))?,
+            hardware: PDUHardware::from_table(tables.hardware).map_err(|e| e.in_table("hardware"))?,
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Status from a branch module
 pub struct BranchStatus {
     /// accumulated energy in kWh
@@ -843,19 +1479,20 @@ pub struct BranchStatus {
 impl BranchStatus {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(BranchStatus {
-            accumulated_energy: table.get("Branch Accumulated Energy").ok_or(MissingDataError)?.get_f32("kWH")?,
-            voltage: table.get("Branch Voltage").ok_or(MissingDataError)?.get_f32("VAC")?,
-            current: table.get("Branch Current").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_available_to_alarm: table.get("Branch Available Current Until Alarm").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_utilization: table.get("Branch Percent Current Utilization").ok_or(MissingDataError)?.get_f32("%")?,
-            power: table.get("Branch Power").ok_or(MissingDataError)?.get_f32("W")?,
-            apparent_power: table.get("Branch Apparent Power").ok_or(MissingDataError)?.get_f32("VA")?,
-            power_factor: table.get("Branch Power Factor").ok_or(MissingDataError)?.get_f32("&nbsp;")?,
+            accumulated_energy: table.field("Branch Accumulated Energy")?.get_f32("kWH")?,
+            voltage: table.field("Branch Voltage")?.get_f32("VAC")?,
+            current: table.field("Branch Current")?.get_f32("A AC")?,
+            current_available_to_alarm: table.field("Branch Available Current Until Alarm")?.get_f32("A AC")?,
+            current_utilization: table.field("Branch Percent Current Utilization")?.get_f32("%")?,
+            power: table.field("Branch Power")?.get_f32("W")?,
+            apparent_power: table.field("Branch Apparent Power")?.get_f32("VA")?,
+            power_factor: table.field("Branch Power Factor")?.get_f32("")?,
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Settings from a branch module
 pub struct BranchSettings {
     /// Branch module user label
@@ -875,17 +1512,18 @@ pub struct BranchSettings {
 impl BranchSettings {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(BranchSettings {
-            label: table.get("Branch User Assigned Label").ok_or(MissingDataError)?.value.clone(),
-            asset_tag_1: table.get("Branch Asset Tag 01").ok_or(MissingDataError)?.value.clone().replace("&nbsp;", ""),
-            asset_tag_2: table.get("Branch Asset Tag 02").ok_or(MissingDataError)?.value.clone().replace("&nbsp;", ""),
-            over_current_alarm_threshold: table.get("Over Current Alarm Threshold").ok_or(MissingDataError)?.get_u32("%")?,
-            over_current_warning_threshold: table.get("Over Current Warning Threshold").ok_or(MissingDataError)?.get_u32("%")?,
-            low_current_alarm_threshold: table.get("Low Current Alarm Threshold").ok_or(MissingDataError)?.get_u32("%")?,
+            label: table.field("Branch User Assigned Label")?.value.clone(),
+            asset_tag_1: table.field("Branch Asset Tag 01")?.value.clone(),
+            asset_tag_2: table.field("Branch Asset Tag 02")?.value.clone(),
+            over_current_alarm_threshold: table.field("Over Current Alarm Threshold")?.get_u32("%")?,
+            over_current_warning_threshold: table.field("Over Current Warning Threshold")?.get_u32("%")?,
+            low_current_alarm_threshold: table.field("Low Current Alarm Threshold")?.get_u32("%")?,
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Hardware information from a branch module
 pub struct BranchHardware {
     /// BRM model description
@@ -911,20 +1549,21 @@ pub struct BranchHardware {
 impl BranchHardware {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(BranchHardware {
-            brm_model: BRMModel::from_str(&table.get("BRM Model").ok_or(MissingDataError)?.value)?,
-            receptacle_type: ReceptacleType::from_str(&table.get("Branch Receptacle Type").ok_or(MissingDataError)?.value)?,
-            capabilities: Capability::from_str(&table.get("Branch Capabilities").ok_or(MissingDataError)?.value)?,
-            line_source: LineSource::from_str(&table.get("Branch Line Source").ok_or(MissingDataError)?.value)?,
-            rated_line_voltage: table.get("Branch Rated Line Voltage").ok_or(MissingDataError)?.get_u32("VAC")?,
-            rated_line_current: table.get("Branch Rated Line Current").ok_or(MissingDataError)?.get_u32("A AC")?,
-            rated_line_frequency: table.get("Branch Rated Line Frequency").ok_or(MissingDataError)?.get_u32("Hz")?,
-            fw_version: FWVersion::from_str(&table.get("Firmware Version").ok_or(MissingDataError)?.value)?,
-            serial_number: table.get("Branch Serial Number").ok_or(MissingDataError)?.value.clone(),
+            brm_model: table.field("BRM Model")?.parse::<BRMModel>()?,
+            receptacle_type: table.field("Branch Receptacle Type")?.parse::<ReceptacleType>()?,
+            capabilities: table.field("Branch Capabilities")?.parse::<Capability>()?,
+            line_source: table.field("Branch Line Source")?.parse::<LineSource>()?,
+            rated_line_voltage: table.field("Branch Rated Line Voltage")?.get_u32("VAC")?,
+            rated_line_current: table.field("Branch Rated Line Current")?.get_u32("A AC")?,
+            rated_line_frequency: table.field("Branch Rated Line Frequency")?.get_u32("Hz")?,
+            fw_version: table.field("Firmware Version")?.parse::<FWVersion>()?,
+            serial_number: table.field("Branch Serial Number")?.value.clone(),
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Event information from a branch module
 pub struct BranchEvents {
     pub low_voltage: EventLevel,
@@ -937,16 +1576,54 @@ pub struct BranchEvents {
 impl BranchEvents {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(BranchEvents {
-            low_voltage: EventLevel::from_str(&table.get("Branch Low Voltage (LN)").ok_or(MissingDataError)?.value)?,
-            over_current: EventLevel::from_str(&table.get("Branch Over Current").ok_or(MissingDataError)?.value)?,
-            low_current: EventLevel::from_str(&table.get("Branch Low Current").ok_or(MissingDataError)?.value)?,
-            failure: EventLevel::from_str(&table.get("Branch Failure").ok_or(MissingDataError)?.value)?,
-            breaker_open: EventLevel::from_str(&table.get("Branch Breaker Open").ok_or(MissingDataError)?.value)?,
+            low_voltage: table.field("Branch Low Voltage (LN)")?.parse::<EventLevel>()?,
+            over_current: table.field("Branch Over Current")?.parse::<EventLevel>()?,
+            low_current: table.field("Branch Low Current")?.parse::<EventLevel>()?,
+            failure: table.field("Branch Failure")?.parse::<EventLevel>()?,
+            breaker_open: table.field("Branch Breaker Open")?.parse::<EventLevel>()?,
         })
     }
+
+    /// Most severe level of all branch events
+    pub fn level(&self) -> EventLevel {
+        [self.low_voltage, self.over_current, self.low_current, self.failure, self.breaker_open].iter().copied().max().unwrap_or(EventLevel::OK)
+    }
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// State of the circuit breaker of a branch module
+pub enum BreakerState {
+    Closed,
+    Open,
+    /// Breaker event not reported or not parseable
+    Unknown,
+}
+
+impl std::fmt::Display for BreakerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BreakerState::Closed => write!(f, "{}", l10n::tr("Closed")),
+            BreakerState::Open => write!(f, "{}", l10n::tr("Open")),
+            BreakerState::Unknown => write!(f, "{}", l10n::tr("Unknown")),
+        }
+    }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+impl BranchEvents {
+    /// Breaker state derived from the breaker open event: a warning or
+    /// alarm means open, an inactive event closed
+    pub fn breaker_state(&self) -> BreakerState {
+        match self.breaker_open {
+            EventLevel::OK => BreakerState::Closed,
+            EventLevel::WARNING | EventLevel::ALARM => BreakerState::Open,
+            EventLevel::INFO => BreakerState::Unknown,
+        }
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Information about a branch module
 pub struct BranchInfo {
     pub status: BranchStatus,
@@ -958,54 +1635,59 @@ pub struct BranchInfo {
 impl BranchInfo {
     fn from_tables(tables: InfoTables) -> Result<Self,MPXError> {
         Ok(BranchInfo {
-            status: BranchStatus::from_table(tables.status)?,
-            events: BranchEvents::from_table(tables.events)?,
-            settings: BranchSettings::from_table(tables.settings)?,
-            hardware: BranchHardware::from_table(tables.hardware)?,
+            status: BranchStatus::from_table(tables.status).map_err(|e| e.in_table("status"))?,
+            events: BranchEvents::from_table(tables.events).map_err(|e| e.in_table("events"))?,
+            settings: BranchSettings::from_table(tables.settings).map_err(|e| e.in_table("settings"))?,
+            hardware: BranchHardware::from_table(tables.hardware).map_err(|e| e.in_table("hardware"))?,
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Status from a receptacle
+///
+/// The measurements are `None` for receptacles of elementary and branch
+/// monitored modules, see [`capabilities`].
 pub struct ReceptacleStatus {
     /// accumulated energy in kWh
-    pub accumulated_energy: f32,
+    pub accumulated_energy: Option<f32>,
     /// voltage in V AC
-    pub voltage: f32,
+    pub voltage: Option<f32>,
     /// current in A AC
-    pub current: f32,
+    pub current: Option<f32>,
     /// current available before alarm in A AC
-    pub current_available_to_alarm: f32,
+    pub current_available_to_alarm: Option<f32>,
     /// line utilization in %
-    pub current_utilization: f32,
+    pub current_utilization: Option<f32>,
     /// input power in W
-    pub power: f32,
+    pub power: Option<f32>,
     /// apparent power in VA
-    pub apparent_power: f32,
+    pub apparent_power: Option<f32>,
     /// power factor (0-1)
-    pub power_factor: f32,
+    pub power_factor: Option<f32>,
     /// current crest factor (0-1)
-    pub current_crest_factor: f32,
+    pub current_crest_factor: Option<f32>,
 }
 
 impl ReceptacleStatus {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(ReceptacleStatus {
-            accumulated_energy: table.get("Receptacle Accumulated Energy").ok_or(MissingDataError)?.get_f32("kWH")?,
-            voltage: table.get("Receptacle Voltage").ok_or(MissingDataError)?.get_f32("VAC")?,
-            current: table.get("Receptacle Current").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_available_to_alarm: table.get("Receptacle Available Current Until Alarm").ok_or(MissingDataError)?.get_f32("A AC")?,
-            current_utilization: table.get("Receptacle Percent Current Utilization").ok_or(MissingDataError)?.get_f32("%")?,
-            power: table.get("Receptacle Power").ok_or(MissingDataError)?.get_f32("W")?,
-            apparent_power: table.get("Receptacle Apparent Power").ok_or(MissingDataError)?.get_f32("VA")?,
-            power_factor: table.get("Receptacle Power Factor").ok_or(MissingDataError)?.get_f32("&nbsp;")?,
-            current_crest_factor: table.get("Receptacle Current Crest Factor").ok_or(MissingDataError)?.get_f32("&nbsp;")?,
+            accumulated_energy: table.optional("Receptacle Accumulated Energy").map(|v| v.get_f32("kWH")).transpose()?,
+            voltage: table.optional("Receptacle Voltage").map(|v| v.get_f32("VAC")).transpose()?,
+            current: table.optional("Receptacle Current").map(|v| v.get_f32("A AC")).transpose()?,
+            current_available_to_alarm: table.optional("Receptacle Available Current Until Alarm").map(|v| v.get_f32("A AC")).transpose()?,
+            current_utilization: table.optional("Receptacle Percent Current Utilization").map(|v| v.get_f32("%")).transpose()?,
+            power: table.optional("Receptacle Power").map(|v| v.get_f32("W")).transpose()?,
+            apparent_power: table.optional("Receptacle Apparent Power").map(|v| v.get_f32("VA")).transpose()?,
+            power_factor: table.optional("Receptacle Power Factor").map(|v| v.get_f32("")).transpose()?,
+            current_crest_factor: table.optional("Receptacle Current Crest Factor").map(|v| v.get_f32("")).transpose()?,
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Settings from a receptacle
 pub struct ReceptacleSettings {
     /// Receptacle user label
@@ -1026,28 +1708,38 @@ pub struct ReceptacleSettings {
     pub power_control: bool,
     /// lock state (true=locked, false=unlocked)
     pub control_lock_state: bool,
-    /// power on delay in seconds
-    pub power_on_delay: u32,
+    /// power on delay (the device uses full seconds)
+    #[cfg_attr(feature = "serde", serde(with = "duration_secs"))]
+    pub power_on_delay: Duration,
+}
+
+/// Serialize a `Duration` as whole seconds, as shown by the device
+#[cfg(feature = "serde")]
+mod duration_secs {
+    pub fn serialize<S: serde::Serializer>(duration: &std::time::Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_secs())
+    }
 }
 
 impl ReceptacleSettings {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(ReceptacleSettings {
-            label: table.get("Receptacle User Assigned Label").ok_or(MissingDataError)?.value.clone(),
-            asset_tag_1: table.get("Receptacle Asset Tag 01").ok_or(MissingDataError)?.value.clone().replace("&nbsp;", ""),
-            asset_tag_2: table.get("Receptacle Asset Tag 02").ok_or(MissingDataError)?.value.clone().replace("&nbsp;", ""),
-            over_current_alarm_threshold: table.get("Over Current Alarm Threshold").ok_or(MissingDataError)?.get_u32("%")?,
-            over_current_warning_threshold: table.get("Over Current Warning Threshold").ok_or(MissingDataError)?.get_u32("%")?,
-            low_current_alarm_threshold: table.get("Low Current Alarm Threshold").ok_or(MissingDataError)?.get_u32("%")?,
-            power_state: table.get("Receptacle Power State").ok_or(MissingDataError)?.value == "On",
-            power_control: table.get("Receptacle Power Control").ok_or(MissingDataError)?.value == "On",
-            control_lock_state: table.get("Receptacle Control Lock State").ok_or(MissingDataError)?.value == "Locked",
-            power_on_delay: table.get("Receptacle Power On Delay").ok_or(MissingDataError)?.get_u32("sec")?,
+            label: table.field("Receptacle User Assigned Label")?.value.clone(),
+            asset_tag_1: table.field("Receptacle Asset Tag 01")?.value.clone(),
+            asset_tag_2: table.field("Receptacle Asset Tag 02")?.value.clone(),
+            over_current_alarm_threshold: table.field("Over Current Alarm Threshold")?.get_u32("%")?,
+            over_current_warning_threshold: table.field("Over Current Warning Threshold")?.get_u32("%")?,
+            low_current_alarm_threshold: table.field("Low Current Alarm Threshold")?.get_u32("%")?,
+            power_state: table.field("Receptacle Power State")?.value == "On",
+            power_control: table.field("Receptacle Power Control")?.value == "On",
+            control_lock_state: table.field("Receptacle Control Lock State")?.value == "Locked",
+            power_on_delay: table.field("Receptacle Power On Delay")?.get_duration("sec")?,
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Hardware information from a receptacle
 pub struct ReceptacleHardware {
     /// Receptacle type (e.g. C13 or Schuko)
@@ -1061,14 +1753,15 @@ pub struct ReceptacleHardware {
 impl ReceptacleHardware {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(ReceptacleHardware {
-            receptacle_type: ReceptacleType::from_str(&table.get("Receptacle Type").ok_or(MissingDataError)?.value)?,
-            line_source: LineSource::from_str(&table.get("Receptacle Line Source").ok_or(MissingDataError)?.value)?,
-            capabilities: Capability::from_str(&table.get("Receptacle Capabilities").ok_or(MissingDataError)?.value)?,
+            receptacle_type: table.field("Receptacle Type")?.parse::<ReceptacleType>()?,
+            line_source: table.field("Receptacle Line Source")?.parse::<LineSource>()?,
+            capabilities: table.field("Receptacle Capabilities")?.parse::<Capability>()?,
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Event information from a receptacle
 pub struct ReceptacleEvents {
     pub over_current: EventLevel,
@@ -1078,13 +1771,14 @@ pub struct ReceptacleEvents {
 impl ReceptacleEvents {
     fn from_table(table: RawDataTable) -> Result<Self,MPXError> {
         Ok(ReceptacleEvents {
-            over_current: EventLevel::from_str(&table.get("Receptacle Over Current").ok_or(MissingDataError)?.value)?,
-            low_current: EventLevel::from_str(&table.get("Receptacle Low Current").ok_or(MissingDataError)?.value)?,
+            over_current: table.field("Receptacle Over Current")?.parse::<EventLevel>()?,
+            low_current: table.field("Receptacle Low Current")?.parse::<EventLevel>()?,
         })
     }
 }
 
-#[derive(Clone,Debug,PartialEq,Serialize)]
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 /// Information about a Receptacle
 pub struct ReceptacleInfo {
     pub status: ReceptacleStatus,
@@ -1093,148 +1787,669 @@ pub struct ReceptacleInfo {
     pub hardware: ReceptacleHardware,
 }
 
+/// Receptacle rows which are only missing on modules without receptacle measurement
+const MEASUREMENT_ROWS: &[(&str, &str)] = &[
+    ("status", "Receptacle Accumulated Energy"), ("status", "Receptacle Voltage"),
+    ("status", "Receptacle Current"), ("status", "Receptacle Available Current Until Alarm"),
+    ("status", "Receptacle Percent Current Utilization"), ("status", "Receptacle Power"),
+    ("status", "Receptacle Apparent Power"), ("status", "Receptacle Power Factor"),
+    ("status", "Receptacle Current Crest Factor"),
+];
+
+impl InfoTables {
+    /// Fail if a receptacle reporting measurement capability misses measurements
+    fn require_measurements(&self) -> Result<(), MPXError> {
+        match self.hardware.optional("Receptacle Capabilities").map(|v| v.parse::<Capability>()) {
+            Some(Ok(capability)) if capability.can_measure() => self.require(MEASUREMENT_ROWS),
+            _ => Ok(()),
+        }
+    }
+}
+
 impl ReceptacleInfo {
     fn from_tables(tables: InfoTables) -> Result<Self,MPXError> {
+        tables.require_measurements()?;
         Ok(ReceptacleInfo {
-            status: ReceptacleStatus::from_table(tables.status)?,
-            events: ReceptacleEvents::from_table(tables.events)?,
-            settings: ReceptacleSettings::from_table(tables.settings)?,
-            hardware: ReceptacleHardware::from_table(tables.hardware)?,
+            status: ReceptacleStatus::from_table(tables.status).map_err(|e| e.in_table("status"))?,
+            events: ReceptacleEvents::from_table(tables.events).map_err(|e| e.in_table("events"))?,
+            settings: ReceptacleSettings::from_table(tables.settings).map_err(|e| e.in_table("settings"))?,
+            hardware: ReceptacleHardware::from_table(tables.hardware).map_err(|e| e.in_table("hardware"))?,
         })
     }
 }
 
+#[cfg(feature = "client")]
 /// Representation of a Liebert MPX PDU
-pub struct MPX {
+///
+/// The handle is cheap to clone: all clones share the HTTP transport,
+/// the session, caches and limits, so a single device can be used from
+/// several tokio tasks (`MPX` is `Send + Sync`).
+pub struct MPX<H = transport::ReqwestTransport> {
+    inner: Arc<Inner<H>>,
+}
+
+#[cfg(feature = "client")]
+/// Shared state of all clones of a [`MPX`]
+struct Inner<H> {
     host: String,
-    username: String,
-    password: String,
+    /// source of username and password, see [`credentials`]
+    credentials: credentials::Provider,
+    /// PEM serial numbers seen on first contact, keyed by PDU number
+    identities: Mutex<HashMap<u8, String>>,
+    /// Recently executed idempotency keys, see [`MPX::once`]
+    idempotency: Mutex<idempotency::IdempotencyKeys>,
+    /// HTTP transport shared by all requests, so connections are kept alive
+    transport: H,
+    /// scheme, host and port, e.g. `http://192.168.23.42`
+    base_url: String,
+    /// fallback addresses, see [`failover`]
+    failover: Mutex<failover::Failover>,
+    /// form based login, see [`session`]
+    session: Option<session::SessionLogin>,
+    /// cookies of the current login session
+    session_cookie: Mutex<Option<String>>,
+    /// hidden form field echoed back on POSTs, see [`csrf`]
+    csrf_field: Option<String>,
+    /// negotiated authentication scheme, see [`auth`]
+    auth: Mutex<auth::AuthState>,
+    /// overall deadline of a single operation
+    deadline: Option<Duration>,
+    /// retry policy for failed requests, see [`retry`]
+    retry: Option<retry::RetryPolicy>,
+    /// circuit breaker configuration, see [`breaker`]
+    breaker: Option<breaker::CircuitBreaker>,
+    breaker_state: Mutex<breaker::BreakerState>,
+    /// request rate limiter, see [`ratelimit`]
+    limiter: Option<ratelimit::Limiter>,
+    /// serializes commands and settings writes, the card processes
+    /// concurrent form posts non-atomically
+    writes: tokio::sync::Mutex<()>,
+    /// upper bound for response bodies, also checked for custom transports
+    max_response_size: Option<usize>,
+    /// BRM models seen in branch information, keyed by PDU and branch
+    /// number, see [`capabilities`]
+    modules: Mutex<HashMap<(u8, u8), BRMModel>>,
+    /// receptacle capabilities seen in receptacle information, keyed by
+    /// PDU, branch and receptacle number
+    receptacle_capabilities: Mutex<HashMap<(u8, u8, u8), Capability>>,
+    /// receptacles of the last receptacle list, see [`address`]
+    topology: Mutex<Option<std::collections::HashSet<(u8, u8, u8)>>>,
+    /// reachability bookkeeping, see [`health`]
+    health: Mutex<health::HealthState>,
+    /// receives unparseable pages, see [`diagnostics`]
+    parse_failure_sink: Option<diagnostics::Sink>,
+    /// directory for fetched pages, see [`record`]
+    record_dir: Option<std::path::PathBuf>,
+    /// PEM firmware selecting the label and form field profile, see [`compat`]
+    firmware: Mutex<Option<FWVersion>>,
+    /// firmware configured by the user instead of detected
+    firmware_pinned: bool,
+}
+
+/// URL authority for `host`, which may be a name, an IPv4/IPv6 address
+/// (optionally bracketed) or contain a port; an explicit `port` wins
+#[cfg(feature = "client")]
+fn authority(host: &str, port: Option<u16>) -> String {
+    let (host, host_port) = if let Some(rest) = host.strip_prefix('[') {
+        match rest.split_once(']') {
+            Some((addr, tail)) => (format!("[{}]", addr), tail.strip_prefix(':')),
+            None => (host.to_string(), None),
+        }
+    } else if host.matches(':').count() > 1 {
+        // bare IPv6 literal, zone ids need to be percent encoded
+        (format!("[{}]", host.replace('%', "%25")), None)
+    } else {
+        match host.split_once(':') {
+            Some((name, host_port)) => (name.to_string(), Some(host_port)),
+            None => (host.to_string(), None),
+        }
+    };
+
+    match (port.map(|p| p.to_string()), host_port) {
+        (Some(port), _) => format!("{}:{}", host, port),
+        (None, Some(port)) => format!("{}:{}", host, port),
+        (None, None) => host,
+    }
 }
 
+#[cfg(feature = "client")]
+#[derive(Debug)]
+/// Connection options for [`MPX`], see [`MPX::builder`]
+pub struct MPXBuilder {
+    host: String,
+    fallback_hosts: Vec<String>,
+    failback_after: Duration,
+    credentials: credentials::Provider,
+    scheme: String,
+    port: Option<u16>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    deadline: Option<Duration>,
+    retry: Option<retry::RetryPolicy>,
+    breaker: Option<breaker::CircuitBreaker>,
+    rate_limit: Option<ratelimit::RateLimit>,
+    max_response_size: Option<usize>,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+    user_agent: Option<String>,
+    headers: reqwest::header::HeaderMap,
+    root_certificates: Vec<reqwest::Certificate>,
+    client: Option<reqwest::ClientBuilder>,
+    http_client: Option<reqwest::Client>,
+    session: Option<session::SessionLogin>,
+    csrf_field: Option<String>,
+    parse_failure_sink: Option<diagnostics::Sink>,
+    record_dir: Option<std::path::PathBuf>,
+    firmware: Option<FWVersion>,
+}
+
+#[cfg(feature = "client")]
+impl MPXBuilder {
+    /// Username and password for commands and settings writes
+    pub fn credentials(mut self, username: &str, password: &str) -> Self {
+        self.credentials = credentials::Provider(Box::new(credentials::Credentials::new(username, password)));
+        self
+    }
+
+    /// Fetch username and password from `provider` for every request
+    pub fn credential_provider<P: credentials::CredentialProvider + 'static>(mut self, provider: P) -> Self {
+        self.credentials = credentials::Provider(Box::new(provider));
+        self
+    }
+
+    /// URL scheme, `http` (default) or `https`
+    pub fn scheme(mut self, scheme: &str) -> Self {
+        self.scheme = scheme.to_string();
+        self
+    }
+
+    /// Additional address of the device, used if the primary host is
+    /// unreachable; may be called several times
+    pub fn fallback_host(mut self, host: &str) -> Self {
+        self.fallback_hosts.push(host.to_string());
+        self
+    }
+
+    /// Time after a failover until the primary host is tried again
+    /// (default: 60 seconds)
+    pub fn failback_after(mut self, interval: Duration) -> Self {
+        self.failback_after = interval;
+        self
+    }
+
+    /// TCP port, defaults to the standard port of the scheme
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Timeout for establishing connections
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for each read from an established connection
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for complete requests, including reading the response
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Overall deadline for an operation (a fetch or command including
+    /// login, token fetches and retries); exceeding it results in
+    /// `MPXError::Timeout`
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Retry failed requests with exponential backoff
+    pub fn retry(mut self, policy: retry::RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Fail fast after repeated transport failures
+    pub fn circuit_breaker(mut self, breaker: breaker::CircuitBreaker) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Limit request rate and concurrency
+    pub fn rate_limit(mut self, limit: ratelimit::RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Fail with `MPXError::ResponseTooLarge` instead of reading more
+    /// than `bytes` of a response body
+    pub fn max_response_size(mut self, bytes: usize) -> Self {
+        self.max_response_size = Some(bytes);
+        self
+    }
+
+    /// Accept gzip compressed responses (default: enabled)
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.gzip = enable;
+        self
+    }
+
+    /// User-Agent header sent with all requests
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Extra header sent with all requests, e.g. for auditing
+    pub fn header(mut self, name: reqwest::header::HeaderName, value: reqwest::header::HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Trust an additional root certificate, e.g. of an internal CA
+    pub fn add_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Trust an additional PEM encoded root certificate
+    pub fn root_certificate_pem(self, pem: &[u8]) -> Result<Self, MPXError> {
+        Ok(self.add_root_certificate(reqwest::Certificate::from_pem(pem)?))
+    }
+
+    /// Start from a pre-configured reqwest client builder (TLS settings,
+    /// proxies, ...); the other options of this builder are applied on top
+    pub fn client_builder(mut self, client: reqwest::ClientBuilder) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Use an existing reqwest client as is; timeouts, User-Agent, extra
+    /// headers, root certificates and client builder set on this builder
+    /// are ignored
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Log in via a form and use session cookies, for newer firmware
+    pub fn session_login(mut self, login: session::SessionLogin) -> Self {
+        self.session = Some(login);
+        self
+    }
+
+    /// Echo the hidden form field `field` back on all POSTs
+    pub fn csrf_token_field(mut self, field: &str) -> Self {
+        self.csrf_field = Some(field.to_string());
+        self
+    }
+
+    /// Hand the raw HTML of pages which could not be parsed to `sink`,
+    /// e.g. a [`diagnostics::DirectorySink`]
+    pub fn parse_failure_sink<S: diagnostics::ParseFailureSink + 'static>(mut self, sink: S) -> Self {
+        self.parse_failure_sink = Some(diagnostics::Sink(Box::new(sink)));
+        self
+    }
+
+    /// Store every fetched page in `dir`, see [`record`]
+    pub fn record_pages<P: Into<std::path::PathBuf>>(mut self, dir: P) -> Self {
+        self.record_dir = Some(dir.into());
+        self
+    }
+
+    /// Use the label and form field profile of `version` instead of
+    /// detecting the firmware, see [`compat`]
+    pub fn firmware(mut self, version: FWVersion) -> Self {
+        self.firmware = Some(version);
+        self
+    }
+
+    pub fn build(mut self) -> Result<MPX, MPXError> {
+        let client = match self.http_client.take() {
+            Some(client) => client,
+            None => {
+                let mut client = self.client.take().unwrap_or_default();
+                for certificate in std::mem::take(&mut self.root_certificates) {
+                    client = client.add_root_certificate(certificate);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    client = client.connect_timeout(timeout);
+                }
+                if let Some(timeout) = self.read_timeout {
+                    client = client.read_timeout(timeout);
+                }
+                if let Some(timeout) = self.timeout {
+                    client = client.timeout(timeout);
+                }
+                if let Some(user_agent) = self.user_agent.take() {
+                    client = client.user_agent(user_agent);
+                }
+                if !self.headers.is_empty() {
+                    client = client.default_headers(std::mem::take(&mut self.headers));
+                }
+                #[cfg(feature = "gzip")]
+                {
+                    client = client.gzip(self.gzip);
+                }
+                client.build()?
+            },
+        };
+        let mut transport = transport::ReqwestTransport::new(client);
+        if let Some(limit) = self.max_response_size {
+            transport = transport.max_response_size(limit);
+        }
+        Ok(self.build_with_transport(transport))
+    }
+
+    /// Send all requests through a custom [`transport::HttpTransport`];
+    /// the reqwest specific options of this builder are ignored
+    pub fn build_with_transport<H: transport::HttpTransport>(self, transport: H) -> MPX<H> {
+        let base_url = format!("{}://{}", self.scheme, authority(&self.host, self.port));
+        let bases = std::iter::once(base_url.clone())
+            .chain(self.fallback_hosts.iter().map(|host| format!("{}://{}", self.scheme, authority(host, self.port))))
+            .collect();
+
+        MPX { inner: Arc::new(Inner {
+            host: self.host,
+            credentials: self.credentials,
+            identities: Mutex::new(HashMap::new()),
+            idempotency: Mutex::new(Default::default()),
+            transport,
+            base_url,
+            failover: Mutex::new(failover::Failover::new(bases, self.failback_after)),
+            session: self.session,
+            session_cookie: Mutex::new(None),
+            csrf_field: self.csrf_field,
+            auth: Mutex::new(Default::default()),
+            deadline: self.deadline,
+            retry: self.retry,
+            breaker: self.breaker,
+            breaker_state: Mutex::new(Default::default()),
+            limiter: self.rate_limit.as_ref().map(ratelimit::Limiter::new),
+            writes: tokio::sync::Mutex::new(()),
+            max_response_size: self.max_response_size,
+            modules: Mutex::new(HashMap::new()),
+            receptacle_capabilities: Mutex::new(HashMap::new()),
+            topology: Mutex::new(None),
+            health: Mutex::new(Default::default()),
+            parse_failure_sink: self.parse_failure_sink,
+            record_dir: self.record_dir,
+            firmware: Mutex::new(self.firmware),
+            firmware_pinned: self.firmware.is_some(),
+        }) }
+    }
+}
+
+#[cfg(feature = "client")]
 impl MPX {
     pub fn new(host: &str, username: &str, password: &str) -> Self {
-        MPX{
+        MPX::builder(host).credentials(username, password).build().expect("default HTTP client")
+    }
+
+    /// Use an existing, shared reqwest client (proxies, TLS settings,
+    /// DNS overrides, ...) instead of building a new one
+    pub fn with_client(client: reqwest::Client, host: &str, username: &str, password: &str) -> Self {
+        MPX::builder(host).credentials(username, password).http_client(client).build().expect("prebuilt HTTP client")
+    }
+
+    /// Builder for connection options (scheme, port, timeouts, ...)
+    ///
+    /// ```no_run
+    /// extern crate liebert_mpx as liebert;
+    /// use std::time::Duration;
+    ///
+    /// fn main() {
+    ///     let pdu = liebert::MPX::builder("pdu1.example.org")
+    ///         .credentials("Liebert", "Liebert")
+    ///         .scheme("https")
+    ///         .connect_timeout(Duration::from_secs(3))
+    ///         .timeout(Duration::from_secs(10))
+    ///         .build()
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn builder(host: &str) -> MPXBuilder {
+        MPXBuilder {
             host: host.to_string(),
-            username: username.to_string(),
-            password: password.to_string(),
+            fallback_hosts: Vec::new(),
+            failback_after: Duration::from_secs(60),
+            credentials: Default::default(),
+            scheme: "http".to_string(),
+            port: None,
+            connect_timeout: None,
+            read_timeout: None,
+            timeout: None,
+            deadline: None,
+            retry: None,
+            breaker: None,
+            rate_limit: None,
+            max_response_size: None,
+            #[cfg(feature = "gzip")]
+            gzip: true,
+            user_agent: None,
+            headers: Default::default(),
+            root_certificates: Vec::new(),
+            client: None,
+            http_client: None,
+            session: None,
+            csrf_field: None,
+            parse_failure_sink: None,
+            record_dir: None,
+            firmware: None,
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl<H> Clone for MPX<H> {
+    fn clone(&self) -> Self {
+        MPX { inner: self.inner.clone() }
+    }
+}
+
+#[cfg(feature = "client")]
+impl<H: std::fmt::Debug> std::fmt::Debug for MPX<H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("MPX")
+            .field("host", &self.inner.host)
+            .field("credentials", &self.inner.credentials)
+            .field("base_url", &self.inner.base_url)
+            .field("transport", &self.inner.transport)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "client")]
+impl<H> MPX<H> {
+    /// Host name or address of the device
+    pub fn host(&self) -> &str {
+        &self.inner.host
+    }
+
+    /// Full URL for a path on the device
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.inner.base_url, path)
+    }
+
+    /// PEM serial number pinned for the given PDU, if it has been polled before
+    pub fn pinned_serial(&self, pdu: u8) -> Option<String> {
+        self.inner.identities.lock().unwrap().get(&pdu).cloned()
+    }
+
+    /// Forget the pinned PEM serial number, e.g. after an intentional device swap
+    pub fn unpin_serial(&self, pdu: u8) {
+        self.inner.identities.lock().unwrap().remove(&pdu);
+    }
+
+    /// Record the PEM serial number on first contact and compare it afterwards
+    fn check_identity(&self, pdu: u8, serial: &str) -> Result<(), MPXError> {
+        let mut identities = self.inner.identities.lock().unwrap();
+        match identities.get(&pdu) {
+            Some(expected) if expected != serial => {
+                Err(MPXError::IdentityMismatch(IdentityMismatchError {
+                    pdu,
+                    expected: expected.clone(),
+                    found: serial.to_string(),
+                }))
+            },
+            Some(_) => Ok(()),
+            None => {
+                identities.insert(pdu, serial.to_string());
+                Ok(())
+            },
+        }
+    }
+
+    /// Emit a structured log record for a command or settings write
+    #[allow(unused_variables)]
+    fn log_operation(&self, operation: &str, address: &str, result: &Result<(), MPXError>) {
+        #[cfg(feature = "log")]
+        match result {
+            Ok(()) => log::info!(target: "liebert_mpx", device = self.inner.host.as_str(), address, operation, outcome = "ok";
+                "{} on {} {}", operation, self.inner.host, address),
+            Err(e) => log::warn!(target: "liebert_mpx", device = self.inner.host.as_str(), address, operation, outcome = "error", error:% = e;
+                "{} on {} {} failed: {}", operation, self.inner.host, address, e),
+        }
+    }
+
+    /// Emit a structured log record for a failed page fetch or parse
+    #[allow(unused_variables)]
+    fn log_fetch<T>(&self, page: &str, address: &str, result: &Result<T, MPXError>) {
+        #[cfg(feature = "log")]
+        match result {
+            Ok(_) => log::debug!(target: "liebert_mpx", device = self.inner.host.as_str(), address, page, outcome = "ok";
+                "fetched {} from {} {}", page, self.inner.host, address),
+            Err(e) => log::warn!(target: "liebert_mpx", device = self.inner.host.as_str(), address, page, outcome = "error", error:% = e;
+                "fetching {} from {} {} failed: {}", page, self.inner.host, address, e),
         }
     }
 }
 
 fn parse_receptacle_list_row(row: &html_parser::Element) -> Result<ReceptacleListEntry, MPXError> {
-    let rowid: Vec<&str> = row.id.as_ref().unwrap().split("-").collect();
+    let raw = row.id.as_ref().unwrap();
+    let rowid: Vec<&str> = raw.split("-").collect();
 
     if rowid.len() != 3 {
-        return Err(MPXError::InvalidDataError(InvalidDataError))
+        return Err(InvalidDataError::identifier(raw).into())
     }
 
-    let pdu = rowid.get(0).unwrap().parse::<u8>()?;
-    let branch = rowid.get(1).unwrap().parse::<u8>()?;
-    let receptacle = rowid.get(2).unwrap().parse::<u8>()?;
+    let bad_identifier = |_| InvalidDataError::identifier(raw);
+    let pdu = rowid.first().unwrap().parse::<u8>().map_err(bad_identifier)?;
+    let branch = rowid.get(1).unwrap().parse::<u8>().map_err(bad_identifier)?;
+    let receptacle = rowid.get(2).unwrap().parse::<u8>().map_err(bad_identifier)?;
 
-    let label = match row.children.get(0) {
+    let label = match row.children.first() {
         Some(html_parser::Node::Element(td)) => {
-            match td.children.get(0) {
+            match td.children.first() {
                 Some(html_parser::Node::Element(a)) => {
-                    match a.children.get(0) {
+                    match a.children.first() {
                         Some(html_parser::Node::Element(nobr)) => {
-                            match nobr.children.get(0) {
+                            match nobr.children.first() {
                                 Some(html_parser::Node::Text(text)) => {
-                                    text.clone()
+                                    decode_entities(text)
                                 },
                                 _ => {
-                                    return Err(MPXError::InvalidDataError(InvalidDataError))
+                                    return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
                                 },
                             }
                         },
                         _ => {
-                            return Err(MPXError::InvalidDataError(InvalidDataError))
+                            return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
                         },
                     }
                 },
                 _ => {
-                    return Err(MPXError::InvalidDataError(InvalidDataError))
+                    return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
                 },
             }
         }
         _ => {
-            return Err(MPXError::InvalidDataError(InvalidDataError))
+            return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
         },
     };
 
     let state = match row.children.get(2) {
         Some(html_parser::Node::Element(td)) => {
-            match td.children.get(0) {
+            match td.children.first() {
                 Some(html_parser::Node::Element(span)) => {
                     match span.attributes.get("title").unwrap_or(&None).as_ref().unwrap_or(&"".to_string()).as_str() {
                         "On" => true,
                         "Off" => false,
                         _ => {
-                            return Err(MPXError::InvalidDataError(InvalidDataError))
+                            return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
                         },
                     }
                 }
                 _ => {
-                    return Err(MPXError::InvalidDataError(InvalidDataError))
+                    return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
                 },
             }
         },
         _ => {
-            return Err(MPXError::InvalidDataError(InvalidDataError))
+            return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
         },
     };
 
     let locked = match row.children.get(3) {
         Some(html_parser::Node::Element(td)) => {
-            match td.children.get(0) {
+            match td.children.first() {
                 Some(html_parser::Node::Element(span)) => {
                     match span.attributes.get("title").unwrap_or(&None).as_ref().unwrap_or(&"".to_string()).as_str() {
                         "Unlocked" => false,
                         "Locked" => true,
                         _ => {
-                            return Err(MPXError::InvalidDataError(InvalidDataError))
+                            return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
                         },
                     }
                 }
                 _ => {
-                    return Err(MPXError::InvalidDataError(InvalidDataError))
+                    return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
                 },
             }
         },
         _ => {
-            return Err(MPXError::InvalidDataError(InvalidDataError))
+            return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
         },
     };
 
     let status = match row.children.get(4) {
         Some(html_parser::Node::Element(td)) => {
-            match td.children.get(0) {
+            match td.children.first() {
                 Some(html_parser::Node::Element(img)) => {
                     EventLevel::from_str(img.attributes.get("src").unwrap_or(&None).as_ref().unwrap_or(&"".to_string()).as_str())?
                 }
                 _ => {
-                    return Err(MPXError::InvalidDataError(InvalidDataError))
+                    return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
                 },
             }
         },
         _ => {
-            return Err(MPXError::InvalidDataError(InvalidDataError))
+            return Err(MPXError::InvalidDataError(InvalidDataError::structure("receptacle list row")))
         },
     };
 
     Ok(ReceptacleListEntry {
-        pdu: pdu,
-        branch: branch,
-        receptacle: receptacle,
+        pdu,
+        branch,
+        receptacle,
         enabled: state,
-        locked: locked,
-        status: status,
-        label: label,
+        locked,
+        status,
+        label,
     })
 }
 
-fn parse_receptacles(html: String) -> Result<ReceptacleList, MPXError> {
-    let dom = html_parser::Dom::parse(&html)?;
+/// Parse the receptacle list (`rpcReceptacleListData.htm`)
+pub fn parse_receptacles(html: String) -> Result<ReceptacleList, MPXError> {
+    let dom = parse_dom(&html)?;
     let mut result = Vec::new();
 
     for child in dom.children.iter() {
@@ -1249,14 +2464,14 @@ fn parse_receptacles(html: String) -> Result<ReceptacleList, MPXError> {
                                 }
                             }
                             _ => {
-                                return Err(MPXError::InvalidDataError(InvalidDataError));
+                                return Err(MPXError::InvalidDataError(InvalidDataError::structure("rcpTable")));
                             },
                         }
                     }
                 }
             },
             _ => {
-                return Err(MPXError::InvalidDataError(InvalidDataError));
+                return Err(MPXError::InvalidDataError(InvalidDataError::structure("rcpTable")));
             },
         }
     }
@@ -1264,16 +2479,77 @@ fn parse_receptacles(html: String) -> Result<ReceptacleList, MPXError> {
     Ok(result)
 }
 
-impl MPX {
-    pub async fn get_receptacles(self: &Self) -> Result<ReceptacleList, MPXError> {
-        let url = format!("http://{}/rpc/rpcReceptacleListData.htm", self.host);
-        let html = reqwest::get(url).await?.text().await?;
-        parse_receptacles(html)
+#[cfg(feature = "client")]
+impl<H: transport::HttpTransport> MPX<H> {
+    pub async fn get_receptacles(&self) -> Result<ReceptacleList, MPXError> {
+        let url = self.url("/rpc/rpcReceptacleListData.htm");
+        let result = async {
+            let list = self.parse_page(&url, self.get_page(url.clone()).await?, parse_receptacles)?;
+            self.remember_topology(&list);
+            Ok(list)
+        }.await;
+        self.log_fetch("receptacle list", "", &result);
+        result
+    }
+}
+
+#[cfg(feature = "client")]
+impl<H: transport::HttpTransport> MPX<H> {
+    /// Get the PDUs connected to the management card
+    ///
+    /// The PDUs are taken from the receptacle list, label, model and status
+    /// from the information page of each PDU input module.
+    pub async fn get_pdus(&self) -> Result<PDUList, MPXError> {
+        let mut pdus: Vec<u8> = self.get_receptacles().await?.iter().map(|r| r.pdu).collect();
+        pdus.sort_unstable();
+        pdus.dedup();
+
+        let mut list = Vec::new();
+        for pdu in pdus {
+            let info = self.get_info_pdu(pdu).await?;
+            list.push(PDUListEntry {
+                pdu,
+                label: info.settings.label,
+                status: info.events.level(),
+                model: info.hardware.pem_model,
+            });
+        }
+        Ok(list)
+    }
+
+    /// Get the installed branch modules of all PDUs
+    ///
+    /// The branch modules are taken from the receptacle list, label, model
+    /// and status from the information page of each branch module.
+    pub async fn get_branches(&self) -> Result<BranchList, MPXError> {
+        let mut addresses: Vec<(u8, u8)> = self.get_receptacles().await?.iter().map(|r| (r.pdu, r.branch)).collect();
+        addresses.sort_unstable();
+        addresses.dedup();
+
+        let mut list = Vec::new();
+        for (pdu, branch) in addresses {
+            let info = self.get_info_branch((pdu, branch)).await?;
+            list.push(BranchListEntry {
+                pdu,
+                branch,
+                label: info.settings.label,
+                status: info.events.level(),
+                model: info.hardware.brm_model,
+            });
+        }
+        Ok(list)
+    }
+
+    /// Get the installed branch modules of a single PDU
+    pub async fn get_branches_pdu(&self, pdu: impl Into<PduId>) -> Result<BranchList, MPXError> {
+        let PduId(pdu) = pdu.into();
+        self.check_address(pdu, None, None)?;
+        Ok(self.get_branches().await?.into_iter().filter(|b| b.pdu == pdu).collect())
     }
 }
 
 fn parse_event_row(row: &html_parser::Element) -> Result<Option<Event>, MPXError> {
-    let colnode0 = row.children.get(0).ok_or(InvalidDataError)?;
+    let colnode0 = row.children.first().ok_or_else(|| InvalidDataError::structure("event row"))?;
 
     let level = match colnode0 {
         html_parser::Node::Element(cell) => {
@@ -1281,64 +2557,131 @@ fn parse_event_row(row: &html_parser::Element) -> Result<Option<Event>, MPXError
                 return Ok(None);
             }
 
-            match get_child_text(colnode0) {
-                Some(text) => {
-                    if text == "No Alarms Present" {
-                        return Ok(None);
-                    }
+            if let Some(text) = get_child_text(colnode0) {
+                if text == "No Alarms Present" {
+                    return Ok(None);
                 }
-                _ => {},
             }
 
-            let imgnode = get_child_node(colnode0, "img").ok_or(InvalidDataError)?;
+            let imgnode = get_child_node(colnode0, "img").ok_or_else(|| InvalidDataError::structure("event row"))?;
 
             match imgnode {
                 html_parser::Node::Element(img) => {
-                    let src = img.attributes.get("src").ok_or(InvalidDataError)?;
-                    let src = src.as_ref().ok_or(InvalidDataError)?;
+                    let src = img.attributes.get("src").ok_or_else(|| InvalidDataError::structure("event row"))?;
+                    let src = src.as_ref().ok_or_else(|| InvalidDataError::structure("event row"))?;
 
                     EventLevel::from_str(src)
                 },
                 _ => {
-                    return Err(MPXError::InvalidDataError(InvalidDataError));
+                    return Err(MPXError::InvalidDataError(InvalidDataError::structure("event row")));
                 },
             }
         },
         _ => {
-            return Err(MPXError::InvalidDataError(InvalidDataError));
+            return Err(MPXError::InvalidDataError(InvalidDataError::structure("event row")));
         },
     }?;
 
-    let colnode1 = row.children.get(1).ok_or(InvalidDataError)?;
-    let colnode2 = row.children.get(2).ok_or(InvalidDataError)?;
+    let colnode1 = row.children.get(1).ok_or_else(|| InvalidDataError::structure("event row"))?;
+    let colnode2 = row.children.get(2).ok_or_else(|| InvalidDataError::structure("event row"))?;
 
-    let id = get_child_text(colnode1).ok_or(InvalidDataError)?;
-    let event = get_child_text(colnode2).ok_or(InvalidDataError)?;
+    let id = get_child_text(colnode1).ok_or_else(|| InvalidDataError::structure("event row"))?;
+    let event = get_child_text(colnode2).ok_or_else(|| InvalidDataError::structure("event row"))?;
 
     let defaultid = "0";
-    let id: Vec<&str> = id.split("-").collect();
-    let pdu = id.get(0).unwrap_or(&defaultid).parse::<u8>()?;
-    let branch = id.get(1).unwrap_or(&defaultid).parse::<u8>()?;
-    let receptacle = id.get(2).unwrap_or(&defaultid).parse::<u8>()?;
+    let bad_identifier = |_| InvalidDataError::identifier(&id);
+    let parts: Vec<&str> = id.split("-").collect();
+    let pdu = parts.first().unwrap_or(&defaultid).parse::<u8>().map_err(bad_identifier)?;
+    let branch = parts.get(1).unwrap_or(&defaultid).parse::<u8>().map_err(bad_identifier)?;
+    let receptacle = parts.get(2).unwrap_or(&defaultid).parse::<u8>().map_err(bad_identifier)?;
 
 
     Ok(Some(Event {
-        pdu: pdu,
-        branch: branch,
-        receptacle: receptacle,
-        level: level,
-        event: EventType::from_str(event)?,
+        pdu,
+        branch,
+        receptacle,
+        level,
+        event: EventType::from_str(&event)?,
 
     }))
 }
 
-fn get_child_text<'a>(node: &'a html_parser::Node) -> Option<&'a String> {
+/// Named character references used by the web interface, including
+/// the usual accented letters of translated firmware
+const ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'), ("lt", '<'), ("gt", '>'), ("quot", '"'), ("apos", '\''),
+    ("nbsp", '\u{a0}'), ("deg", '°'), ("micro", 'µ'), ("copy", '©'), ("reg", '®'), ("euro", '€'),
+    ("auml", 'ä'), ("ouml", 'ö'), ("uuml", 'ü'), ("Auml", 'Ä'), ("Ouml", 'Ö'), ("Uuml", 'Ü'), ("szlig", 'ß'),
+    ("agrave", 'à'), ("acirc", 'â'), ("ccedil", 'ç'), ("eacute", 'é'), ("egrave", 'è'), ("ecirc", 'ê'),
+    ("euml", 'ë'), ("icirc", 'î'), ("iuml", 'ï'), ("ocirc", 'ô'), ("ugrave", 'ù'), ("ucirc", 'û'),
+    ("Eacute", 'É'), ("aacute", 'á'), ("iacute", 'í'), ("oacute", 'ó'), ("uacute", 'ú'), ("ntilde", 'ñ'),
+];
+
+/// Decode HTML character references (`&amp;`, `&uuml;`, `&#228;`, `&#xE4;`);
+/// unknown references are kept as they are
+fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest[1..].split_once(';').and_then(|(name, _)| {
+            let c = match name.strip_prefix('#') {
+                Some(num) => match num.strip_prefix('x').or_else(|| num.strip_prefix('X')) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(std::char::from_u32),
+                    None => num.parse::<u32>().ok().and_then(std::char::from_u32),
+                },
+                None => ENTITIES.iter().find(|(n, _)| *n == name).map(|(_, c)| *c),
+            }?;
+            Some((c, name.len() + 2))
+        });
+
+        match decoded {
+            Some((c, len)) => {
+                result.push(c);
+                rest = &rest[len..];
+            },
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// Check that a page is complete
+///
+/// `html_parser` backtracks over the rest of the page for each element
+/// without end tag, which takes minutes for truncated information pages.
+/// Pages are therefore expected to end with `</html>`; end tags omitted
+/// inside the page are left to the parser.
+fn check_complete(html: &str) -> Result<(), InvalidDataError> {
+    let html = html.to_ascii_lowercase();
+    if html.contains("<html") && !html.trim_end().ends_with("</html>") {
+        return Err(InvalidDataError::structure("end of page, missing </html>"));
+    }
+    Ok(())
+}
+
+/// Parse a page, failing early on truncated pages
+pub(crate) fn parse_dom(html: &str) -> Result<html_parser::Dom, MPXError> {
+    check_complete(html)?;
+    Ok(html_parser::Dom::parse(html)?)
+}
+
+/// Decoded text of a node, without surrounding (non-breaking) spaces
+fn get_child_text(node: &html_parser::Node) -> Option<String> {
     match node {
         html_parser::Node::Element(e) => {
             for child in e.children.iter() {
                 match child {
                     html_parser::Node::Text(t) => {
-                        return Some(t);
+                        return Some(decode_entities(t).trim().to_string());
                     },
                     _ => continue,
                 }
@@ -1390,83 +2733,78 @@ fn get_child_node_by_id<'a>(node: &'a html_parser::Node, name: &str, id: &str) -
     None
 }
 
-fn parse_table<'a>(node: &'a html_parser::Node, alarm: bool) -> Result<RawDataTable, MPXError> {
+fn parse_table(node: &html_parser::Node, alarm: bool) -> Result<RawDataTable, MPXError> {
     let mut result = HashMap::new();
 
     match node {
         html_parser::Node::Element(table) => {
             for rownode in table.children.iter() {
                 match rownode {
-                    html_parser::Node::Element(row) => {
-                        if row.name == "tr" {
-                            let keynode = row.children.get(if alarm { 1 } else { 0 }).ok_or(InvalidDataError)?;
-                            match keynode {
-                                html_parser::Node::Element(e) => {
-                                    if e.name == "th" {
-                                        continue;
-                                    }
-                                },
-                                _ => {},
+                    html_parser::Node::Element(row)
+                        if row.name == "tr" => {
+                            let keynode = row.children.get(if alarm { 1 } else { 0 }).ok_or_else(|| InvalidDataError::structure("table row"))?;
+                            if let html_parser::Node::Element(e) = keynode {
+                                if e.name == "th" {
+                                    continue;
+                                }
                             }
-                            let key = get_child_text(keynode).ok_or(InvalidDataError)?;
+                            let key = labels::canonical(get_child_text(keynode).ok_or_else(|| InvalidDataError::structure("table row"))?);
 
-                            let valuenode = row.children.get(if alarm { 0 } else { 1 }).ok_or(InvalidDataError)?;
+                            let valuenode = row.children.get(if alarm { 0 } else { 1 }).ok_or_else(|| InvalidDataError::structure("table row"))?;
                             let value = if !alarm {
-                                get_child_text(valuenode).ok_or(InvalidDataError)?
+                                get_child_text(valuenode).ok_or_else(|| InvalidDataError::structure("table row"))?
                             } else {
-                                let valuenode = get_child_node(valuenode, "img").ok_or(InvalidDataError)?;
+                                let valuenode = get_child_node(valuenode, "img").ok_or_else(|| InvalidDataError::structure("table row"))?;
                                 match valuenode {
                                     html_parser::Node::Element(e) => {
-                                        let src = e.attributes.get("src").ok_or(InvalidDataError)?;
-                                        src.as_ref().ok_or(InvalidDataError)?
+                                        let src = e.attributes.get("src").ok_or_else(|| InvalidDataError::structure("table row"))?;
+                                        src.clone().ok_or_else(|| InvalidDataError::structure("table row"))?
                                     },
                                     _ => {
-                                        return Err(MPXError::InvalidDataError(InvalidDataError));
+                                        return Err(MPXError::InvalidDataError(InvalidDataError::structure("table row")));
                                     },
                                 }
                             };
 
-                            let empty = "".to_string();
-                            let unitnode = row.children.get(2).ok_or(InvalidDataError)?;
+                            let unitnode = row.children.get(2).ok_or_else(|| InvalidDataError::structure("table row"))?;
                             let unit = if !alarm {
-                                get_child_text(unitnode).ok_or(InvalidDataError)?
+                                get_child_text(unitnode).ok_or_else(|| InvalidDataError::structure("table row"))?
                             } else {
-                                &empty
+                                String::new()
                             };
 
                             result.insert(
                                 key.clone(),
-                                TableValue { value: value.clone(), unit: unit.clone() }
+                                TableValue { key, value, unit }
                             );
-                        }
-                    },
+                        },
                     _ => {},
                 }
             }
 
             Ok(result)
         },
-        _ => Err(MPXError::InvalidDataError(InvalidDataError))
+        _ => Err(MPXError::InvalidDataError(InvalidDataError::structure("table row")))
     }
 }
 
 fn get_info_tables(html: String) -> Result<InfoTables, MPXError> {
-    let dom = html_parser::Dom::parse(&html)?;
+    let dom = parse_dom(&html)?;
 
-    let html_node = dom.children.get(0).ok_or(InvalidDataError)?;
-    let body_node = get_child_node(html_node, "body").ok_or(InvalidDataError)?;
+    let html_node = dom.children.first().ok_or_else(|| InvalidDataError::structure("html"))?;
+    let body_node = get_child_node(html_node, "body").ok_or_else(|| InvalidDataError::structure("body"))?;
 
-    let status_node = get_child_node_by_id(body_node, "div", "RpcStatusArea").ok_or(InvalidDataError)?;
-    let status_node = get_child_node(status_node, "table").ok_or(InvalidDataError)?;
+    let status_node = get_child_node_by_id(body_node, "div", "RpcStatusArea").ok_or_else(|| InvalidDataError::structure("RpcStatusArea"))?;
+    let status_node = get_child_node(status_node, "table").ok_or_else(|| InvalidDataError::structure("RpcStatusArea table"))?;
 
-    let alarm_node = get_child_node_by_id(body_node, "div", "RpcAlarmArea").ok_or(InvalidDataError)?;
-    let alarm_node = get_child_node(alarm_node, "table").ok_or(InvalidDataError)?;
+    let alarm_node = get_child_node_by_id(body_node, "div", "RpcAlarmArea").ok_or_else(|| InvalidDataError::structure("RpcAlarmArea"))?;
+    let alarm_node = get_child_node(alarm_node, "table").ok_or_else(|| InvalidDataError::structure("RpcAlarmArea table"))?;
 
-    let settings_node = get_child_node_by_id(body_node, "div", "RpcSettingArea").ok_or(InvalidDataError)?;
-    let settings_node = get_child_node(settings_node, "table").ok_or(InvalidDataError)?;
+    let settings_node = get_child_node_by_id(body_node, "div", "RpcSettingArea").ok_or_else(|| InvalidDataError::structure("RpcSettingArea"))?;
+    let settings_node = get_child_node(settings_node, "table").ok_or_else(|| InvalidDataError::structure("RpcSettingArea table"))?;
 
-    let hardware_node = get_child_node_by_id(body_node, "div", "RpcInfoArea").ok_or(InvalidDataError)?;
-    let hardware_node = get_child_node(hardware_node, "table").ok_or(InvalidDataError)?;
+    let hardware_node = get_child_node_by_id(body_node, "div", "RpcInfoArea").ok_or_else(|| InvalidDataError::structure("RpcInfoArea"))?;
+    let hardware_node = get_child_node(hardware_node, "table").ok_or_else(|| InvalidDataError::structure("RpcInfoArea table"))?;
 
     Ok(InfoTables {
         status: parse_table(status_node, false)?,
@@ -1476,15 +2814,28 @@ fn get_info_tables(html: String) -> Result<InfoTables, MPXError> {
     })
 }
 
-fn parse_events(html: String)  -> Result<EventList, MPXError> {
-    let dom = html_parser::Dom::parse(&html)?;
+/// Rows of the table in the `<div>` with id `area` of a card page
+fn get_area_table(html: String, area: &str) -> Result<RawDataTable, MPXError> {
+    let dom = parse_dom(&html)?;
+
+    let html_node = dom.children.first().ok_or_else(|| InvalidDataError::structure("html"))?;
+    let body_node = get_child_node(html_node, "body").ok_or_else(|| InvalidDataError::structure("body"))?;
+    let area_node = get_child_node_by_id(body_node, "div", area).ok_or_else(|| InvalidDataError::structure(area))?;
+    let table_node = get_child_node(area_node, "table").ok_or_else(|| InvalidDataError::structure(&format!("{} table", area)))?;
+
+    parse_table(table_node, false)
+}
+
+/// Parse the active event list (`rpcActiveAlarms.htm`)
+pub fn parse_events(html: String) -> Result<EventList, MPXError> {
+    let dom = parse_dom(&html)?;
     let mut result = Vec::new();
 
-    let html_node = dom.children.get(0).ok_or(InvalidDataError)?;
-    let body_node = get_child_node(html_node, "body").ok_or(InvalidDataError)?;
+    let html_node = dom.children.first().ok_or_else(|| InvalidDataError::structure("html"))?;
+    let body_node = get_child_node(html_node, "body").ok_or_else(|| InvalidDataError::structure("body"))?;
 
-    let detail_node = get_child_node_by_id(body_node, "div", "DetailPanelArea").ok_or(InvalidDataError)?;
-    let table_node = get_child_node(detail_node, "table").ok_or(InvalidDataError)?;
+    let detail_node = get_child_node_by_id(body_node, "div", "DetailPanelArea").ok_or_else(|| InvalidDataError::structure("DetailPanelArea"))?;
+    let table_node = get_child_node(detail_node, "table").ok_or_else(|| InvalidDataError::structure("DetailPanelArea table"))?;
 
     match table_node {
         html_parser::Node::Element(table) => {
@@ -1492,148 +2843,273 @@ fn parse_events(html: String)  -> Result<EventList, MPXError> {
                 match rownode {
                     html_parser::Node::Element(row) => {
                         if row.name == "tr" {
-                            let re = parse_event_row(row)?;
-                            if re.is_some() {
-                                result.push(re.unwrap());
+                            if let Some(event) = parse_event_row(row)? {
+                                result.push(event);
                             }
                         }
                     }
                     _ => {
-                        return Err(MPXError::InvalidDataError(InvalidDataError));
+                        return Err(MPXError::InvalidDataError(InvalidDataError::structure("DetailPanelArea table")));
                     }
                 }
             }
         }
         _ => {
-            return Err(MPXError::InvalidDataError(InvalidDataError));
+            return Err(MPXError::InvalidDataError(InvalidDataError::structure("DetailPanelArea table")));
         },
     }
 
     Ok(result)
 }
 
-impl MPX {
-    pub async fn get_events(self: &Self) -> Result<EventList, MPXError> {
-        let url = format!("http://{}/rpc/rpcActiveAlarms.htm", self.host);
-        let html = reqwest::get(url).await?.text().await?;
-        parse_events(html)
+/// Parse the PDU input module information page (`rpcAps.htm`)
+pub fn parse_pdu_info(html: String) -> Result<PDUInfo, MPXError> {
+    PDUInfo::from_tables(get_info_tables(html)?)
+}
+
+/// Parse the branch module information page (`rpcRem.htm`)
+pub fn parse_branch_info(html: String) -> Result<BranchInfo, MPXError> {
+    BranchInfo::from_tables(get_info_tables(html)?)
+}
+
+/// Parse the receptacle information page (`rpcReceptacle.htm`)
+pub fn parse_receptacle_info(html: String) -> Result<ReceptacleInfo, MPXError> {
+    ReceptacleInfo::from_tables(get_info_tables(html)?)
+}
+
+#[cfg(feature = "client")]
+impl<H: transport::HttpTransport> MPX<H> {
+    pub async fn get_events(&self) -> Result<EventList, MPXError> {
+        let url = self.url("/rpc/rpcActiveAlarms.htm");
+        let result = async { self.parse_page(&url, self.get_page(url.clone()).await?, parse_events) }.await;
+        self.log_fetch("active alarms", "", &result);
+        result
+    }
+
+    pub async fn get_info_pdu(&self, pdu: impl Into<PduId>) -> Result<PDUInfo, MPXError> {
+        let PduId(pdu) = pdu.into();
+        self.check_address(pdu, None, None)?;
+        let url = self.url(&format!("/dp/std:{}.0.0_0.0.0/rpc/rpcAps.htm", pdu));
+        let result = async {
+            let html = self.get_page(url.clone()).await?;
+            self.detect_firmware(&html);
+            let info = self.parse_page(&url, html, parse_pdu_info)?;
+            self.check_identity(pdu, &info.hardware.serial_number)?;
+            Ok(info)
+        }.await;
+        self.log_fetch("PDU info", &format!("{}", pdu), &result);
+        result
+    }
+
+    /// Fetch the PEM information and verify it against the pinned serial number
+    pub async fn verify_identity(&self, pdu: impl Into<PduId>) -> Result<(), MPXError> {
+        self.get_info_pdu(pdu).await.map(|_| ())
+    }
+
+    pub async fn get_info_branch(&self, address: impl Into<BranchAddress>) -> Result<BranchInfo, MPXError> {
+        let BranchAddress { pdu: PduId(pdu), branch: BranchId(branch) } = address.into();
+        self.check_address(pdu, Some(branch), None)?;
+        let url = self.url(&format!("/dp/std:{}.{}.0_0.0.0/rpc/rpcRem.htm", pdu, branch));
+        let result = async {
+            let info = self.parse_page(&url, self.get_page(url.clone()).await?, parse_branch_info)?;
+            self.remember_module(pdu, branch, &info.hardware.brm_model);
+            Ok(info)
+        }.await;
+        self.log_fetch("branch info", &format!("{}-{}", pdu, branch), &result);
+        result
+    }
+
+    /// State of the circuit breaker of a branch module, `Unknown` if the
+    /// events section of the branch page cannot be parsed
+    pub async fn get_breaker_state(&self, address: impl Into<BranchAddress>) -> Result<BreakerState, MPXError> {
+        let info = self.get_info_branch_partial(address).await?;
+        Ok(info.events.map(|events| events.breaker_state()).unwrap_or(BreakerState::Unknown))
+    }
+
+    pub async fn get_info_receptacle(&self, address: impl Into<ReceptacleAddress>) -> Result<ReceptacleInfo, MPXError> {
+        let ReceptacleAddress { pdu: PduId(pdu), branch: BranchId(branch), receptacle: ReceptacleId(receptacle) } = address.into();
+        self.check_address(pdu, Some(branch), Some(receptacle))?;
+        let url = self.url(&format!("/dp/std:{}.{}.{}_0.0.0/rpc/rpcReceptacle.htm", pdu, branch, receptacle));
+        let result = async {
+            let info = self.parse_page(&url, self.get_page(url.clone()).await?, parse_receptacle_info)?;
+            self.remember_capability(pdu, branch, receptacle, &info.hardware.capabilities);
+            Ok(info)
+        }.await;
+        self.log_fetch("receptacle info", &format!("{}-{}-{}", pdu, branch, receptacle), &result);
+        result
+    }
+
+    /// Fail with an `AuthenticationError` if the PDU rejected the credentials
+    fn check_auth(response: &transport::Response) -> Result<(), MPXError> {
+        match response.status {
+            401 | 403 => Err(AuthenticationError { status: response.status }.into()),
+            _ => Ok(()),
+        }
     }
 
-    pub async fn get_info_pdu(self: &Self, pdu: u8) -> Result<PDUInfo, MPXError> {
-        let url = format!("http://{}/dp/std:{}.0.0_0.0.0/rpc/rpcAps.htm", self.host, pdu);
-        let html = reqwest::get(url).await?.text().await?;
-        PDUInfo::from_tables(get_info_tables(html)?)
+    /// Run an operation within the configured deadline
+    async fn with_deadline<T, F>(&self, operation: F) -> Result<T, MPXError>
+    where
+        F: std::future::Future<Output = Result<T, MPXError>>,
+    {
+        match self.inner.deadline {
+            Some(deadline) => tokio::time::timeout(deadline, operation).await
+                .map_err(|_| TimeoutError { deadline: Some(deadline) })?,
+            None => operation.await,
+        }
     }
 
-    pub async fn get_info_branch(self: &Self, pdu: u8, branch: u8) -> Result<BranchInfo, MPXError> {
-        let url = format!("http://{}/dp/std:{}.{}.0_0.0.0/rpc/rpcRem.htm", self.host, pdu, branch);
-        let html = reqwest::get(url).await?.text().await?;
-        BranchInfo::from_tables(get_info_tables(html)?)
+    async fn get_page(&self, url: String) -> Result<String, MPXError> {
+        let result = self.with_breaker(self.with_deadline(self.with_retry(false, || self.get_page_inner(url.clone())))).await;
+        self.record_health(&result);
+        result
     }
 
-    pub async fn get_info_receptacle(self: &Self, pdu: u8, branch: u8, receptacle: u8) -> Result<ReceptacleInfo, MPXError> {
-        let url = format!("http://{}/dp/std:{}.{}.{}_0.0.0/rpc/rpcReceptacle.htm", self.host, pdu, branch, receptacle);
-        let html = reqwest::get(url).await?.text().await?;
-        ReceptacleInfo::from_tables(get_info_tables(html)?)
+    async fn get_page_inner(&self, url: String) -> Result<String, MPXError> {
+        let response = self.request(transport::Request::get(&url)).await?;
+
+        Self::check_auth(&response)?;
+        self.record_page(&url, &response.body);
+        Ok(response.body)
     }
 
-    async fn send_query(self: &Self, url: String, params: &[(&str, &str)]) -> Result<(), MPXError> {
-        let client = reqwest::Client::new();
-        let response = client.post(url)
-            .basic_auth(self.username.clone(), Some(self.password.clone()))
-            .form(params)
-            .send()
-            .await?;
+    async fn send_query(&self, url: String, params: &[(&str, &str)]) -> Result<(), MPXError> {
+        let result = self.with_breaker(self.with_deadline(async {
+            // reads may proceed, but only one write at a time
+            let _queued = self.inner.writes.lock().await;
+            self.with_retry(true, || self.send_query_inner(url.clone(), params)).await
+        })).await;
+        self.record_health(&result);
+        result
+    }
+
+    async fn send_query_inner(&self, url: String, params: &[(&str, &str)]) -> Result<(), MPXError> {
+        let mut params = params.to_vec();
+        let token = match &self.inner.csrf_field {
+            Some(field) => self.csrf_token(&url, field).await?,
+            None => None,
+        };
+        if let (Some(field), Some(token)) = (&self.inner.csrf_field, &token) {
+            params.push((field, token));
+        }
+
+        let response = self.request(self.form_fields(transport::Request::post(&url, &params))).await?;
 
-        if response.status() != reqwest::StatusCode::OK && response.status() != reqwest::StatusCode::SEE_OTHER {
-            return Err(MPXError::InvalidDataError(InvalidDataError))
+        Self::check_auth(&response)?;
+        if response.status != 200 && response.status != 303 {
+            return Err(InvalidDataError { detail: InvalidData::UnexpectedStatus { status: response.status }, ..Default::default() }.into())
         }
 
         Ok(())
     }
 
-    pub async fn pdu_command(self: &Self, pdu: u8, cmd: PDUCmd) -> Result<(), MPXError> {
-        let url = format!("http://{}/dp/std:{}.0.0_0.0.0/rpc/rpcControlApsCommand", self.host, pdu);
-        match cmd {
+    pub async fn pdu_command(&self, pdu: impl Into<PduId>, cmd: PDUCmd) -> Result<(), MPXError> {
+        let PduId(pdu) = pdu.into();
+        self.check_address(pdu, None, None)?;
+        let url = self.url(&format!("/dp/std:{}.0.0_0.0.0/rpc/rpcControlApsCommand", pdu));
+        let result = match cmd {
             PDUCmd::TestEvent => self.send_query(url, &[("testEvent", "Send")]).await,
             PDUCmd::ResetEnergy => self.send_query(url, &[("energyControl", "Reset")]).await,
-        }
+        };
+        self.log_operation(&format!("{:?}", cmd), &format!("{}", pdu), &result);
+        result
     }
 
-    pub async fn pdu_reset_energy(self: &Self, pdu: u8) -> Result<(), MPXError> {
+    pub async fn pdu_reset_energy(&self, pdu: impl Into<PduId>) -> Result<(), MPXError> {
         self.pdu_command(pdu, PDUCmd::ResetEnergy).await
     }
 
-    pub async fn pdu_test_event(self: &Self, pdu: u8) -> Result<(), MPXError> {
+    pub async fn pdu_test_event(&self, pdu: impl Into<PduId>) -> Result<(), MPXError> {
         self.pdu_command(pdu, PDUCmd::TestEvent).await
     }
 
-    pub async fn branch_command(self: &Self, pdu: u8, branch: u8, cmd: BranchCmd) -> Result<(), MPXError> {
-        let url = format!("http://{}/dp/std:{}.{}.0_0.0.0/rpc/rpcControlRemCommand", self.host, pdu, branch);
-        match cmd {
+    pub async fn branch_command(&self, address: impl Into<BranchAddress>, cmd: BranchCmd) -> Result<(), MPXError> {
+        let BranchAddress { pdu: PduId(pdu), branch: BranchId(branch) } = address.into();
+        self.check_address(pdu, Some(branch), None)?;
+        let url = self.url(&format!("/dp/std:{}.{}.0_0.0.0/rpc/rpcControlRemCommand", pdu, branch));
+        let result = match cmd {
             BranchCmd::ResetEnergy => self.send_query(url, &[("energyControl", "Reset")]).await,
-        }
+        };
+        self.log_operation(&format!("{:?}", cmd), &format!("{}-{}", pdu, branch), &result);
+        result
     }
 
-    pub async fn branch_reset_energy(self: &Self, pdu: u8, branch: u8) -> Result<(), MPXError> {
-        self.branch_command(pdu, branch, BranchCmd::ResetEnergy).await
+    pub async fn branch_reset_energy(&self, address: impl Into<BranchAddress>) -> Result<(), MPXError> {
+        self.branch_command(address, BranchCmd::ResetEnergy).await
     }
 
-    pub async fn receptacle_command(self: &Self, pdu: u8, branch: u8, port: u8, cmd: ReceptacleCmd) -> Result<(), MPXError> {
-        let url = format!("http://{}/dp/std:{}.{}.{}_0.0.0/rpc/rpcControlReceptacleCommand", self.host, pdu, branch, port);
-        match cmd {
+    pub async fn receptacle_command(&self, address: impl Into<ReceptacleAddress>, cmd: ReceptacleCmd) -> Result<(), MPXError> {
+        let ReceptacleAddress { pdu: PduId(pdu), branch: BranchId(branch), receptacle: ReceptacleId(port) } = address.into();
+        self.check_address(pdu, Some(branch), Some(port))?;
+        let url = self.url(&format!("/dp/std:{}.{}.{}_0.0.0/rpc/rpcControlReceptacleCommand", pdu, branch, port));
+        let request = match cmd {
             ReceptacleCmd::Disable => self.send_query(url, &[("receptacleStateGroup", "0"), ("Submit", "Save")]),
             ReceptacleCmd::Enable => self.send_query(url, &[("receptacleStateGroup", "1"), ("Submit", "Save")]),
             ReceptacleCmd::Reboot => self.send_query(url, &[("receptacleStateGroup", "2"), ("Submit", "Save")]),
             ReceptacleCmd::Identify => self.send_query(url, &[("rcpIdentControl", "Submit")]),
             ReceptacleCmd::ResetEnergy => self.send_query(url, &[("energyControl", "Reset")]),
-        }.await
+        };
+        let result = self.receptacle_control((pdu, branch, port), &format!("{:?}", cmd), request).await;
+        self.log_operation(&format!("{:?}", cmd), &format!("{}-{}-{}", pdu, branch, port), &result);
+        result
     }
 
-    pub async fn receptacle_identify(self: &Self, pdu: u8, branch: u8, port: u8) -> Result<(), MPXError> {
-        self.receptacle_command(pdu, branch, port, ReceptacleCmd::Identify).await
+    pub async fn receptacle_identify(&self, address: impl Into<ReceptacleAddress>) -> Result<(), MPXError> {
+        self.receptacle_command(address, ReceptacleCmd::Identify).await
     }
 
-    pub async fn receptacle_reboot(self: &Self, pdu: u8, branch: u8, port: u8) -> Result<(), MPXError> {
-        self.receptacle_command(pdu, branch, port, ReceptacleCmd::Reboot).await
+    pub async fn receptacle_reboot(&self, address: impl Into<ReceptacleAddress>) -> Result<(), MPXError> {
+        self.receptacle_command(address, ReceptacleCmd::Reboot).await
     }
 
-    pub async fn receptacle_enable(self: &Self, pdu: u8, branch: u8, port: u8) -> Result<(), MPXError> {
-        self.receptacle_command(pdu, branch, port, ReceptacleCmd::Enable).await
+    pub async fn receptacle_enable(&self, address: impl Into<ReceptacleAddress>) -> Result<(), MPXError> {
+        self.receptacle_command(address, ReceptacleCmd::Enable).await
     }
 
-    pub async fn receptacle_disable(self: &Self, pdu: u8, branch: u8, port: u8) -> Result<(), MPXError> {
-        self.receptacle_command(pdu, branch, port, ReceptacleCmd::Disable).await
+    pub async fn receptacle_disable(&self, address: impl Into<ReceptacleAddress>) -> Result<(), MPXError> {
+        self.receptacle_command(address, ReceptacleCmd::Disable).await
     }
 
-    pub async fn receptacle_reset_energy(self: &Self, pdu: u8, branch: u8, port: u8) -> Result<(), MPXError> {
-        self.receptacle_command(pdu, branch, port, ReceptacleCmd::ResetEnergy).await
+    pub async fn receptacle_reset_energy(&self, address: impl Into<ReceptacleAddress>) -> Result<(), MPXError> {
+        self.receptacle_command(address, ReceptacleCmd::ResetEnergy).await
     }
 
-    pub async fn set_pdu_settings(self: &Self, pdu: u8, settings: &PDUSettings) -> Result<(), MPXError> {
-        let url = format!("http://{}/dp/std:{}.0.0_0.0.0/rpc/rpcControlApsSetting", self.host, pdu);
-        let parameters = [
+    pub async fn set_pdu_settings(&self, pdu: impl Into<PduId>, settings: &PDUSettings) -> Result<(), MPXError> {
+        let PduId(pdu) = pdu.into();
+        self.check_address(pdu, None, None)?;
+        settings.validate()?;
+        let url = self.url(&format!("/dp/std:{}.0.0_0.0.0/rpc/rpcControlApsSetting", pdu));
+        // single phase PDUs have no L2/L3 thresholds
+        let thresholds: Vec<(&str, String)> = [
+            ("ecNeutralThrshldOverAlarm", Some(settings.n_over_current_alarm_threshold)),
+            ("ecNeutralThrshldOverWarn", Some(settings.n_over_current_warning_threshold)),
+            ("ecThresholdHiAlmL1", Some(settings.l1_over_current_alarm_threshold)),
+            ("ecThresholdHiAlmL2", settings.l2_over_current_alarm_threshold),
+            ("ecThresholdHiAlmL3", settings.l3_over_current_alarm_threshold),
+            ("ecThresholdHiWrnL1", Some(settings.l1_over_current_warning_threshold)),
+            ("ecThresholdHiWrnL2", settings.l2_over_current_warning_threshold),
+            ("ecThresholdHiWrnL3", settings.l3_over_current_warning_threshold),
+            ("ecThresholdLoAlmL1", Some(settings.l1_low_current_alarm_threshold)),
+            ("ecThresholdLoAlmL2", settings.l2_low_current_alarm_threshold),
+            ("ecThresholdLoAlmL3", settings.l3_low_current_alarm_threshold),
+        ].iter().filter_map(|(name, value)| Some((*name, (*value)?.to_string()))).collect();
+        let mut parameters = vec![
             ("Submit", "Save"),
-            ("label", &settings.label),
-            ("assetTag1", &settings.asset_tag_1),
-            ("assetTag2", &settings.asset_tag_2),
-            ("ecNeutralThrshldOverAlarm", &format!("{}", settings.n_over_current_alarm_threshold)),
-            ("ecNeutralThrshldOverWarn", &format!("{}", settings.n_over_current_warning_threshold)),
-            ("ecThresholdHiAlmL1", &format!("{}", settings.l1_over_current_alarm_threshold)),
-            ("ecThresholdHiAlmL2", &format!("{}", settings.l2_over_current_alarm_threshold)),
-            ("ecThresholdHiAlmL3", &format!("{}", settings.l3_over_current_alarm_threshold)),
-            ("ecThresholdHiWrnL1", &format!("{}", settings.l1_over_current_warning_threshold)),
-            ("ecThresholdHiWrnL2", &format!("{}", settings.l2_over_current_warning_threshold)),
-            ("ecThresholdHiWrnL3", &format!("{}", settings.l3_over_current_warning_threshold)),
-            ("ecThresholdLoAlmL1", &format!("{}", settings.l1_low_current_alarm_threshold)),
-            ("ecThresholdLoAlmL2", &format!("{}", settings.l2_low_current_alarm_threshold)),
-            ("ecThresholdLoAlmL3", &format!("{}", settings.l3_low_current_alarm_threshold)),
+            ("label", settings.label.as_str()),
+            ("assetTag1", settings.asset_tag_1.as_str()),
+            ("assetTag2", settings.asset_tag_2.as_str()),
         ];
-        self.send_query(url, &parameters).await
+        parameters.extend(thresholds.iter().map(|(name, value)| (*name, value.as_str())));
+        let result = self.send_query(url, &parameters).await;
+        self.log_operation("set_pdu_settings", &format!("{}", pdu), &result);
+        result
     }
 
-    pub async fn set_branch_settings(self: &Self, pdu: u8, branch: u8, settings: &BranchSettings) -> Result<(), MPXError> {
-        let url = format!("http://{}/dp/std:{}.{}.0_0.0.0/rpc/rpcControlRemSetting", self.host, pdu, branch);
+    pub async fn set_branch_settings(&self, address: impl Into<BranchAddress>, settings: &BranchSettings) -> Result<(), MPXError> {
+        let BranchAddress { pdu: PduId(pdu), branch: BranchId(branch) } = address.into();
+        self.check_address(pdu, Some(branch), None)?;
+        settings.validate()?;
+        let url = self.url(&format!("/dp/std:{}.{}.0_0.0.0/rpc/rpcControlRemSetting", pdu, branch));
         let parameters = [
             ("Submit", "Save"),
             ("label", &settings.label),
@@ -1643,11 +3119,16 @@ impl MPX {
             ("ecThresholdHiWrnLN", &format!("{}", settings.over_current_warning_threshold)),
             ("ecThresholdLoAlmLN", &format!("{}", settings.low_current_alarm_threshold)),
         ];
-        self.send_query(url, &parameters).await
+        let result = self.send_query(url, &parameters).await;
+        self.log_operation("set_branch_settings", &format!("{}-{}", pdu, branch), &result);
+        result
     }
 
-    pub async fn set_receptacle_settings(self: &Self, pdu: u8, branch: u8, receptacle: u8, settings: &ReceptacleSettings) -> Result<(), MPXError> {
-        let url = format!("http://{}/dp/std:{}.{}.{}_0.0.0/rpc/rpcControlReceptacleSetting", self.host, pdu, branch, receptacle);
+    pub async fn set_receptacle_settings(&self, address: impl Into<ReceptacleAddress>, settings: &ReceptacleSettings) -> Result<(), MPXError> {
+        let ReceptacleAddress { pdu: PduId(pdu), branch: BranchId(branch), receptacle: ReceptacleId(receptacle) } = address.into();
+        self.check_address(pdu, Some(branch), Some(receptacle))?;
+        settings.validate()?;
+        let url = self.url(&format!("/dp/std:{}.{}.{}_0.0.0/rpc/rpcControlReceptacleSetting", pdu, branch, receptacle));
         let parameters = [
             ("Submit", "Save"),
             ("label", &settings.label),
@@ -1656,10 +3137,12 @@ impl MPX {
             ("ecThresholdHiAlmL1", &format!("{}", settings.over_current_alarm_threshold)),
             ("ecThresholdHiWrnL1", &format!("{}", settings.over_current_warning_threshold)),
             ("ecThresholdLoAlmL1", &format!("{}", settings.low_current_alarm_threshold)),
-            ("powerUpDelay", &format!("{}", settings.power_on_delay)),
+            ("powerUpDelay", &format!("{}", settings.power_on_delay.as_secs())),
             ("lockStateTypeGroup1", if settings.control_lock_state { "1" } else { "0" }),
         ];
-        self.send_query(url, &parameters).await
+        let result = self.receptacle_control((pdu, branch, receptacle), "set_receptacle_settings", self.send_query(url, &parameters)).await;
+        self.log_operation("set_receptacle_settings", &format!("{}-{}-{}", pdu, branch, receptacle), &result);
+        result
     }
 }
 
@@ -1667,6 +3150,297 @@ impl MPX {
 mod parser_unit_tests {
     use super::*;
 
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_builder_url() {
+        let mpx = MPX::builder("pdu1").scheme("https").port(8443).build().unwrap();
+        assert_eq!(mpx.url("/rpc/rpcActiveAlarms.htm"), "https://pdu1:8443/rpc/rpcActiveAlarms.htm");
+        assert_eq!(MPX::new("pdu1", "", "").url("/x"), "http://pdu1/x");
+        assert_eq!(MPX::with_client(reqwest::Client::new(), "pdu2", "", "").url("/x"), "http://pdu2/x");
+    }
+
+    #[test]
+    fn test_error_messages() {
+        use std::error::Error;
+
+        let e = MPXError::from(InvalidDataError::default());
+        assert_eq!(e.to_string(), "malformed data in PDU response");
+        assert!(e.source().is_none());
+
+        let e = MPXError::from("x".parse::<u8>().unwrap_err());
+        assert_eq!(e.to_string(), "invalid integer in PDU response");
+        assert_eq!(e.source().unwrap().to_string(), "invalid digit found in string");
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("R&amp;D M&uuml;nchen"), "R&D München");
+        assert_eq!(decode_entities("&#228;&#xE4;&nbsp;"), "ää\u{a0}");
+        assert_eq!(decode_entities("A & B &unknown; &"), "A & B &unknown; &");
+
+        let html = include_str!("../testdata/receptacle-info.htm").replace("Receptacle 1.1.1", "Rack 4 &#8211; Caf&eacute; &amp; Bar");
+        let info = parse_receptacle_info(html).unwrap();
+        assert_eq!(info.settings.label, "Rack 4 – Café & Bar");
+        assert_eq!(info.settings.asset_tag_1, "");
+    }
+
+    #[test]
+    fn test_error_kind() {
+        let e = MPXError::from(MissingDataError::default());
+        assert_eq!(e.kind(), ErrorKind::Parse);
+        assert!(e.is_parse() && !e.is_retryable() && !e.is_auth());
+
+        let e = MPXError::from(AuthenticationError { status: 401 });
+        assert!(e.is_auth() && !e.is_retryable());
+
+        let e = MPXError::from(TimeoutError { deadline: None });
+        assert_eq!(e.kind(), ErrorKind::Network);
+        assert!(e.is_retryable());
+
+        let e = MPXError::from(TimeoutError { deadline: Some(Duration::from_secs(5)) });
+        assert_eq!(e.kind(), ErrorKind::Timeout);
+        assert!(!e.is_retryable());
+    }
+
+    #[test]
+    fn test_unknown_variants() {
+        let event = EventType::from_str("PDU Over Temperature").unwrap();
+        assert_eq!(event, EventType::Unknown("PDU Over Temperature".to_string()));
+        assert_eq!(event.to_string(), "PDU Over Temperature");
+
+        assert_eq!(PEMModel::from_str("MPXPEM-EHAXXQ30").unwrap().to_string(), "MPXPEM-EHAXXQ30");
+        assert_eq!(PEMModel::from_str("MPXPEM-EHCXXZ40").unwrap().to_string(), "MPXPEM-EHCXXZ40");
+        assert_eq!(BRMModel::from_str("MPXBRM-EXBC9N1N").unwrap(), BRMModel::Unknown("MPXBRM-EXBC9N1N".to_string()));
+        assert_eq!(LineSource::from_str("Type L1-L2").unwrap().to_string(), "Type L1-L2");
+    }
+
+    #[test]
+    fn test_parse_error_context() {
+        let html = include_str!("../testdata/receptacle-info.htm").replace("Receptacle Power Factor", "Receptacle Power Fctr");
+        let e = parse_receptacle_info(html).unwrap_err().at_url("http://pdu1/rpc/rpcReceptacle.htm");
+        match &e {
+            MPXError::MissingDataError(e) => {
+                assert_eq!(e.key.as_deref(), Some("Receptacle Power Factor"));
+                assert_eq!(e.table.as_deref(), Some("status"));
+            },
+            _ => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(e.to_string(), "required data missing in PDU response (key 'Receptacle Power Factor') in status table of http://pdu1/rpc/rpcReceptacle.htm");
+
+        let value = TableValue { key: "Branch Voltage".to_string(), value: "23.x".to_string(), unit: "VAC".to_string() };
+        assert_eq!(value.get_f32("VAC").unwrap_err().to_string(), "malformed data in PDU response (key 'Branch Voltage')");
+    }
+
+    #[test]
+    fn test_fw_version() {
+        let dashed = FWVersion::from_str("0-3-4-2").unwrap();
+        let dotted = FWVersion::from_str("0.3.4.2").unwrap();
+        assert_eq!(dashed, dotted);
+        assert_eq!(dotted, FWVersion::new(0, 3, 4, 2));
+        assert_eq!(FWVersion::from_str(&dotted.to_string()).unwrap(), dotted);
+        assert!(FWVersion::from_str("0.3.4").is_err());
+        assert!(FWVersion::from_str("0.3-4.2").is_err());
+
+        assert!(dotted < FWVersion::new(0, 10, 0, 0));
+        assert!(FWVersion::new(5, 4, 0, 0) > FWVersion::new(5, 3, 9, 9));
+        assert!(dotted.at_least(0, 3, 4, 2));
+        assert!(dotted.at_least(0, 3, 0, 0));
+        assert!(!dotted.at_least(5, 4, 0, 0));
+        assert_eq!([FWVersion::new(1, 0, 0, 0), dotted].iter().max(), Some(&FWVersion::new(1, 0, 0, 0)));
+    }
+
+    #[test]
+    fn test_check_complete() {
+        for page in [
+            include_str!("../testdata/pdu-info.htm"),
+            include_str!("../testdata/branch-info.htm"),
+            include_str!("../testdata/receptacle-info.htm"),
+            include_str!("../testdata/receptacle-list.htm"),
+            include_str!("../testdata/events-test.htm"),
+            include_str!("../testdata/events-none.htm"),
+        ].iter() {
+            assert!(check_complete(page).is_ok());
+        }
+        assert!(check_complete("<table><tr><td>1<td>2</table>").is_ok());
+        assert!(check_complete("<HTML><body><p>text</BODY></HTML>\r\n").is_ok());
+
+        let page = include_str!("../testdata/receptacle-info.htm");
+        let e = parse_receptacle_info(page[..page.len() / 2].to_string()).unwrap_err();
+        assert!(e.to_string().contains("missing </html>"), "{}", e);
+
+        // optional end tags may be left out
+        let html = page.replacen("</body>", "<p>note<ul><li>one<li>two</ul></body>", 1);
+        assert_eq!(parse_receptacle_info(html).unwrap(), parse_receptacle_info(page.to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_receptacle_types() {
+        let html = include_str!("../testdata/receptacle-info.htm").to_string();
+        assert_eq!(parse_receptacle_info(html).unwrap().hardware.receptacle_type, ReceptacleType::C13);
+
+        // strings without device capture are kept as reported
+        for raw in ["C13", "NEMA 5-15", "BS 1363", "IEC 60309 16A"].iter() {
+            let parsed = ReceptacleType::from_str(raw).unwrap();
+            assert_eq!(parsed, ReceptacleType::Unknown(raw.to_string()));
+            assert_eq!(parsed.to_string(), *raw);
+        }
+    }
+
+    #[test]
+    fn test_branch_breaker_state() {
+        let html = include_str!("../testdata/branch-info.htm");
+        assert_eq!(parse_branch_info(html.to_string()).unwrap().events.breaker_state(), BreakerState::Closed);
+
+        let open = html.replace("accept.png\"></td><td class=\"left\">Branch Breaker Open", "err.png\"></td><td class=\"left\">Branch Breaker Open");
+        assert_eq!(parse_branch_info(open).unwrap().events.breaker_state(), BreakerState::Open);
+    }
+
+    #[test]
+    fn test_event_level_order() {
+        assert!(EventLevel::OK < EventLevel::INFO && EventLevel::INFO < EventLevel::WARNING && EventLevel::WARNING < EventLevel::ALARM);
+        let mut levels = [EventLevel::WARNING, EventLevel::OK, EventLevel::ALARM, EventLevel::INFO];
+        assert_eq!(levels.iter().max(), Some(&EventLevel::ALARM));
+        levels.sort();
+        assert_eq!(levels.iter().map(EventLevel::severity).collect::<Vec<u8>>(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_invalid_data_detail() {
+        let value = TableValue { key: "Branch Voltage".to_string(), value: "230.0".to_string(), unit: "A AC".to_string() };
+        match value.get_f32("VAC").unwrap_err() {
+            MPXError::InvalidDataError(e) => assert_eq!(e.detail, InvalidData::UnitMismatch { expected: "VAC".to_string(), got: "A AC".to_string() }),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let html = "<table id=\"rcpTable\"><tr id=\"1-x-1\"><td></td></tr></table>".to_string();
+        let e = parse_receptacles(html).unwrap_err();
+        assert_eq!(e.to_string(), "malformed data in PDU response: bad identifier '1-x-1'");
+
+        let html = include_str!("../testdata/receptacle-info.htm").replace("RpcAlarmArea", "RpcAlarms");
+        match parse_receptacle_info(html).unwrap_err() {
+            MPXError::InvalidDataError(e) => assert_eq!(e.detail, InvalidData::UnexpectedStructure { location: "RpcAlarmArea".to_string() }),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_single_phase_pdu() {
+        let html: String = include_str!("../testdata/pdu-info.htm")
+            .replace("3-Phase / 5-Wire (L1, L2, L3, N, PE)", "1-Phase / 3-Wire (L, N, PE)")
+            .split_inclusive("</tr>")
+            .map(|row| match row.rfind("<tr") {
+                Some(start) if row[start..].contains("L2") || row[start..].contains("L3") => &row[..start],
+                _ => row,
+            })
+            .collect();
+        let info = parse_pdu_info(html).unwrap();
+        assert!(info.status.voltage_l2_n.is_none() && info.status.current_l3.is_none());
+        assert!(info.settings.l2_over_current_alarm_threshold.is_none());
+        assert!(info.events.low_voltage_l3.is_none());
+        assert!(info.status.voltage_l1_n > 0.0);
+
+        let html = include_str!("../testdata/pdu-info.htm").replace(">PDU Current L2<", ">PDU Current L4<");
+        match parse_pdu_info(html).unwrap_err() {
+            MPXError::MissingDataError(e) => assert_eq!(e.key.as_deref(), Some("PDU Current L2")),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_shared_handle() {
+        fn assert_shared<T: Clone + Send + Sync + 'static>() {}
+        assert_shared::<MPX>();
+
+        let mpx = MPX::new("pdu1", "", "");
+        let clone = mpx.clone();
+        mpx.check_identity(1, "0815").unwrap();
+        assert_eq!(clone.pinned_serial(1).as_deref(), Some("0815"));
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_redacted_debug() {
+        let builder = MPX::builder("pdu1").credentials("Liebert", "hunter2");
+        assert!(!format!("{:?}", builder).contains("hunter2"));
+        let mpx = builder.build().unwrap();
+        assert!(!format!("{:?}", mpx).contains("hunter2"));
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_deadline() {
+        let mpx = MPX::builder("pdu1").deadline(Duration::from_millis(10)).build().unwrap();
+        let result = mpx.with_deadline(async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        }).await;
+        assert!(matches!(result, Err(MPXError::Timeout(TimeoutError { deadline: Some(_) }))));
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_write_queue() {
+        let mpx = MPX::builder("pdu1").deadline(Duration::from_millis(10)).build().unwrap();
+        let _pending = mpx.inner.writes.lock().await;
+        let result = mpx.send_query(mpx.url("/rpc/rpcControlApsCommand"), &[("testEvent", "Send")]).await;
+        assert!(matches!(result, Err(MPXError::Timeout(_))));
+        assert_eq!(mpx.consecutive_failures(), 1);
+        assert!(mpx.last_error().is_some());
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_headers() {
+        use reqwest::header::{HeaderName, HeaderValue};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let len = socket.read(&mut request).await.unwrap();
+            socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await.unwrap();
+            String::from_utf8_lossy(&request[..len]).to_ascii_lowercase()
+        });
+
+        let mpx = MPX::builder("127.0.0.1").port(port)
+            .user_agent("oob-inventory/1.0")
+            .header(HeaderName::from_static("x-audit-id"), HeaderValue::from_static("ticket-42"))
+            .build().unwrap();
+        mpx.get_page(mpx.url("/")).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.contains("user-agent: oob-inventory/1.0\r\n"));
+        assert!(request.contains("x-audit-id: ticket-42\r\n"));
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_authority() {
+        assert_eq!(authority("pdu1", None), "pdu1");
+        assert_eq!(authority("pdu1:8080", None), "pdu1:8080");
+        assert_eq!(authority("pdu1:8080", Some(81)), "pdu1:81");
+        assert_eq!(authority("192.168.23.42", Some(80)), "192.168.23.42:80");
+        assert_eq!(authority("2001:db8::42", None), "[2001:db8::42]");
+        assert_eq!(authority("2001:db8::42", Some(8080)), "[2001:db8::42]:8080");
+        assert_eq!(authority("fe80::1%eth0", None), "[fe80::1%25eth0]");
+        assert_eq!(authority("[2001:db8::42]:8080", None), "[2001:db8::42]:8080");
+        assert_eq!(authority("[2001:db8::42]", Some(443)), "[2001:db8::42]:443");
+    }
+
+    /// Receptacle list as returned by `rpcReceptacleListData.htm`, i.e.
+    /// the rows of the table of the saved receptacle list page with the
+    /// image paths the browser rewrote on saving restored
+    #[cfg(feature = "client")]
+    pub(crate) fn receptacle_list_data() -> String {
+        let page = include_str!("../testdata/receptacle-list.htm");
+        let start = page.find("<tr id=").unwrap();
+        let end = start + page[start..].find("</tbody></table>").unwrap();
+        format!("<table id=\"rcpTable\">{}</table>", page[start..end].replace("rpcReceptacleList_files/", "../../../images/"))
+    }
+
     #[test]
     fn test_01_parse_receptacles() {
         let html = include_str!("../testdata/receptacle-list.htm").to_string();
@@ -1675,6 +3449,38 @@ mod parser_unit_tests {
         assert!(parsed.is_ok())
     }
 
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_get_pdus_and_branches() {
+        use transport::{HttpTransport, Request, Response};
+
+        struct Device;
+
+        impl HttpTransport for Device {
+            async fn send(&self, request: Request) -> Result<Response, MPXError> {
+                let body = match request.url.as_str() {
+                    url if url.contains("rpcReceptacleListData.htm") => receptacle_list_data(),
+                    url if url.contains("rpcAps.htm") => include_str!("../testdata/pdu-info.htm").to_string(),
+                    url if url.contains("rpcRem.htm") => include_str!("../testdata/branch-info.htm").to_string(),
+                    url => panic!("unexpected request {}", url),
+                };
+                Ok(Response { status: 200, url: request.url, headers: Vec::new(), body })
+            }
+        }
+
+        let mpx = MPX::builder("pdu1").build_with_transport(Device);
+        let pdus = mpx.get_pdus().await.unwrap();
+        assert_eq!(pdus.iter().map(|p| p.pdu).collect::<Vec<u8>>(), vec![1]);
+        assert_eq!(pdus[0].model, parse_pdu_info(include_str!("../testdata/pdu-info.htm").to_string()).unwrap().hardware.pem_model);
+
+        let branches = mpx.get_branches().await.unwrap();
+        assert_eq!(branches.iter().map(|b| (b.pdu, b.branch)).collect::<Vec<_>>(), vec![(1, 1), (1, 2), (1, 3)]);
+        assert_eq!(branches[0].model, BRMModel::ERBC6N3N);
+        assert_eq!(branches[0].status, EventLevel::OK);
+        assert_eq!(mpx.get_branches_pdu(1).await.unwrap().len(), 3);
+        assert!(mpx.get_branches_pdu(2).await.is_err());
+    }
+
     #[test]
     fn test_02_parse_events_empty() {
         let html = include_str!("../testdata/events-none.htm").to_string();
@@ -1697,8 +3503,8 @@ mod parser_unit_tests {
         let tables = get_info_tables(html);
         assert!(tables.is_ok(), "failed to get info tables");
 
-        if tables.is_ok() {
-            let info = PDUInfo::from_tables(tables.unwrap());
+        if let Ok(tables) = tables {
+            let info = PDUInfo::from_tables(tables);
             assert!(info.is_ok(), "failed to get PDUInfo");
         }
     }
@@ -1709,8 +3515,8 @@ mod parser_unit_tests {
         let tables = get_info_tables(html);
         assert!(tables.is_ok(), "failed to get info tables");
 
-        if tables.is_ok() {
-            let info = BranchInfo::from_tables(tables.unwrap());
+        if let Ok(tables) = tables {
+            let info = BranchInfo::from_tables(tables);
             assert!(info.is_ok(), "failed to get BranchInfo");
         }
     }
@@ -1721,9 +3527,148 @@ mod parser_unit_tests {
         let tables = get_info_tables(html);
         assert!(tables.is_ok(), "failed to get info tables");
 
-        if tables.is_ok() {
-            let info = ReceptacleInfo::from_tables(tables.unwrap());
+        if let Ok(tables) = tables {
+            let info = ReceptacleInfo::from_tables(tables);
             assert!(info.is_ok(), "failed to get ReceptacleInfo");
         }
     }
+
+    #[test]
+    #[cfg(feature = "client")]
+    fn test_07_identity_pinning() {
+        let pdu = MPX::new("192.168.23.42", "Liebert", "Liebert");
+        assert!(pdu.check_identity(1, "1234").is_ok());
+        assert!(pdu.check_identity(1, "1234").is_ok());
+        assert!(pdu.check_identity(2, "5678").is_ok());
+        assert_eq!(pdu.pinned_serial(1), Some("1234".to_string()));
+
+        match pdu.check_identity(1, "5678") {
+            Err(MPXError::IdentityMismatch(e)) => {
+                assert_eq!(e.expected, "1234");
+                assert_eq!(e.found, "5678");
+            },
+            _ => panic!("serial change not detected"),
+        }
+
+        pdu.unpin_serial(1);
+        assert!(pdu.check_identity(1, "5678").is_ok());
+    }
+
+    #[test]
+    fn test_08_power_on_delay_duration() {
+        let html = include_str!("../testdata/receptacle-info.htm").to_string();
+        let info = parse_receptacle_info(html).unwrap();
+        assert_eq!(info.settings.power_on_delay, Duration::from_secs(0));
+
+        let value = TableValue { key: "Power On Delay".to_string(), value: "5".to_string(), unit: "sec".to_string() };
+        assert_eq!(value.get_duration("sec").unwrap(), Duration::from_secs(5));
+        assert!(value.get_duration("min").is_err());
+    }
+
+    #[test]
+    fn test_unit_conversion() {
+        let value = |value: &str, unit: &str| TableValue { key: "Receptacle Accumulated Energy".to_string(), value: value.to_string(), unit: unit.to_string() };
+        assert_eq!(value("1.5", "kWh").get_f32("kWH").unwrap(), 1.5);
+        assert_eq!(value("1500", "Wh").get_f32("kWH").unwrap(), 1.5);
+        assert_eq!(value("0.25", "kW").get_f32("W").unwrap(), 250.0);
+        assert_eq!(value("0.5", "kVA").get_f32("VA").unwrap(), 500.0);
+        assert_eq!(value("0.98", "&nbsp;").get_f32("").unwrap(), 0.98);
+        assert_eq!(value("16", "A").get_u32("A AC").unwrap(), 16);
+        assert_eq!(value("0.23", "kV").get_u32("VAC").unwrap(), 230);
+        assert!(value("230", "VAC").get_f32("W").is_err());
+        assert!(value("1", "k").get_f32("").is_err());
+    }
+}
+
+#[cfg(test)]
+mod parser_prop_tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const RECEPTACLE_INFO: &str = include_str!("../testdata/receptacle-info.htm");
+
+    /// Line of the table with the `header` row, which the fixtures keep
+    /// on a single line, split into the header and the data rows
+    fn table_rows<'a>(html: &'a str, header: &str) -> (&'a str, &'a str, Vec<String>) {
+        let line = html.lines().find(|l| l.contains(header)).unwrap();
+        let (head, rows) = line.split_at(line.find("</tr>").unwrap() + "</tr>".len());
+        (line, head, rows.split_inclusive("</tr>").map(|r| r.trim().to_string()).filter(|r| r.starts_with("<tr")).collect())
+    }
+
+    /// Parsed fixture, shared by all cases
+    fn receptacle_info() -> &'static ReceptacleInfo {
+        static INFO: std::sync::OnceLock<ReceptacleInfo> = std::sync::OnceLock::new();
+        INFO.get_or_init(|| parse_receptacle_info(RECEPTACLE_INFO.to_string()).unwrap())
+    }
+
+    fn replace_rows(html: &str, header: &str, rows: &[String]) -> String {
+        let (line, head, _) = table_rows(html, header);
+        html.replace(line, &format!("{}{}", head, rows.concat()))
+    }
+
+    // parsing a fixture takes about half a second in debug builds
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+
+        #[test]
+        fn reordered_rows(rows in Just(table_rows(RECEPTACLE_INFO, "Supported Status").2).prop_shuffle()) {
+            let html = replace_rows(RECEPTACLE_INFO, "Supported Status", &rows);
+            prop_assert_eq!(&parse_receptacle_info(html).unwrap(), receptacle_info());
+        }
+
+        #[test]
+        fn extra_whitespace(before in "[ \t\n]{0,4}", after in "[ \t\n]{0,4}") {
+            let html = RECEPTACLE_INFO
+                .replace("<td class=\"right\">", &format!("<td class=\"right\">{}", before))
+                .replace("</td><td>", &format!("{}</td><td>", after));
+            prop_assert_eq!(&parse_receptacle_info(html).unwrap(), receptacle_info());
+        }
+
+        #[test]
+        fn missing_units(index in 0..12usize) {
+            let (_, _, mut rows) = table_rows(RECEPTACLE_INFO, "Supported Status");
+            let row = index % rows.len();
+            let unit_start = rows[row].rfind("<td>").unwrap() + "<td>".len();
+            let unit_end = rows[row].rfind("</td>").unwrap();
+            rows[row].replace_range(unit_start..unit_end, "");
+            match parse_receptacle_info(replace_rows(RECEPTACLE_INFO, "Supported Status", &rows)) {
+                Ok(info) => prop_assert!(info.status.power_factor.is_some()),
+                Err(e) => prop_assert_eq!(e.kind(), ErrorKind::Parse),
+            }
+        }
+
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn truncated_pages(fraction in 0.0..1.0f64) {
+            for page in [
+                RECEPTACLE_INFO,
+                include_str!("../testdata/branch-info.htm"),
+                include_str!("../testdata/pdu-info.htm"),
+                include_str!("../testdata/events-test.htm"),
+                include_str!("../testdata/events-none.htm"),
+            ].iter() {
+                let mut end = (page.len() as f64 * fraction) as usize;
+                while !page.is_char_boundary(end) {
+                    end -= 1;
+                }
+                let html = page[..end].to_string();
+                let _ = parse_pdu_info(html.clone());
+                let _ = parse_branch_info(html.clone());
+                let _ = parse_receptacle_info(html.clone());
+                let _ = parse_events(html.clone());
+                let _ = parse_receptacles(html);
+            }
+        }
+
+        #[test]
+        fn arbitrary_input(html in "(<[a-z/]{0,6}( id=\"[a-zA-Z-]{0,12}\")?>|[^<]{0,8}){0,32}") {
+            let _ = parse_receptacle_info(html.clone());
+            let _ = parse_events(html.clone());
+            let _ = parse_receptacles(html);
+        }
+    }
 }
@@ -0,0 +1,232 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Headroom to the configured over current thresholds
+//!
+//! The thresholds are configured in percent of the rated current, while
+//! status values are reported in A AC. The helpers in this module
+//! combine status, settings and ratings into the remaining current
+//! before a warning or alarm is raised.
+//!
+//! The `safe_margin` helpers apply a continuous load derating factor
+//! (e.g. [`CONTINUOUS_LOAD_DERATING`], the 80% rule) to the rated current
+//! instead; [`Margin::exceeded`] then tells if a phase, branch module or
+//! receptacle is over its safe limit.
+
+use crate::{BranchInfo, PDUInfo, ReceptacleInfo};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Usual derating factor for continuous loads (80% of the rated current)
+pub const CONTINUOUS_LOAD_DERATING: f32 = 0.8;
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Headroom to a current threshold
+pub struct Margin {
+    /// current at which the threshold is reached in A AC
+    pub limit: f32,
+    /// current left until the threshold is reached in A AC (negative if exceeded)
+    pub remaining: f32,
+    /// utilization left until the threshold is reached in percentage points
+    pub remaining_percent: f32,
+}
+
+impl Margin {
+    /// Margin of `current` to `threshold` percent of `rated_current`
+    pub fn new(rated_current: f32, threshold: u32, current: f32) -> Self {
+        Self::with_threshold(rated_current, threshold as f32, current)
+    }
+
+    /// Margin of `current` to `factor` (e.g. 0.8) times `rated_current`
+    pub fn derated(rated_current: f32, factor: f32, current: f32) -> Self {
+        Self::with_threshold(rated_current, factor * 100.0, current)
+    }
+
+    fn with_threshold(rated_current: f32, threshold: f32, current: f32) -> Self {
+        let limit = rated_current * threshold / 100.0;
+        let remaining_percent = if rated_current > 0.0 {
+            threshold - current / rated_current * 100.0
+        } else {
+            0.0
+        };
+
+        Margin {
+            limit,
+            remaining: limit - current,
+            remaining_percent,
+        }
+    }
+
+    /// Threshold has been reached or exceeded
+    pub fn exceeded(&self) -> bool {
+        self.remaining <= 0.0
+    }
+}
+
+/// Margin of a phase which is missing on single phase PDUs
+fn phase_margin(rated_current: f32, threshold: Option<u32>, current: Option<f32>) -> Option<Margin> {
+    Some(Margin::new(rated_current, threshold?, current?))
+}
+
+impl PDUInfo {
+    /// Margins to the over current alarm thresholds of L1, L2 and L3,
+    /// `None` for L2 and L3 of single phase PDUs
+    pub fn over_current_alarm_margins(&self) -> [Option<Margin>; 3] {
+        let rated = self.hardware.rated_input_current as f32;
+        let (s, c) = (&self.status, &self.settings);
+        [
+            Some(Margin::new(rated, c.l1_over_current_alarm_threshold, s.current_l1)),
+            phase_margin(rated, c.l2_over_current_alarm_threshold, s.current_l2),
+            phase_margin(rated, c.l3_over_current_alarm_threshold, s.current_l3),
+        ]
+    }
+
+    /// Margins to the over current warning thresholds of L1, L2 and L3,
+    /// `None` for L2 and L3 of single phase PDUs
+    pub fn over_current_warning_margins(&self) -> [Option<Margin>; 3] {
+        let rated = self.hardware.rated_input_current as f32;
+        let (s, c) = (&self.status, &self.settings);
+        [
+            Some(Margin::new(rated, c.l1_over_current_warning_threshold, s.current_l1)),
+            phase_margin(rated, c.l2_over_current_warning_threshold, s.current_l2),
+            phase_margin(rated, c.l3_over_current_warning_threshold, s.current_l3),
+        ]
+    }
+
+    /// Margins of L1, L2 and L3 to the derated input current, `None` for
+    /// L2 and L3 of single phase PDUs
+    pub fn safe_margins(&self, factor: f32) -> [Option<Margin>; 3] {
+        let rated = self.hardware.rated_input_current as f32;
+        let s = &self.status;
+        [
+            Some(Margin::derated(rated, factor, s.current_l1)),
+            s.current_l2.map(|current| Margin::derated(rated, factor, current)),
+            s.current_l3.map(|current| Margin::derated(rated, factor, current)),
+        ]
+    }
+
+    /// Margin to the neutral over current alarm threshold
+    pub fn neutral_over_current_alarm_margin(&self) -> Margin {
+        Margin::new(self.hardware.rated_input_current as f32, self.settings.n_over_current_alarm_threshold, self.status.current_n)
+    }
+
+    /// Margin to the neutral over current warning threshold
+    pub fn neutral_over_current_warning_margin(&self) -> Margin {
+        Margin::new(self.hardware.rated_input_current as f32, self.settings.n_over_current_warning_threshold, self.status.current_n)
+    }
+}
+
+impl BranchInfo {
+    /// Margin to the over current alarm threshold
+    pub fn over_current_alarm_margin(&self) -> Margin {
+        Margin::new(self.hardware.rated_line_current as f32, self.settings.over_current_alarm_threshold, self.status.current)
+    }
+
+    /// Margin to the over current warning threshold
+    pub fn over_current_warning_margin(&self) -> Margin {
+        Margin::new(self.hardware.rated_line_current as f32, self.settings.over_current_warning_threshold, self.status.current)
+    }
+
+    /// Margin to the derated line current
+    pub fn safe_margin(&self, factor: f32) -> Margin {
+        Margin::derated(self.hardware.rated_line_current as f32, factor, self.status.current)
+    }
+}
+
+impl ReceptacleInfo {
+    /// Rated current in A AC
+    ///
+    /// Receptacle pages do not list a rating, so it is derived from the
+    /// available current until alarm and the alarm threshold. Returns
+    /// `None` if the alarm threshold is disabled (0%) or the receptacle
+    /// is not measured.
+    pub fn rated_current(&self) -> Option<f32> {
+        let threshold = self.settings.over_current_alarm_threshold;
+        if threshold == 0 {
+            return None;
+        }
+
+        let limit = self.status.current? + self.status.current_available_to_alarm?;
+        // the device reports values with 0.01 A resolution
+        Some((limit * 100.0 / threshold as f32 * 100.0).round() / 100.0)
+    }
+
+    /// Margin to the over current alarm threshold
+    pub fn over_current_alarm_margin(&self) -> Option<Margin> {
+        let rated = self.rated_current()?;
+        Some(Margin::new(rated, self.settings.over_current_alarm_threshold, self.status.current?))
+    }
+
+    /// Margin to the over current warning threshold
+    pub fn over_current_warning_margin(&self) -> Option<Margin> {
+        let rated = self.rated_current()?;
+        Some(Margin::new(rated, self.settings.over_current_warning_threshold, self.status.current?))
+    }
+
+    /// Margin to the derated current, see [`rated_current`](Self::rated_current)
+    /// for when it is not available
+    pub fn safe_margin(&self, factor: f32) -> Option<Margin> {
+        Some(Margin::derated(self.rated_current()?, factor, self.status.current?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::tests::snapshot;
+
+    #[test]
+    fn test_margins() {
+        let mut snapshot = snapshot();
+
+        let pdu = &mut snapshot.info;
+        pdu.status.current_l2 = Some(20.0);
+        let margins = pdu.over_current_alarm_margins().map(Option::unwrap);
+        assert_eq!(margins[0].limit, 25.6);
+        assert_eq!(margins[0].remaining, pdu.status.current_available_to_alarm_l1);
+        assert!((margins[1].remaining - 5.6).abs() < 0.001);
+        assert!((margins[1].remaining_percent - 17.5).abs() < 0.001);
+        assert!(!margins[1].exceeded());
+        assert!(pdu.over_current_warning_margins()[1].unwrap().remaining < margins[1].remaining);
+
+        pdu.status.current_l2 = None;
+        assert!(pdu.over_current_alarm_margins()[1].is_none());
+
+        let branch = &snapshot.branches[0].info;
+        assert_eq!(branch.over_current_alarm_margin().remaining, branch.status.current_available_to_alarm);
+
+        let receptacle = &mut snapshot.receptacles[0].info;
+        assert_eq!(receptacle.rated_current(), Some(10.0));
+        receptacle.status.current = Some(9.6);
+        receptacle.status.current_available_to_alarm = Some(-0.1);
+        assert!(receptacle.over_current_alarm_margin().unwrap().exceeded());
+        receptacle.settings.over_current_alarm_threshold = 0;
+        assert!(receptacle.over_current_alarm_margin().is_none());
+    }
+
+    #[test]
+    fn test_safe_margins() {
+        let mut snapshot = snapshot();
+
+        let pdu = &mut snapshot.info;
+        pdu.hardware.rated_input_current = 32;
+        pdu.status.current_l1 = 20.0;
+        pdu.status.current_l3 = None;
+        let margins = pdu.safe_margins(CONTINUOUS_LOAD_DERATING);
+        assert!((margins[0].unwrap().limit - 25.6).abs() < 0.001);
+        assert!((margins[0].unwrap().remaining - 5.6).abs() < 0.001);
+        assert!(margins[2].is_none());
+        assert!(pdu.safe_margins(0.5)[0].unwrap().exceeded());
+
+        let branch = &mut snapshot.branches[0].info;
+        branch.status.current = 17.0;
+        assert!(branch.safe_margin(CONTINUOUS_LOAD_DERATING).exceeded());
+        assert!(!branch.safe_margin(1.0).exceeded());
+
+        let receptacle = &snapshot.receptacles[0].info;
+        let margin = receptacle.safe_margin(CONTINUOUS_LOAD_DERATING).unwrap();
+        assert!((margin.remaining - (8.0 - receptacle.status.current.unwrap())).abs() < 0.001);
+    }
+}
@@ -0,0 +1,231 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Receptacle metadata store
+//!
+//! Keeps orchestration data (attached host, boot order, dependencies)
+//! in a file next to the device instead of encoding it in receptacle
+//! labels, which are short and easily overwritten on the web UI.
+//!
+//! The file contains one receptacle per line, addressed as
+//! `pdu-branch-receptacle`, followed by `key=value` fields:
+//!
+//! ```text
+//! # address  fields
+//! 1-1-1 host=switch-01 order=0
+//! 1-1-2 host=db-01 order=10 after=switch-01
+//! 1-2-1 host=app-01 order=20 after=db-01,switch-01
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Receptacle address (pdu, branch, receptacle)
+pub type Address = (u8, u8, u8);
+
+#[derive(Clone,Debug,Default,PartialEq)]
+/// Metadata of a single receptacle
+pub struct ReceptacleMetadata {
+    /// Host name of the attached device
+    pub host: String,
+    /// Boot order, lower values are powered on first
+    pub boot_order: Option<u32>,
+    /// Host names which must be powered on before this one
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+/// Dependencies between hosts contain a cycle
+pub struct DependencyCycleError {
+    /// Hosts which could not be ordered
+    pub hosts: Vec<String>,
+}
+
+impl std::fmt::Display for DependencyCycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "dependency cycle between {}", self.hosts.join(", "))
+    }
+}
+
+impl std::error::Error for DependencyCycleError {}
+
+fn invalid(line: usize, msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("line {}: {}", line, msg))
+}
+
+pub(crate) fn parse_address(input: &str) -> Option<Address> {
+    let parts: Vec<&str> = input.split('-').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    Some((parts[0].parse().ok()?, parts[1].parse().ok()?, parts[2].parse().ok()?))
+}
+
+#[derive(Clone,Debug,Default,PartialEq)]
+/// File backed collection of receptacle metadata
+pub struct MetadataStore {
+    path: Option<PathBuf>,
+    entries: BTreeMap<Address, ReceptacleMetadata>,
+}
+
+impl MetadataStore {
+    /// Empty store without backing file
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the store from `path`; a missing file results in an empty store
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut store = match std::fs::File::open(&path) {
+            Ok(file) => Self::from_reader(std::io::BufReader::new(file))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::new(),
+            Err(e) => return Err(e),
+        };
+        store.path = Some(path);
+        Ok(store)
+    }
+
+    /// Parse the store from its text representation
+    pub fn from_reader<R: BufRead>(input: R) -> std::io::Result<Self> {
+        let mut store = Self::new();
+
+        for (no, line) in input.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let address = fields.next().and_then(parse_address).ok_or_else(|| invalid(no + 1, "invalid address"))?;
+            let mut meta = ReceptacleMetadata::default();
+
+            for field in fields {
+                let pos = field.find('=').ok_or_else(|| invalid(no + 1, "expected key=value"))?;
+                let (key, value) = (&field[..pos], &field[pos + 1..]);
+                match key {
+                    "host" => meta.host = value.to_string(),
+                    "order" => meta.boot_order = Some(value.parse().map_err(|_| invalid(no + 1, "invalid order"))?),
+                    "after" => meta.depends_on = value.split(',').filter(|h| !h.is_empty()).map(String::from).collect(),
+                    _ => return Err(invalid(no + 1, &format!("unknown key {}", key))),
+                }
+            }
+
+            store.entries.insert(address, meta);
+        }
+
+        Ok(store)
+    }
+
+    /// Write the text representation of the store
+    pub fn write_to<W: Write>(&self, mut out: W) -> std::io::Result<()> {
+        for ((pdu, branch, receptacle), meta) in self.entries.iter() {
+            write!(out, "{}-{}-{}", pdu, branch, receptacle)?;
+            if !meta.host.is_empty() {
+                write!(out, " host={}", meta.host)?;
+            }
+            if let Some(order) = meta.boot_order {
+                write!(out, " order={}", order)?;
+            }
+            if !meta.depends_on.is_empty() {
+                write!(out, " after={}", meta.depends_on.join(","))?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Write the store back to the file it has been opened from
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = self.path.as_ref().ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no backing file"))?;
+        let tmp = path.with_extension("tmp");
+        self.write_to(std::io::BufWriter::new(std::fs::File::create(&tmp)?))?;
+        std::fs::rename(tmp, path)
+    }
+
+    pub fn get(&self, address: Address) -> Option<&ReceptacleMetadata> {
+        self.entries.get(&address)
+    }
+
+    pub fn set(&mut self, address: Address, meta: ReceptacleMetadata) {
+        self.entries.insert(address, meta);
+    }
+
+    pub fn remove(&mut self, address: Address) -> Option<ReceptacleMetadata> {
+        self.entries.remove(&address)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Address, &ReceptacleMetadata)> {
+        self.entries.iter()
+    }
+
+    /// Receptacles feeding a host
+    pub fn find_host(&self, host: &str) -> Vec<Address> {
+        self.entries.iter().filter(|(_, m)| m.host == host).map(|(a, _)| *a).collect()
+    }
+
+    /// All receptacles in power on order
+    ///
+    /// Dependencies take precedence, remaining ties are resolved by boot
+    /// order (receptacles without order last) and address. Dependencies
+    /// on unknown hosts are ignored. Power off order is the reverse.
+    pub fn startup_order(&self) -> Result<Vec<Address>, DependencyCycleError> {
+        let mut pending: Vec<(&Address, &ReceptacleMetadata)> = self.entries.iter().collect();
+        pending.sort_by_key(|(a, m)| (m.boot_order.is_none(), m.boot_order, **a));
+
+        let mut result = Vec::new();
+
+        while !pending.is_empty() {
+            // all receptacles of the dependencies must be powered on before
+            let ready = pending.iter().position(|(_, m)| {
+                m.depends_on.iter().all(|dep| !pending.iter().any(|(_, p)| &p.host == dep))
+            });
+
+            match ready {
+                Some(i) => result.push(*pending.remove(i).0),
+                None => {
+                    let mut hosts: Vec<String> = pending.iter().map(|(_, m)| m.host.clone()).collect();
+                    hosts.dedup();
+                    return Err(DependencyCycleError { hosts });
+                },
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORE: &str = "# test\n1-2-1 host=app-01 order=20 after=db-01,switch-01\n1-1-2 host=db-01 order=10 after=switch-01\n1-1-1 host=switch-01 order=30\n1-1-3 host=db-01 order=10\n";
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let store = MetadataStore::from_reader(STORE.as_bytes()).unwrap();
+        assert_eq!(store.get((1, 2, 1)).unwrap().depends_on, vec!["db-01", "switch-01"]);
+        assert_eq!(store.find_host("db-01"), vec![(1, 1, 2), (1, 1, 3)]);
+
+        let mut out = Vec::new();
+        store.write_to(&mut out).unwrap();
+        assert_eq!(MetadataStore::from_reader(&out[..]).unwrap(), store);
+
+        assert!(MetadataStore::from_reader("1-1 host=x\n".as_bytes()).is_err());
+        assert!(MetadataStore::from_reader("1-1-1 color=red\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_startup_order() {
+        let mut store = MetadataStore::from_reader(STORE.as_bytes()).unwrap();
+        assert_eq!(store.startup_order().unwrap(), vec![(1, 1, 3), (1, 1, 1), (1, 1, 2), (1, 2, 1)]);
+
+        let mut switch = store.get((1, 1, 1)).unwrap().clone();
+        switch.depends_on = vec!["app-01".to_string()];
+        store.set((1, 1, 1), switch);
+        assert!(store.startup_order().is_err());
+    }
+}
@@ -0,0 +1,339 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Network UPS Tools (NUT) compatibility shim
+//!
+//! Maps PDU data to the standard NUT variable names and serves them
+//! using the NUT network protocol, so existing `upsc`/`upsmon` based
+//! monitoring can poll MPX PDUs like any other NUT device.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use std::sync::Arc;
+//!
+//! fn main() {
+//!     let pdu = Arc::new(liebert::MPX::new("192.168.23.42", "Liebert", "Liebert"));
+//!     let server = Arc::new(liebert::nut::Server::new(pdu, 1, "mpx"));
+//!     async {
+//!         server.run("0.0.0.0:3493").await.unwrap();
+//!     };
+//! }
+//! ```
+
+use crate::{MPX, MPXError, PDUInfo, ReceptacleInfo, ReceptacleList, WiringType};
+use crate::capabilities::ModuleType;
+use crate::transport::{HttpTransport, ReqwestTransport};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// NUT protocol version implemented by the shim
+const NETVER: &str = "1.3";
+
+#[derive(Clone,Debug,Default,PartialEq)]
+/// Set of NUT variables (e.g. "input.L1.current") with their values
+pub struct Variables(BTreeMap<String, String>);
+
+impl Variables {
+    pub fn new() -> Self {
+        Variables(BTreeMap::new())
+    }
+
+    /// Variables describing the PDU input module
+    pub fn from_pdu(info: &PDUInfo) -> Self {
+        let mut vars = Variables::new();
+
+        vars.set("device.mfr", "Liebert");
+        vars.set("device.model", info.hardware.pem_model.to_string());
+        vars.set("device.serial", &info.hardware.serial_number);
+        vars.set("device.type", "pdu");
+        vars.set("input.phases", match info.hardware.wiring_type {
+            WiringType::OnePhase => "1",
+            WiringType::ThreePhase => "3",
+        });
+        vars.set("input.voltage.nominal", info.hardware.rated_input_voltage);
+        vars.set("input.current.nominal", info.hardware.rated_input_current);
+        vars.set("input.frequency.nominal", info.hardware.rated_input_line_frequency);
+        vars.set("input.frequency", info.status.line_frequency);
+        vars.set("input.realpower", info.status.input_power);
+        vars.set("input.L1-N.voltage", info.status.voltage_l1_n);
+        vars.set("input.L1.current", info.status.current_l1);
+        if let (Some(l2), Some(l3)) = (info.status.voltage_l2_n, info.status.voltage_l3_n) {
+            vars.set("input.L2-N.voltage", l2);
+            vars.set("input.L3-N.voltage", l3);
+        }
+        if let (Some(l2), Some(l3)) = (info.status.current_l2, info.status.current_l3) {
+            vars.set("input.L2.current", l2);
+            vars.set("input.L3.current", l3);
+        }
+        vars.set("input.N.current", info.status.current_n);
+
+        vars
+    }
+
+    /// Add one NUT outlet per receptacle, numbered in list order starting at 1
+    pub fn add_receptacles(&mut self, list: &ReceptacleList) {
+        self.set("outlet.count", list.len());
+
+        for (i, receptacle) in list.iter().enumerate() {
+            let outlet = i + 1;
+            self.set(&format!("outlet.{}.id", outlet), outlet);
+            self.set(&format!("outlet.{}.name", outlet), format!("{}-{}-{}", receptacle.pdu, receptacle.branch, receptacle.receptacle));
+            self.set(&format!("outlet.{}.desc", outlet), &receptacle.label);
+            self.set(&format!("outlet.{}.status", outlet), if receptacle.enabled { "on" } else { "off" });
+            self.set(&format!("outlet.{}.switchable", outlet), if receptacle.locked { "no" } else { "yes" });
+        }
+    }
+
+    /// Add measurements for an outlet previously added with `add_receptacles()`
+    pub fn add_receptacle_info(&mut self, outlet: usize, info: &ReceptacleInfo) {
+        // receptacles of elementary and branch monitored modules are not measured
+        for (name, value) in [
+            ("voltage", info.status.voltage),
+            ("current", info.status.current),
+            ("realpower", info.status.power),
+            ("power", info.status.apparent_power),
+            ("powerfactor", info.status.power_factor),
+        ] {
+            if let Some(value) = value {
+                self.set(&format!("outlet.{}.{}", outlet, name), value);
+            }
+        }
+    }
+
+    pub fn set<T: ToString>(&mut self, name: &str, value: T) {
+        self.0.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.0.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.0.iter()
+    }
+}
+
+/// Quote a value as required by the NUT protocol
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Answer a single NUT protocol command line for the device `ups`
+///
+/// `vars` is `None` when the device data could not be fetched, which
+/// is reported to the client as stale data.
+pub fn handle_command(ups: &str, desc: &str, vars: Option<&Variables>, line: &str) -> String {
+    let args: Vec<&str> = line.split_whitespace().collect();
+
+    match args.as_slice() {
+        ["VER"] => format!("Network UPS Tools compatible server (liebert-mpx {})", env!("CARGO_PKG_VERSION")),
+        ["NETVER"] => NETVER.to_string(),
+        ["USERNAME", _] | ["PASSWORD", _] => "OK".to_string(),
+        ["LOGOUT"] => "OK Goodbye".to_string(),
+        ["LIST", "UPS"] => format!("BEGIN LIST UPS\nUPS {} {}\nEND LIST UPS", ups, quote(desc)),
+        ["GET", "UPSDESC", name] if *name == ups => format!("UPSDESC {} {}", ups, quote(desc)),
+        ["LIST", "CMD", name] if *name == ups => format!("BEGIN LIST CMD {}\nEND LIST CMD {}", ups, ups),
+        ["LIST", "VAR", name] if *name == ups => {
+            match vars {
+                Some(vars) => {
+                    let mut result = format!("BEGIN LIST VAR {}\n", ups);
+                    for (key, value) in vars.iter() {
+                        result.push_str(&format!("VAR {} {} {}\n", ups, key, quote(value)));
+                    }
+                    result.push_str(&format!("END LIST VAR {}", ups));
+                    result
+                },
+                None => "ERR DATA-STALE".to_string(),
+            }
+        },
+        ["GET", "VAR", name, var] if *name == ups => {
+            match vars {
+                Some(vars) => match vars.get(var) {
+                    Some(value) => format!("VAR {} {} {}", ups, var, quote(value)),
+                    None => "ERR VAR-NOT-SUPPORTED".to_string(),
+                },
+                None => "ERR DATA-STALE".to_string(),
+            }
+        },
+        ["GET", _, _, ..] | ["LIST", _, _, ..] => "ERR UNKNOWN-UPS".to_string(),
+        _ => "ERR UNKNOWN-COMMAND".to_string(),
+    }
+}
+
+/// NUT protocol server exposing a single PDU
+pub struct Server<H = ReqwestTransport> {
+    mpx: Arc<MPX<H>>,
+    pdu: u8,
+    name: String,
+    max_age: Duration,
+    outlet_readings: bool,
+    cache: tokio::sync::Mutex<Option<(Instant, Variables)>>,
+}
+
+impl<H: HttpTransport + 'static> Server<H> {
+    /// Serve PDU number `pdu` of `mpx` under the NUT device name `name`
+    pub fn new(mpx: Arc<MPX<H>>, pdu: u8, name: &str) -> Self {
+        Server {
+            mpx,
+            pdu,
+            name: name.to_string(),
+            max_age: Duration::from_secs(10),
+            outlet_readings: false,
+            cache: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Maximum age of cached device data before it is fetched again
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Also serve voltage, current and power of the outlets of receptacle
+    /// managed modules (default: off), which needs one request per outlet
+    pub fn outlet_readings(mut self, enabled: bool) -> Self {
+        self.outlet_readings = enabled;
+        self
+    }
+
+    async fn fetch(&self) -> Result<Variables, MPXError> {
+        let mut vars = Variables::from_pdu(&self.mpx.get_info_pdu(self.pdu).await?);
+        let receptacles: ReceptacleList = self.mpx.get_receptacles().await?
+            .into_iter()
+            .filter(|r| r.pdu == self.pdu)
+            .collect();
+        vars.add_receptacles(&receptacles);
+
+        if self.outlet_readings {
+            for (i, r) in receptacles.iter().enumerate() {
+                if self.mpx.module_type((r.pdu, r.branch)).await? != Some(ModuleType::ReceptacleManaged) {
+                    continue;
+                }
+                let info = self.mpx.get_info_receptacle((r.pdu, r.branch, r.receptacle)).await?;
+                vars.add_receptacle_info(i + 1, &info);
+            }
+        }
+
+        Ok(vars)
+    }
+
+    async fn variables(&self) -> Option<Variables> {
+        let mut cache = self.cache.lock().await;
+
+        if let Some((fetched, vars)) = cache.as_ref() {
+            if fetched.elapsed() < self.max_age {
+                return Some(vars.clone());
+            }
+        }
+
+        let vars = self.fetch().await.ok()?;
+        *cache = Some((Instant::now(), vars.clone()));
+        Some(vars)
+    }
+
+    async fn handle_connection(&self, stream: tokio::net::TcpStream) -> std::io::Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let desc = format!("Liebert MPX PDU {}", self.pdu);
+
+        while let Some(line) = lines.next_line().await? {
+            let needs_data = line.starts_with("GET VAR") || line.starts_with("LIST VAR");
+            let vars = if needs_data { self.variables().await } else { None };
+            let response = handle_command(&self.name, &desc, vars.as_ref(), &line);
+
+            writer.write_all(response.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+
+            if line.trim() == "LOGOUT" {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Accept NUT clients on `addr` (usually port 3493) until an error occurs
+    pub async fn run(self: Arc<Self>, addr: &str) -> std::io::Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                let _ = server.handle_connection(stream).await;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_info_tables;
+
+    fn pdu_variables() -> Variables {
+        let html = include_str!("../testdata/pdu-info.htm").to_string();
+        let info = PDUInfo::from_tables(get_info_tables(html).unwrap()).unwrap();
+        Variables::from_pdu(&info)
+    }
+
+    #[test]
+    fn test_variables_from_pdu() {
+        let vars = pdu_variables();
+        assert_eq!(vars.get("device.model").map(|s| s.as_str()), Some("MPXPEM-EHAXXR30"));
+        assert_eq!(vars.get("input.phases").map(|s| s.as_str()), Some("3"));
+        assert!(vars.get("input.L1.current").is_some());
+    }
+
+    #[test]
+    fn test_handle_command() {
+        let vars = pdu_variables();
+        let vars = Some(&vars);
+
+        assert_eq!(handle_command("mpx", "PDU", vars, "GET VAR mpx device.type"), "VAR mpx device.type \"pdu\"");
+        assert_eq!(handle_command("mpx", "PDU", vars, "GET VAR mpx ups.foo"), "ERR VAR-NOT-SUPPORTED");
+        assert_eq!(handle_command("mpx", "PDU", vars, "GET VAR other device.type"), "ERR UNKNOWN-UPS");
+        assert_eq!(handle_command("mpx", "PDU", None, "GET VAR mpx device.type"), "ERR DATA-STALE");
+        assert_eq!(handle_command("mpx", "PDU", vars, "LIST UPS"), "BEGIN LIST UPS\nUPS mpx \"PDU\"\nEND LIST UPS");
+        assert_eq!(handle_command("mpx", "PDU", vars, "FOO"), "ERR UNKNOWN-COMMAND");
+
+        let list = handle_command("mpx", "PDU", vars, "LIST VAR mpx");
+        assert!(list.starts_with("BEGIN LIST VAR mpx\n"));
+        assert!(list.ends_with("END LIST VAR mpx"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch() {
+        use crate::transport::{Request, Response};
+
+        struct Device;
+
+        impl HttpTransport for Device {
+            async fn send(&self, request: Request) -> Result<Response, MPXError> {
+                let body = match request.url.as_str() {
+                    url if url.contains("rpcAps.htm") => include_str!("../testdata/pdu-info.htm").to_string(),
+                    url if url.contains("rpcRem.htm") => include_str!("../testdata/branch-info.htm").to_string(),
+                    url if url.contains("rpcReceptacle.htm") => include_str!("../testdata/receptacle-info.htm").to_string(),
+                    _ => format!("<table id=\"rcpTable\">{}</table>", ["1-1-1", "1-1-2", "2-1-1"].iter().map(|id| format!(concat!(
+                        "<tr id=\"{}\"><td><a href=\"#\"><nobr>r</nobr></a></td><td></td>",
+                        "<td><span title=\"On\"></span></td><td><span title=\"Unlocked\"></span></td>",
+                        "<td><img src=\"../../../images/accept.png\"></td></tr>"), id)).collect::<String>()),
+                };
+                Ok(Response { status: 200, url: request.url, headers: Vec::new(), body })
+            }
+        }
+
+        let mpx = Arc::new(MPX::builder("pdu1").build_with_transport(Device));
+        let vars = Server::new(mpx.clone(), 1, "mpx").fetch().await.unwrap();
+        assert_eq!(vars.get("outlet.count").map(|s| s.as_str()), Some("2"));
+        assert_eq!(vars.get("outlet.2.name").map(|s| s.as_str()), Some("1-1-2"));
+        assert!(vars.get("outlet.1.current").is_none());
+
+        let vars = Server::new(mpx, 1, "mpx").outlet_readings(true).fetch().await.unwrap();
+        assert!(vars.get("outlet.1.current").is_some());
+        assert!(vars.get("outlet.2.voltage").is_some());
+    }
+}
@@ -0,0 +1,308 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Partial parsing of information pages
+//!
+//! [`MPX::get_info_pdu`](crate::MPX::get_info_pdu) and friends fail if
+//! any field of a page cannot be parsed. The partial variants return
+//! every table section (status, events, settings, hardware) which could
+//! be parsed, together with a [`ParseWarning`] for each section which
+//! could not. The status variants only parse the status table and
+//! ignore the other sections and areas of the page entirely, e.g. for
+//! frequent polling of measurements.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         let info = pdu.get_info_pdu_partial(1).await.unwrap();
+//!         if let Some(status) = info.status {
+//!             println!("input power: {} W", status.input_power);
+//!         }
+//!         for warning in info.warnings {
+//!             println!("{}", warning);
+//!         }
+//!     };
+//! }
+//! ```
+
+use crate::{get_area_table, get_info_tables, MPXError};
+use crate::{BranchEvents, BranchHardware, BranchSettings, BranchStatus};
+use crate::{PDUEvents, PDUHardware, PDUSettings, PDUStatus};
+use crate::{ReceptacleEvents, ReceptacleHardware, ReceptacleSettings, ReceptacleStatus};
+#[cfg(feature = "client")]
+use crate::{BranchAddress, BranchId, MPX, PduId, ReceptacleAddress, ReceptacleId};
+#[cfg(feature = "client")]
+use crate::transport::HttpTransport;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// A table section which could not be parsed
+pub struct ParseWarning {
+    /// Table section (`status`, `events`, `settings`, `hardware`)
+    pub table: String,
+    /// First missing or malformed key, if known
+    pub key: Option<String>,
+    /// Error message
+    pub message: String,
+}
+
+impl ParseWarning {
+    fn new(table: &str, e: &MPXError) -> Self {
+        let key = match e {
+            MPXError::MissingDataError(e) => e.key.clone(),
+            MPXError::InvalidDataError(e) => e.key.clone(),
+            _ => None,
+        };
+
+        ParseWarning {
+            table: table.to_string(),
+            key,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Keep a parsed section, or record a warning for it
+fn section<T>(result: Result<T, MPXError>, table: &str, warnings: &mut Vec<ParseWarning>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            let e = e.in_table(table);
+            warnings.push(ParseWarning::new(table, &e));
+            None
+        },
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// PDU input module information with the parseable sections only
+pub struct PartialPDUInfo {
+    pub status: Option<PDUStatus>,
+    pub events: Option<PDUEvents>,
+    pub settings: Option<PDUSettings>,
+    pub hardware: Option<PDUHardware>,
+    pub warnings: Vec<ParseWarning>,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Branch module information with the parseable sections only
+pub struct PartialBranchInfo {
+    pub status: Option<BranchStatus>,
+    pub events: Option<BranchEvents>,
+    pub settings: Option<BranchSettings>,
+    pub hardware: Option<BranchHardware>,
+    pub warnings: Vec<ParseWarning>,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Receptacle information with the parseable sections only
+pub struct PartialReceptacleInfo {
+    pub status: Option<ReceptacleStatus>,
+    pub events: Option<ReceptacleEvents>,
+    pub settings: Option<ReceptacleSettings>,
+    pub hardware: Option<ReceptacleHardware>,
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// Parse the PDU input module information page (`rpcAps.htm`),
+/// skipping sections with unparseable fields
+///
+/// Only a page without the expected table layout is an error.
+pub fn parse_pdu_info_partial(html: String) -> Result<PartialPDUInfo, MPXError> {
+    let tables = get_info_tables(html)?;
+    let mut warnings = Vec::new();
+
+    Ok(PartialPDUInfo {
+        status: section(PDUStatus::from_table(tables.status), "status", &mut warnings),
+        events: section(PDUEvents::from_table(tables.events), "events", &mut warnings),
+        settings: section(PDUSettings::from_table(tables.settings), "settings", &mut warnings),
+        hardware: section(PDUHardware::from_table(tables.hardware), "hardware", &mut warnings),
+        warnings,
+    })
+}
+
+/// Parse the branch module information page (`rpcRem.htm`),
+/// skipping sections with unparseable fields
+pub fn parse_branch_info_partial(html: String) -> Result<PartialBranchInfo, MPXError> {
+    let tables = get_info_tables(html)?;
+    let mut warnings = Vec::new();
+
+    Ok(PartialBranchInfo {
+        status: section(BranchStatus::from_table(tables.status), "status", &mut warnings),
+        events: section(BranchEvents::from_table(tables.events), "events", &mut warnings),
+        settings: section(BranchSettings::from_table(tables.settings), "settings", &mut warnings),
+        hardware: section(BranchHardware::from_table(tables.hardware), "hardware", &mut warnings),
+        warnings,
+    })
+}
+
+/// Parse the receptacle information page (`rpcReceptacle.htm`),
+/// skipping sections with unparseable fields
+pub fn parse_receptacle_info_partial(html: String) -> Result<PartialReceptacleInfo, MPXError> {
+    let tables = get_info_tables(html)?;
+    let measurements = tables.require_measurements();
+    let mut warnings = Vec::new();
+
+    Ok(PartialReceptacleInfo {
+        status: section(measurements.and(ReceptacleStatus::from_table(tables.status)), "status", &mut warnings),
+        events: section(ReceptacleEvents::from_table(tables.events), "events", &mut warnings),
+        settings: section(ReceptacleSettings::from_table(tables.settings), "settings", &mut warnings),
+        hardware: section(ReceptacleHardware::from_table(tables.hardware), "hardware", &mut warnings),
+        warnings,
+    })
+}
+
+/// Parse only the status table of the PDU input module information page
+pub fn parse_pdu_status(html: String) -> Result<PDUStatus, MPXError> {
+    PDUStatus::from_table(get_area_table(html, "RpcStatusArea")?).map_err(|e| e.in_table("status"))
+}
+
+/// Parse only the status table of the branch module information page
+pub fn parse_branch_status(html: String) -> Result<BranchStatus, MPXError> {
+    BranchStatus::from_table(get_area_table(html, "RpcStatusArea")?).map_err(|e| e.in_table("status"))
+}
+
+/// Parse only the status table of the receptacle information page
+///
+/// Without the hardware section the capability is unknown, missing
+/// measurements are therefore `None` instead of an error.
+pub fn parse_receptacle_status(html: String) -> Result<ReceptacleStatus, MPXError> {
+    ReceptacleStatus::from_table(get_area_table(html, "RpcStatusArea")?).map_err(|e| e.in_table("status"))
+}
+
+#[cfg(feature = "client")]
+impl<H: HttpTransport> MPX<H> {
+    /// Like [`MPX::get_info_pdu`], but keeps all parseable sections;
+    /// the serial number is verified if the hardware section is available
+    pub async fn get_info_pdu_partial(&self, pdu: impl Into<PduId>) -> Result<PartialPDUInfo, MPXError> {
+        let PduId(pdu) = pdu.into();
+        self.check_address(pdu, None, None)?;
+        let url = self.url(&format!("/dp/std:{}.0.0_0.0.0/rpc/rpcAps.htm", pdu));
+        let result = async {
+            let info = self.parse_page(&url, self.get_page(url.clone()).await?, parse_pdu_info_partial)?;
+            if let Some(hardware) = &info.hardware {
+                self.check_identity(pdu, &hardware.serial_number)?;
+            }
+            Ok(info)
+        }.await;
+        self.log_fetch("PDU info", &format!("{}", pdu), &result);
+        result
+    }
+
+    /// Like [`MPX::get_info_branch`], but keeps all parseable sections
+    pub async fn get_info_branch_partial(&self, address: impl Into<BranchAddress>) -> Result<PartialBranchInfo, MPXError> {
+        let BranchAddress { pdu: PduId(pdu), branch: BranchId(branch) } = address.into();
+        self.check_address(pdu, Some(branch), None)?;
+        let url = self.url(&format!("/dp/std:{}.{}.0_0.0.0/rpc/rpcRem.htm", pdu, branch));
+        let result = async {
+            let info = self.parse_page(&url, self.get_page(url.clone()).await?, parse_branch_info_partial)?;
+            if let Some(hardware) = &info.hardware {
+                self.remember_module(pdu, branch, &hardware.brm_model);
+            }
+            Ok(info)
+        }.await;
+        self.log_fetch("branch info", &format!("{}-{}", pdu, branch), &result);
+        result
+    }
+
+    /// Like [`MPX::get_info_receptacle`], but keeps all parseable sections
+    pub async fn get_info_receptacle_partial(&self, address: impl Into<ReceptacleAddress>) -> Result<PartialReceptacleInfo, MPXError> {
+        let ReceptacleAddress { pdu: PduId(pdu), branch: BranchId(branch), receptacle: ReceptacleId(receptacle) } = address.into();
+        self.check_address(pdu, Some(branch), Some(receptacle))?;
+        let url = self.url(&format!("/dp/std:{}.{}.{}_0.0.0/rpc/rpcReceptacle.htm", pdu, branch, receptacle));
+        let result = async {
+            let info = self.parse_page(&url, self.get_page(url.clone()).await?, parse_receptacle_info_partial)?;
+            if let Some(hardware) = &info.hardware {
+                self.remember_capability(pdu, branch, receptacle, &hardware.capabilities);
+            }
+            Ok(info)
+        }.await;
+        self.log_fetch("receptacle info", &format!("{}-{}-{}", pdu, branch, receptacle), &result);
+        result
+    }
+
+    /// Fetch only the status of the PDU input module
+    pub async fn get_status_pdu(&self, pdu: impl Into<PduId>) -> Result<PDUStatus, MPXError> {
+        let PduId(pdu) = pdu.into();
+        self.check_address(pdu, None, None)?;
+        let url = self.url(&format!("/dp/std:{}.0.0_0.0.0/rpc/rpcAps.htm", pdu));
+        let result = async { self.parse_page(&url, self.get_page(url.clone()).await?, parse_pdu_status) }.await;
+        self.log_fetch("PDU status", &format!("{}", pdu), &result);
+        result
+    }
+
+    /// Fetch only the status of a branch module
+    pub async fn get_status_branch(&self, address: impl Into<BranchAddress>) -> Result<BranchStatus, MPXError> {
+        let BranchAddress { pdu: PduId(pdu), branch: BranchId(branch) } = address.into();
+        self.check_address(pdu, Some(branch), None)?;
+        let url = self.url(&format!("/dp/std:{}.{}.0_0.0.0/rpc/rpcRem.htm", pdu, branch));
+        let result = async { self.parse_page(&url, self.get_page(url.clone()).await?, parse_branch_status) }.await;
+        self.log_fetch("branch status", &format!("{}-{}", pdu, branch), &result);
+        result
+    }
+
+    /// Fetch only the status of a receptacle
+    pub async fn get_status_receptacle(&self, address: impl Into<ReceptacleAddress>) -> Result<ReceptacleStatus, MPXError> {
+        let ReceptacleAddress { pdu: PduId(pdu), branch: BranchId(branch), receptacle: ReceptacleId(receptacle) } = address.into();
+        self.check_address(pdu, Some(branch), Some(receptacle))?;
+        let url = self.url(&format!("/dp/std:{}.{}.{}_0.0.0/rpc/rpcReceptacle.htm", pdu, branch, receptacle));
+        let result = async { self.parse_page(&url, self.get_page(url.clone()).await?, parse_receptacle_status) }.await;
+        self.log_fetch("receptacle status", &format!("{}-{}-{}", pdu, branch, receptacle), &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_receptacle_info() {
+        let html = include_str!("../testdata/receptacle-info.htm").to_string();
+        let info = parse_receptacle_info_partial(html).unwrap();
+        assert!(info.status.is_some() && info.events.is_some() && info.settings.is_some() && info.hardware.is_some());
+        assert!(info.warnings.is_empty());
+
+        let html = include_str!("../testdata/receptacle-info.htm").replace("Receptacle Power Factor", "Receptacle Power Fctr");
+        let info = parse_receptacle_info_partial(html).unwrap();
+        assert!(info.status.is_none());
+        assert!(info.hardware.is_some());
+        assert_eq!(info.warnings, vec![ParseWarning {
+            table: "status".to_string(),
+            key: Some("Receptacle Power Factor".to_string()),
+            message: "required data missing in PDU response (key 'Receptacle Power Factor') in status table".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_status_only() {
+        let html = include_str!("../testdata/receptacle-info.htm").to_string();
+        let status = parse_receptacle_status(html.clone()).unwrap();
+        assert_eq!(Some(status), parse_receptacle_info_partial(html.clone()).unwrap().status);
+
+        // problems outside of the status table are ignored
+        let broken = html.replace("RpcSettingArea", "RpcSettings").replace("Receptacle Over Current", "Receptacle Overcurrent");
+        assert!(parse_receptacle_status(broken).is_ok());
+
+        let html = include_str!("../testdata/pdu-info.htm").replace("RpcInfoArea", "RpcInfo");
+        assert!(parse_pdu_status(html).is_ok());
+        let html = include_str!("../testdata/branch-info.htm").replace(">Branch Voltage<", ">Branch Volts<");
+        assert_eq!(parse_branch_status(html).unwrap_err().to_string(),
+            "required data missing in PDU response (key 'Branch Voltage') in status table");
+    }
+}
@@ -0,0 +1,99 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Request rate limiting
+//!
+//! The embedded web server of the MPX card gets unstable with more than
+//! a few requests per second. A [`RateLimit`] set via
+//! [`MPXBuilder::rate_limit`](crate::MPXBuilder::rate_limit) spaces out
+//! all HTTP requests of a [`MPX`] handle (including logins, token
+//! fetches and retries) and bounds the number of concurrent requests.
+
+use crate::MPX;
+use crate::transport::HttpTransport;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+/// Rate limit configuration
+pub struct RateLimit {
+    /// Maximum number of requests started per second
+    pub requests_per_second: f32,
+    /// Maximum number of concurrent requests
+    pub max_in_flight: usize,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit {
+            requests_per_second: 2.0,
+            max_in_flight: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Rate limiter state of a [`MPX`]
+pub(crate) struct Limiter {
+    interval: Duration,
+    next: Mutex<Option<Instant>>,
+    in_flight: Semaphore,
+}
+
+impl Limiter {
+    pub(crate) fn new(limit: &RateLimit) -> Self {
+        Limiter {
+            interval: Duration::from_secs_f32(1.0 / limit.requests_per_second.max(0.001)),
+            next: Mutex::new(None),
+            in_flight: Semaphore::new(limit.max_in_flight.max(1)),
+        }
+    }
+
+    /// Reserve the next start slot, returning when it is due
+    fn reserve(&self, now: Instant) -> Instant {
+        let mut next = self.next.lock().unwrap();
+        let slot = match *next {
+            Some(next) if next > now => next,
+            _ => now,
+        };
+        *next = Some(slot + self.interval);
+        slot
+    }
+
+    async fn acquire(&self) -> SemaphorePermit<'_> {
+        // the semaphore is never closed
+        let permit = self.in_flight.acquire().await.expect("rate limiter semaphore closed");
+        tokio::time::sleep_until(self.reserve(Instant::now())).await;
+        permit
+    }
+}
+
+impl<H: HttpTransport> MPX<H> {
+    /// Wait until the rate limit allows another request; the returned
+    /// permit must be held until the request finished
+    pub(crate) async fn throttle(&self) -> Option<SemaphorePermit<'_>> {
+        match &self.inner.limiter {
+            Some(limiter) => Some(limiter.acquire().await),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve() {
+        let limiter = Limiter::new(&RateLimit { requests_per_second: 4.0, max_in_flight: 1 });
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(now), now);
+        assert_eq!(limiter.reserve(now), now + Duration::from_millis(250));
+        assert_eq!(limiter.reserve(now + Duration::from_millis(100)), now + Duration::from_millis(500));
+        let later = now + Duration::from_secs(5);
+        assert_eq!(limiter.reserve(later), later);
+    }
+}
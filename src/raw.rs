@@ -0,0 +1,123 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Untyped access to the information page tables
+//!
+//! The typed structs only contain rows known to this crate. Rows added
+//! by newer firmware can be read from the raw tables, which map each
+//! (English, see [`labels`](crate::labels)) row label to its value and
+//! unit as shown by the PDU:
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         let raw = pdu.get_raw_info_pdu(1).await.unwrap();
+//!         if let Some(row) = raw.status.get("PDU Total Input Power") {
+//!             println!("{} {}", row.value, row.unit);
+//!         }
+//!     };
+//! }
+//! ```
+
+use crate::{get_info_tables, MPXError, RawDataTable};
+#[cfg(feature = "client")]
+use crate::{BranchAddress, BranchId, MPX, PduId, ReceptacleAddress, ReceptacleId};
+#[cfg(feature = "client")]
+use crate::transport::HttpTransport;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Value of a table row as shown by the PDU
+pub struct RawValue {
+    /// Value text (e.g. `23.42`); for event rows the status icon path
+    pub value: String,
+    /// Unit text (e.g. `kWH`, `VAC`), empty for rows without unit
+    pub unit: String,
+}
+
+/// Rows of a table section by label
+pub type RawTable = BTreeMap<String, RawValue>;
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// All rows of an information page
+pub struct RawInfo {
+    pub status: RawTable,
+    pub events: RawTable,
+    pub settings: RawTable,
+    pub hardware: RawTable,
+}
+
+fn raw_table(table: RawDataTable) -> RawTable {
+    table.into_iter().map(|(key, v)| (key, RawValue { value: v.value, unit: v.unit })).collect()
+}
+
+/// Parse the tables of a PDU, branch or receptacle information page
+/// without interpreting them
+pub fn parse_raw_info(html: String) -> Result<RawInfo, MPXError> {
+    let tables = get_info_tables(html)?;
+
+    Ok(RawInfo {
+        status: raw_table(tables.status),
+        events: raw_table(tables.events),
+        settings: raw_table(tables.settings),
+        hardware: raw_table(tables.hardware),
+    })
+}
+
+#[cfg(feature = "client")]
+impl<H: HttpTransport> MPX<H> {
+    /// Fetch the untyped tables of the PDU input module information page
+    pub async fn get_raw_info_pdu(&self, pdu: impl Into<PduId>) -> Result<RawInfo, MPXError> {
+        let PduId(pdu) = pdu.into();
+        self.check_address(pdu, None, None)?;
+        let url = self.url(&format!("/dp/std:{}.0.0_0.0.0/rpc/rpcAps.htm", pdu));
+        let result = async { self.parse_page(&url, self.get_page(url.clone()).await?, parse_raw_info) }.await;
+        self.log_fetch("raw PDU info", &format!("{}", pdu), &result);
+        result
+    }
+
+    /// Fetch the untyped tables of the branch module information page
+    pub async fn get_raw_info_branch(&self, address: impl Into<BranchAddress>) -> Result<RawInfo, MPXError> {
+        let BranchAddress { pdu: PduId(pdu), branch: BranchId(branch) } = address.into();
+        self.check_address(pdu, Some(branch), None)?;
+        let url = self.url(&format!("/dp/std:{}.{}.0_0.0.0/rpc/rpcRem.htm", pdu, branch));
+        let result = async { self.parse_page(&url, self.get_page(url.clone()).await?, parse_raw_info) }.await;
+        self.log_fetch("raw branch info", &format!("{}-{}", pdu, branch), &result);
+        result
+    }
+
+    /// Fetch the untyped tables of the receptacle information page
+    pub async fn get_raw_info_receptacle(&self, address: impl Into<ReceptacleAddress>) -> Result<RawInfo, MPXError> {
+        let ReceptacleAddress { pdu: PduId(pdu), branch: BranchId(branch), receptacle: ReceptacleId(receptacle) } = address.into();
+        self.check_address(pdu, Some(branch), Some(receptacle))?;
+        let url = self.url(&format!("/dp/std:{}.{}.{}_0.0.0/rpc/rpcReceptacle.htm", pdu, branch, receptacle));
+        let result = async { self.parse_page(&url, self.get_page(url.clone()).await?, parse_raw_info) }.await;
+        self.log_fetch("raw receptacle info", &format!("{}-{}-{}", pdu, branch, receptacle), &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_raw_info() {
+        let html = include_str!("../testdata/branch-info.htm").to_string();
+        let raw = parse_raw_info(html.clone()).unwrap();
+        assert_eq!(raw.status.get("Branch Voltage"), Some(&RawValue { value: "236.3".to_string(), unit: "VAC".to_string() }));
+        assert!(raw.events.contains_key("Branch Breaker Open"));
+
+        // rows unknown to the typed structs are kept
+        let html = html.replace(">Branch Power Factor<", ">Branch Line Frequency<");
+        assert!(parse_raw_info(html).unwrap().status.contains_key("Branch Line Frequency"));
+    }
+}
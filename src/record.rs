@@ -0,0 +1,109 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Recording and replaying pages
+//!
+//! With [`MPXBuilder::record_pages`](crate::MPXBuilder::record_pages)
+//! every successfully fetched page is stored in a directory, one file
+//! per endpoint (e.g. `dp_std_1.0.0_0.0.0_rpc_rpcAps.htm`). Such a
+//! capture is the easiest way to provide test data for a firmware
+//! version or model this library fails on, and can be served again
+//! with [`ReplayTransport`].
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::record::ReplayTransport;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::builder("192.168.23.42")
+//!         .credentials("Liebert", "Liebert")
+//!         .record_pages("capture")
+//!         .build()
+//!         .unwrap();
+//!
+//!     // later, without the device
+//!     let replay = liebert::MPX::builder("192.168.23.42").build_with_transport(ReplayTransport::new("capture"));
+//!     async {
+//!         let events = replay.get_events().await.unwrap();
+//!     };
+//! }
+//! ```
+
+use crate::{MPX, MPXError};
+use crate::diagnostics::page_name;
+use crate::transport::{HttpTransport, Method, Request, Response};
+use std::path::PathBuf;
+
+impl<H> MPX<H> {
+    /// Store a fetched page if recording is enabled
+    pub(crate) fn record_page(&self, url: &str, body: &str) {
+        let dir = match &self.inner.record_dir {
+            Some(dir) => dir,
+            None => return,
+        };
+
+        let path = dir.join(page_name(url));
+        if let Err(_e) = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&path, body)) {
+            #[cfg(feature = "log")]
+            log::warn!(target: "liebert_mpx", device = self.inner.host.as_str(), error:% = _e; "could not record page {}: {}", path.display(), _e);
+        }
+    }
+}
+
+#[derive(Clone,Debug)]
+/// Transport answering GET requests with pages recorded by
+/// [`MPXBuilder::record_pages`](crate::MPXBuilder::record_pages)
+///
+/// Pages missing from the capture are answered with status 404, POSTs
+/// are accepted without effect.
+pub struct ReplayTransport {
+    dir: PathBuf,
+}
+
+impl ReplayTransport {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        ReplayTransport { dir: dir.into() }
+    }
+}
+
+impl HttpTransport for ReplayTransport {
+    async fn send(&self, request: Request) -> Result<Response, MPXError> {
+        let (status, body) = match request.method {
+            Method::Get => match std::fs::read_to_string(self.dir.join(page_name(&request.url))) {
+                Ok(body) => (200, body),
+                Err(_) => (404, String::new()),
+            },
+            Method::Post => (200, String::new()),
+        };
+
+        Ok(Response { status, url: request.url, headers: Vec::new(), body })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Page(&'static str);
+
+    impl HttpTransport for Page {
+        async fn send(&self, request: Request) -> Result<Response, MPXError> {
+            Ok(Response { status: 200, url: request.url, headers: Vec::new(), body: self.0.to_string() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("liebert-mpx-record-{}", std::process::id()));
+        let mpx = MPX::builder("pdu1").record_pages(&dir).build_with_transport(Page(include_str!("../testdata/events-test.htm")));
+        let events = mpx.get_events().await.unwrap();
+        assert!(dir.join("rpc_rpcActiveAlarms.htm").exists());
+
+        let replay = MPX::builder("pdu1").build_with_transport(ReplayTransport::new(&dir));
+        assert_eq!(replay.get_events().await.unwrap(), events);
+        assert!(replay.get_info_pdu(1).await.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
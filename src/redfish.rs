@@ -0,0 +1,435 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Redfish-style REST facade
+//!
+//! Exposes the PDU as Redfish `PowerEquipment/RackPDUs` resources backed
+//! by the web interface scraping API, so DCIM tools which only speak
+//! Redfish can monitor and switch the receptacles.
+//!
+//! The facade acts with the credentials of the `MPX` handle, so it is
+//! read-only unless client credentials are configured. With credentials
+//! every request must authenticate via HTTP basic authentication and
+//! `Outlet.PowerControl` is served:
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::credentials::Credentials;
+//! use std::sync::Arc;
+//!
+//! fn main() {
+//!     let pdu = Arc::new(liebert::MPX::new("192.168.23.42", "Liebert", "Liebert"));
+//!     let auth = Credentials::new("dcim", "secret");
+//!     async {
+//!         liebert::redfish::serve(pdu, "127.0.0.1:8080", Some(auth)).await.unwrap();
+//!     };
+//! }
+//! ```
+
+use crate::{BranchInfo, EventLevel, MPX, MPXError, PDUInfo, ReceptacleInfo, ReceptacleListEntry};
+use crate::auth::base64;
+use crate::credentials::Credentials;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const RACK_PDUS: &str = "/redfish/v1/PowerEquipment/RackPDUs";
+
+/// Redfish health of the worst given event level
+fn health(levels: &[EventLevel]) -> &'static str {
+    if levels.contains(&EventLevel::ALARM) {
+        "Critical"
+    } else if levels.contains(&EventLevel::WARNING) {
+        "Warning"
+    } else {
+        "OK"
+    }
+}
+
+fn reading(value: f32) -> Value {
+    json!({ "Reading": value })
+}
+
+fn collection(odata_type: &str, name: &str, base: &str, ids: &[String]) -> Value {
+    let members: Vec<Value> = ids.iter()
+        .map(|id| json!({ "@odata.id": format!("{}/{}", base, id) }))
+        .collect();
+
+    json!({
+        "@odata.id": base,
+        "@odata.type": odata_type,
+        "Name": name,
+        "Members@odata.count": members.len(),
+        "Members": members,
+    })
+}
+
+/// Redfish service root
+pub fn service_root() -> Value {
+    json!({
+        "@odata.id": "/redfish/v1",
+        "@odata.type": "#ServiceRoot.v1_15_0.ServiceRoot",
+        "Id": "RootService",
+        "Name": "Liebert MPX Redfish Facade",
+        "RedfishVersion": "1.17.0",
+        "PowerEquipment": { "@odata.id": "/redfish/v1/PowerEquipment" },
+    })
+}
+
+/// Redfish power equipment resource
+pub fn power_equipment() -> Value {
+    json!({
+        "@odata.id": "/redfish/v1/PowerEquipment",
+        "@odata.type": "#PowerEquipment.v1_2_0.PowerEquipment",
+        "Id": "PowerEquipment",
+        "Name": "Power Equipment",
+        "RackPDUs": { "@odata.id": RACK_PDUS },
+    })
+}
+
+/// Rack PDU collection for the given PDU numbers
+pub fn rack_pdus(pdus: &[u8]) -> Value {
+    let ids: Vec<String> = pdus.iter().map(|p| p.to_string()).collect();
+    collection("#PowerDistributionCollection.PowerDistributionCollection", "Rack PDUs", RACK_PDUS, &ids)
+}
+
+/// Rack PDU resource for a PDU input module
+pub fn rack_pdu(pdu: u8, info: &PDUInfo) -> Value {
+    let base = format!("{}/{}", RACK_PDUS, pdu);
+    let events = &info.events;
+    let levels: Vec<EventLevel> = [
+        Some(events.low_voltage_l1), events.low_voltage_l2, events.low_voltage_l3,
+        Some(events.over_current_l1), events.over_current_l2, events.over_current_l3,
+        Some(events.low_current_l1), events.low_current_l2, events.low_current_l3,
+        Some(events.failure), Some(events.communication_fail), Some(events.over_current_n),
+    ].iter().flatten().copied().collect();
+    let worst = health(&levels);
+
+    json!({
+        "@odata.id": base,
+        "@odata.type": "#PowerDistribution.v1_3_0.PowerDistribution",
+        "Id": pdu.to_string(),
+        "Name": info.settings.label,
+        "EquipmentType": "RackPDU",
+        "Manufacturer": "Liebert",
+        "Model": info.hardware.pem_model.to_string(),
+        "SerialNumber": info.hardware.serial_number,
+        "FirmwareVersion": info.hardware.fw_version.to_string(),
+        "AssetTag": info.settings.asset_tag_1,
+        "Status": { "State": "Enabled", "Health": worst },
+        "Branches": { "@odata.id": format!("{}/Branches", base) },
+        "Outlets": { "@odata.id": format!("{}/Outlets", base) },
+        "Oem": {
+            "Liebert": {
+                "InputPowerWatts": reading(info.status.input_power),
+                "EnergykWh": reading(info.status.accumulated_energy),
+                "FrequencyHz": reading(info.status.line_frequency),
+                "PolyPhaseVoltage": {
+                    "Line1ToNeutral": reading(info.status.voltage_l1_n),
+                    "Line2ToNeutral": info.status.voltage_l2_n.map(reading),
+                    "Line3ToNeutral": info.status.voltage_l3_n.map(reading),
+                },
+                "PolyPhaseCurrentAmps": {
+                    "Line1": reading(info.status.current_l1),
+                    "Line2": info.status.current_l2.map(reading),
+                    "Line3": info.status.current_l3.map(reading),
+                    "Neutral": reading(info.status.current_n),
+                },
+            },
+        },
+    })
+}
+
+/// Circuit collection for the given branch numbers
+pub fn branches(pdu: u8, branches: &[u8]) -> Value {
+    let ids: Vec<String> = branches.iter().map(|b| b.to_string()).collect();
+    collection("#CircuitCollection.CircuitCollection", "Branches", &format!("{}/{}/Branches", RACK_PDUS, pdu), &ids)
+}
+
+/// Circuit resource for a branch module
+pub fn branch(pdu: u8, branch: u8, info: &BranchInfo) -> Value {
+    let events = &info.events;
+    let breaker = if events.breaker_open == EventLevel::OK { "Normal" } else { "Tripped" };
+
+    json!({
+        "@odata.id": format!("{}/{}/Branches/{}", RACK_PDUS, pdu, branch),
+        "@odata.type": "#Circuit.v1_7_0.Circuit",
+        "Id": branch.to_string(),
+        "Name": info.settings.label,
+        "CircuitType": "Branch",
+        "Model": info.hardware.brm_model.to_string(),
+        "SerialNumber": info.hardware.serial_number,
+        "FirmwareVersion": info.hardware.fw_version.to_string(),
+        "NominalVoltage": format!("AC{}V", info.hardware.rated_line_voltage),
+        "RatedCurrentAmps": info.hardware.rated_line_current,
+        "BreakerState": breaker,
+        "Status": {
+            "State": "Enabled",
+            "Health": health(&[events.over_current, events.low_current, events.low_voltage, events.failure]),
+        },
+        "Voltage": reading(info.status.voltage),
+        "CurrentAmps": reading(info.status.current),
+        "PowerWatts": {
+            "Reading": info.status.power,
+            "ApparentVA": info.status.apparent_power,
+            "PowerFactor": info.status.power_factor,
+        },
+        "EnergykWh": reading(info.status.accumulated_energy),
+    })
+}
+
+/// Outlet identifier used in resource paths ("<branch>-<receptacle>")
+fn outlet_id(branch: u8, receptacle: u8) -> String {
+    format!("{}-{}", branch, receptacle)
+}
+
+fn parse_outlet_id(id: &str) -> Option<(u8, u8)> {
+    let mut parts = id.splitn(2, '-');
+    let branch = parts.next()?.parse::<u8>().ok()?;
+    let receptacle = parts.next()?.parse::<u8>().ok()?;
+    Some((branch, receptacle))
+}
+
+/// Outlet collection for the given receptacles
+pub fn outlets(pdu: u8, receptacles: &[ReceptacleListEntry]) -> Value {
+    let ids: Vec<String> = receptacles.iter().map(|r| outlet_id(r.branch, r.receptacle)).collect();
+    collection("#OutletCollection.OutletCollection", "Outlets", &format!("{}/{}/Outlets", RACK_PDUS, pdu), &ids)
+}
+
+/// Outlet resource for a receptacle
+pub fn outlet(pdu: u8, branch: u8, receptacle: u8, info: &ReceptacleInfo) -> Value {
+    let base = format!("{}/{}/Outlets/{}", RACK_PDUS, pdu, outlet_id(branch, receptacle));
+
+    json!({
+        "@odata.id": base,
+        "@odata.type": "#Outlet.v1_4_0.Outlet",
+        "Id": outlet_id(branch, receptacle),
+        "Name": info.settings.label,
+        "OutletType": info.hardware.receptacle_type.to_string(),
+        "PhaseWiringType": "OnePhase3Wire",
+        "PowerState": if info.settings.power_state { "On" } else { "Off" },
+        "PowerOnDelaySeconds": info.settings.power_on_delay.as_secs(),
+        "Status": {
+            "State": "Enabled",
+            "Health": health(&[info.events.over_current, info.events.low_current]),
+        },
+        "Voltage": info.status.voltage.map(reading),
+        "CurrentAmps": info.status.current.map(reading),
+        "PowerWatts": {
+            "Reading": info.status.power,
+            "ApparentVA": info.status.apparent_power,
+            "PowerFactor": info.status.power_factor,
+        },
+        "EnergykWh": info.status.accumulated_energy.map(reading),
+        "Actions": {
+            "#Outlet.PowerControl": {
+                "target": format!("{}/Actions/Outlet.PowerControl", base),
+                "PowerState@Redfish.AllowableValues": ["On", "Off", "PowerCycle"],
+            },
+        },
+    })
+}
+
+/// Error response in Redfish format
+struct RedfishError(StatusCode, String);
+
+impl From<MPXError> for RedfishError {
+    fn from(e: MPXError) -> Self {
+        match e {
+            MPXError::InvalidAddress(_) => RedfishError(StatusCode::NOT_FOUND, format!("{}", e)),
+            e => RedfishError(StatusCode::BAD_GATEWAY, format!("{}", e)),
+        }
+    }
+}
+
+impl IntoResponse for RedfishError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": {
+                "code": "Base.1.0.GeneralError",
+                "message": self.1,
+            },
+        });
+        (self.0, Json(body)).into_response()
+    }
+}
+
+fn not_found() -> RedfishError {
+    RedfishError(StatusCode::NOT_FOUND, "resource not found".to_string())
+}
+
+type ApiResult = Result<Json<Value>, RedfishError>;
+
+async fn get_service_root() -> Json<Value> {
+    Json(service_root())
+}
+
+async fn get_power_equipment() -> Json<Value> {
+    Json(power_equipment())
+}
+
+async fn get_rack_pdus(State(mpx): State<Arc<MPX>>) -> ApiResult {
+    let mut pdus: Vec<u8> = mpx.get_receptacles().await?.iter().map(|r| r.pdu).collect();
+    pdus.sort_unstable();
+    pdus.dedup();
+    Ok(Json(rack_pdus(&pdus)))
+}
+
+async fn get_rack_pdu(State(mpx): State<Arc<MPX>>, Path(pdu): Path<u8>) -> ApiResult {
+    Ok(Json(rack_pdu(pdu, &mpx.get_info_pdu(pdu).await?)))
+}
+
+async fn get_branches(State(mpx): State<Arc<MPX>>, Path(pdu): Path<u8>) -> ApiResult {
+    let mut ids: Vec<u8> = mpx.get_receptacles().await?.iter()
+        .filter(|r| r.pdu == pdu)
+        .map(|r| r.branch)
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    Ok(Json(branches(pdu, &ids)))
+}
+
+async fn get_branch(State(mpx): State<Arc<MPX>>, Path((pdu, id)): Path<(u8, u8)>) -> ApiResult {
+    Ok(Json(branch(pdu, id, &mpx.get_info_branch((pdu, id)).await?)))
+}
+
+async fn get_outlets(State(mpx): State<Arc<MPX>>, Path(pdu): Path<u8>) -> ApiResult {
+    let receptacles: Vec<ReceptacleListEntry> = mpx.get_receptacles().await?.into_iter()
+        .filter(|r| r.pdu == pdu)
+        .collect();
+    Ok(Json(outlets(pdu, &receptacles)))
+}
+
+async fn get_outlet(State(mpx): State<Arc<MPX>>, Path((pdu, id)): Path<(u8, String)>) -> ApiResult {
+    let (branch, receptacle) = parse_outlet_id(&id).ok_or_else(not_found)?;
+    let info = mpx.get_info_receptacle((pdu, branch, receptacle)).await?;
+    Ok(Json(outlet(pdu, branch, receptacle, &info)))
+}
+
+async fn post_power_control(State(mpx): State<Arc<MPX>>, Path((pdu, id)): Path<(u8, String)>, Json(body): Json<Value>) -> Result<StatusCode, RedfishError> {
+    let (branch, receptacle) = parse_outlet_id(&id).ok_or_else(not_found)?;
+
+    match body.get("PowerState").and_then(|s| s.as_str()) {
+        Some("On") => mpx.receptacle_enable((pdu, branch, receptacle)).await?,
+        Some("Off") => mpx.receptacle_disable((pdu, branch, receptacle)).await?,
+        Some("PowerCycle") => mpx.receptacle_reboot((pdu, branch, receptacle)).await?,
+        _ => return Err(RedfishError(StatusCode::BAD_REQUEST, "unsupported PowerState".to_string())),
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn check_auth(State(expected): State<Arc<String>>, request: Request, next: Next) -> Response {
+    if request.headers().get(header::AUTHORIZATION).map(|value| value.as_bytes()) != Some(expected.as_bytes()) {
+        let mut response = RedfishError(StatusCode::UNAUTHORIZED, "authentication required".to_string()).into_response();
+        response.headers_mut().insert(header::WWW_AUTHENTICATE, header::HeaderValue::from_static("Basic realm=\"Redfish\""));
+        return response;
+    }
+    next.run(request).await
+}
+
+/// Router serving the Redfish resources for `mpx`
+///
+/// Without `auth` the resources are read-only. With `auth` requests must
+/// carry these credentials and outlets can be switched.
+pub fn router(mpx: Arc<MPX>, auth: Option<Credentials>) -> Router {
+    let router = Router::new()
+        .route("/redfish/v1", get(get_service_root))
+        .route("/redfish/v1/PowerEquipment", get(get_power_equipment))
+        .route(RACK_PDUS, get(get_rack_pdus))
+        .route(&format!("{}/{{pdu}}", RACK_PDUS), get(get_rack_pdu))
+        .route(&format!("{}/{{pdu}}/Branches", RACK_PDUS), get(get_branches))
+        .route(&format!("{}/{{pdu}}/Branches/{{id}}", RACK_PDUS), get(get_branch))
+        .route(&format!("{}/{{pdu}}/Outlets", RACK_PDUS), get(get_outlets))
+        .route(&format!("{}/{{pdu}}/Outlets/{{id}}", RACK_PDUS), get(get_outlet));
+
+    let auth = match auth {
+        Some(auth) => auth,
+        None => return router.with_state(mpx),
+    };
+
+    let expected = format!("Basic {}", base64(format!("{}:{}", auth.username(), auth.password()).as_bytes()));
+    router
+        .route(&format!("{}/{{pdu}}/Outlets/{{id}}/Actions/Outlet.PowerControl", RACK_PDUS), post(post_power_control))
+        .layer(axum::middleware::from_fn_with_state(Arc::new(expected), check_auth))
+        .with_state(mpx)
+}
+
+/// Serve the Redfish facade on `addr` until an error occurs, see
+/// [`router`] for `auth`
+pub async fn serve(mpx: Arc<MPX>, addr: &str, auth: Option<Credentials>) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router(mpx, auth)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_info_tables, PEMModel};
+
+    #[test]
+    fn test_rack_pdu() {
+        let html = include_str!("../testdata/pdu-info.htm").to_string();
+        let mut info = PDUInfo::from_tables(get_info_tables(html).unwrap()).unwrap();
+        let doc = rack_pdu(1, &info);
+
+        assert_eq!(doc["@odata.id"], "/redfish/v1/PowerEquipment/RackPDUs/1");
+        assert_eq!(doc["Model"], "MPXPEM-EHAXXR30");
+        assert_eq!(doc["Outlets"]["@odata.id"], "/redfish/v1/PowerEquipment/RackPDUs/1/Outlets");
+
+        info.hardware.pem_model = PEMModel::Unknown("MPXPEM-EHCXXZ30".to_string());
+        assert_eq!(rack_pdu(1, &info)["Model"], "MPXPEM-EHCXXZ30");
+    }
+
+    #[test]
+    fn test_outlet() {
+        let html = include_str!("../testdata/receptacle-info.htm").to_string();
+        let info = ReceptacleInfo::from_tables(get_info_tables(html).unwrap()).unwrap();
+        let doc = outlet(1, 2, 3, &info);
+
+        assert_eq!(doc["Id"], "2-3");
+        assert_eq!(doc["Actions"]["#Outlet.PowerControl"]["target"],
+                   "/redfish/v1/PowerEquipment/RackPDUs/1/Outlets/2-3/Actions/Outlet.PowerControl");
+        assert_eq!(parse_outlet_id("2-3"), Some((2, 3)));
+        assert_eq!(parse_outlet_id("2"), None);
+    }
+
+    #[tokio::test]
+    async fn test_router_auth() {
+        let mpx = Arc::new(MPX::new("192.0.2.1", "Liebert", "Liebert"));
+        let power_control = format!("{}/1/Outlets/1-1/Actions/Outlet.PowerControl", RACK_PDUS);
+        let client = reqwest::Client::new();
+        let post = |url: String, state: &str| client.post(url).header("Content-Type", "application/json").body(json!({ "PowerState": state }).to_string());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let app = router(mpx.clone(), None);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let response = client.get(format!("{}/redfish/v1", base)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = post(format!("{}{}", base, power_control), "Off").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let app = router(mpx, Some(Credentials::new("dcim", "secret")));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let response = client.get(format!("{}/redfish/v1", base)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().contains_key(header::WWW_AUTHENTICATE));
+        let response = post(format!("{}{}", base, power_control), "Off").basic_auth("dcim", Some("wrong")).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = client.get(format!("{}/redfish/v1", base)).basic_auth("dcim", Some("secret")).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = post(format!("{}{}", base, power_control), "Explode").basic_auth("dcim", Some("secret")).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,363 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Human readable device reports
+//!
+//! Summarizes a [`Snapshot`] (hardware, per-phase load, receptacles and
+//! active alarms) as plain text, Markdown or HTML, e.g. for attaching
+//! to change tickets and handover documents. All labels go through
+//! [`l10n`](crate::l10n), so reports can be produced in other languages.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//!
+//! fn print_report(snapshot: &liebert::Snapshot) {
+//!     let report = liebert::report::render(snapshot);
+//!     println!("{}", report.to_markdown());
+//! }
+//! ```
+
+use crate::Snapshot;
+use crate::annotations::Annotations;
+use crate::l10n::tr;
+
+#[derive(Clone,Debug,PartialEq)]
+/// Part of a report
+pub enum Section {
+    /// List of name/value pairs
+    Fields {
+        title: String,
+        fields: Vec<(String, String)>,
+    },
+    /// Table with header row
+    Table {
+        title: String,
+        header: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// Rendered device report, see [`render`]
+pub struct Report {
+    pub title: String,
+    pub sections: Vec<Section>,
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| tr(s)).collect()
+}
+
+fn on_off(state: bool) -> String {
+    tr(if state { "on" } else { "off" })
+}
+
+fn yes_no(state: bool) -> String {
+    tr(if state { "yes" } else { "no" })
+}
+
+/// Receptacle measurement, `-` for receptacles which are not measured
+fn measurement(value: Option<f32>, precision: usize, unit: &str) -> String {
+    match value {
+        Some(value) => format!("{:.*} {}", precision, value, unit),
+        None => "-".to_string(),
+    }
+}
+
+/// Build the report for a snapshot
+pub fn render(snapshot: &Snapshot) -> Report {
+    render_annotated(snapshot, &Annotations::default())
+}
+
+/// Build the report for a snapshot, adding the annotation columns to
+/// the receptacle and alarm tables
+pub fn render_annotated(snapshot: &Snapshot, annotations: &Annotations) -> Report {
+    let info = &snapshot.info;
+    let hw = &info.hardware;
+    let status = &info.status;
+
+    let label = if info.settings.label.is_empty() {
+        format!("{} {}", tr("PDU"), snapshot.pdu)
+    } else {
+        format!("{} {} ({})", tr("PDU"), snapshot.pdu, info.settings.label)
+    };
+
+    let mut hardware = vec![
+        (tr("PEM model"), hw.pem_model.to_string()),
+        (tr("PEM serial number"), hw.serial_number.clone()),
+        (tr("PEM firmware"), hw.fw_version.to_string()),
+        (tr("Wiring"), hw.wiring_type.to_string()),
+        (tr("Rated input"), format!("{} V / {} A / {} Hz", hw.rated_input_voltage, hw.rated_input_current, hw.rated_input_line_frequency)),
+    ];
+    for b in snapshot.branches.iter() {
+        let bhw = &b.info.hardware;
+        hardware.push((format!("{} {}", tr("Branch"), b.branch), format!("{} ({}, {}), {} {}, {} {}",
+            bhw.brm_model, bhw.receptacle_type, bhw.line_source,
+            tr("serial"), bhw.serial_number, tr("firmware"), bhw.fw_version)));
+    }
+
+    let summary = vec![
+        (tr("Input power"), format!("{:.0} W", status.input_power)),
+        (tr("Accumulated energy"), format!("{:.1} kWh", status.accumulated_energy)),
+        (tr("Line frequency"), format!("{:.1} Hz", status.line_frequency)),
+        (tr("Neutral current"), format!("{:.2} A", status.current_n)),
+    ];
+
+    let mut phases = vec![
+        ("L1", status.voltage_l1_n, status.current_l1, status.current_utilization_l1, status.current_available_to_alarm_l1),
+    ];
+    // single phase PDUs only have L1
+    for (phase, voltage, current, utilization, available) in [
+        ("L2", status.voltage_l2_n, status.current_l2, status.current_utilization_l2, status.current_available_to_alarm_l2),
+        ("L3", status.voltage_l3_n, status.current_l3, status.current_utilization_l3, status.current_available_to_alarm_l3),
+    ] {
+        if let (Some(voltage), Some(current), Some(utilization), Some(available)) = (voltage, current, utilization, available) {
+            phases.push((phase, voltage, current, utilization, available));
+        }
+    }
+    let phases = phases.into_iter().map(|(phase, voltage, current, utilization, available)| vec![
+        phase.to_string(),
+        format!("{:.1} V", voltage),
+        format!("{:.2} A", current),
+        format!("{:.0} %", utilization),
+        format!("{:.2} A", available),
+    ]).collect();
+
+    let branches = snapshot.branches.iter().map(|b| vec![
+        b.branch.to_string(),
+        b.info.settings.label.clone(),
+        format!("{:.1} V", b.info.status.voltage),
+        format!("{:.2} A", b.info.status.current),
+        format!("{:.0} %", b.info.status.current_utilization),
+        format!("{:.0} W", b.info.status.power),
+        format!("{:.1} kWh", b.info.status.accumulated_energy),
+    ]).collect();
+
+    let receptacles = snapshot.receptacles.iter().map(|r| {
+        let mut row = vec![
+            format!("{}-{}-{}", snapshot.pdu, r.branch, r.receptacle),
+            r.info.settings.label.clone(),
+            on_off(r.info.settings.power_state),
+            yes_no(r.info.settings.control_lock_state),
+            r.info.hardware.receptacle_type.to_string(),
+            r.info.hardware.line_source.to_string(),
+            measurement(r.info.status.current, 2, "A"),
+            measurement(r.info.status.power, 0, "W"),
+            measurement(r.info.status.accumulated_energy, 1, "kWh"),
+        ];
+        row.extend(annotations.row((snapshot.pdu, r.branch, r.receptacle)));
+        row
+    }).collect();
+
+    let alarms = snapshot.events.iter().filter(|e| e.pdu == snapshot.pdu).map(|e| {
+        let mut row = vec![
+            e.level.to_string(),
+            format!("{}-{}-{}", e.pdu, e.branch, e.receptacle),
+            e.event.to_string(),
+        ];
+        row.extend(annotations.row((e.pdu, e.branch, e.receptacle)));
+        row
+    }).collect();
+
+    let with_annotations = |columns: &[&str]| {
+        let mut header = strings(columns);
+        header.extend(annotations.columns().iter().cloned());
+        header
+    };
+
+    Report {
+        title: label,
+        sections: vec![
+            Section::Fields { title: tr("Hardware"), fields: hardware },
+            Section::Fields { title: tr("Input"), fields: summary },
+            Section::Table {
+                title: tr("Per-phase load"),
+                header: strings(&["Phase", "Voltage", "Current", "Utilization", "Until alarm"]),
+                rows: phases,
+            },
+            Section::Table {
+                title: tr("Branches"),
+                header: strings(&["Branch", "Label", "Voltage", "Current", "Utilization", "Power", "Energy"]),
+                rows: branches,
+            },
+            Section::Table {
+                title: tr("Receptacles"),
+                header: with_annotations(&["Receptacle", "Label", "State", "Locked", "Type", "Line", "Current", "Power", "Energy"]),
+                rows: receptacles,
+            },
+            Section::Table {
+                title: tr("Active alarms"),
+                header: with_annotations(&["Level", "Location", "Event"]),
+                rows: alarms,
+            },
+        ],
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+impl Report {
+    /// Plain text rendering with aligned columns
+    pub fn to_text(&self) -> String {
+        let mut out = format!("{}\n{}\n", self.title, "=".repeat(self.title.chars().count()));
+
+        for section in self.sections.iter() {
+            match section {
+                Section::Fields { title, fields } => {
+                    out += &format!("\n{}\n{}\n", title, "-".repeat(title.chars().count()));
+                    let width = fields.iter().map(|(k, _)| k.chars().count()).max().unwrap_or(0);
+                    for (key, value) in fields.iter() {
+                        out += &format!("{:width$}  {}\n", key, value, width = width);
+                    }
+                },
+                Section::Table { title, header, rows } => {
+                    out += &format!("\n{}\n{}\n", title, "-".repeat(title.chars().count()));
+                    if rows.is_empty() {
+                        out += &format!("{}\n", tr("none"));
+                        continue;
+                    }
+                    let widths: Vec<usize> = header.iter().enumerate().map(|(i, h)| {
+                        rows.iter().map(|r| r[i].chars().count()).chain(std::iter::once(h.chars().count())).max().unwrap_or(0)
+                    }).collect();
+                    for row in std::iter::once(header).chain(rows.iter()) {
+                        let cells: Vec<String> = row.iter().zip(widths.iter())
+                            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                            .collect();
+                        out += cells.join("  ").trim_end();
+                        out += "\n";
+                    }
+                },
+            }
+        }
+
+        out
+    }
+
+    /// Markdown rendering (tables in GitHub flavored Markdown syntax)
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n", escape_markdown(&self.title));
+
+        for section in self.sections.iter() {
+            match section {
+                Section::Fields { title, fields } => {
+                    out += &format!("\n## {}\n\n", escape_markdown(title));
+                    for (key, value) in fields.iter() {
+                        out += &format!("- **{}:** {}\n", escape_markdown(key), escape_markdown(value));
+                    }
+                },
+                Section::Table { title, header, rows } => {
+                    out += &format!("\n## {}\n\n", escape_markdown(title));
+                    if rows.is_empty() {
+                        out += &format!("{}\n", tr("none"));
+                        continue;
+                    }
+                    let row_line = |row: &Vec<String>| {
+                        let cells: Vec<String> = row.iter().map(|c| escape_markdown(c)).collect();
+                        format!("| {} |\n", cells.join(" | "))
+                    };
+                    out += &row_line(header);
+                    out += &format!("|{}\n", "---|".repeat(header.len()));
+                    for row in rows.iter() {
+                        out += &row_line(row);
+                    }
+                },
+            }
+        }
+
+        out
+    }
+
+    /// Standalone HTML document
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title);
+        let mut out = format!("<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{}</title></head>\n<body>\n<h1>{}</h1>\n", title, title);
+
+        for section in self.sections.iter() {
+            match section {
+                Section::Fields { title, fields } => {
+                    out += &format!("<h2>{}</h2>\n<dl>\n", escape_html(title));
+                    for (key, value) in fields.iter() {
+                        out += &format!("<dt>{}</dt><dd>{}</dd>\n", escape_html(key), escape_html(value));
+                    }
+                    out += "</dl>\n";
+                },
+                Section::Table { title, header, rows } => {
+                    out += &format!("<h2>{}</h2>\n", escape_html(title));
+                    if rows.is_empty() {
+                        out += &format!("<p>{}</p>\n", escape_html(&tr("none")));
+                        continue;
+                    }
+                    out += "<table>\n<tr>";
+                    for cell in header.iter() {
+                        out += &format!("<th>{}</th>", escape_html(cell));
+                    }
+                    out += "</tr>\n";
+                    for row in rows.iter() {
+                        out += "<tr>";
+                        for cell in row.iter() {
+                            out += &format!("<td>{}</td>", escape_html(cell));
+                        }
+                        out += "</tr>\n";
+                    }
+                    out += "</table>\n";
+                },
+            }
+        }
+
+        out += "</body>\n</html>\n";
+        out
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::tests::snapshot;
+
+    #[test]
+    fn test_render_formats() {
+        let mut snapshot = snapshot();
+        snapshot.receptacles[0].info.settings.label = "db|<01>".to_string();
+        let report = render(&snapshot);
+
+        let text = report.to_text();
+        assert!(text.contains("MPXPEM-EHAXXR30"));
+        assert!(text.contains("1-1-2"));
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("## Per-phase load"));
+        assert!(markdown.contains("db\\|<01>"));
+
+        let html = report.to_html();
+        assert!(html.contains("db|&lt;01&gt;"));
+        assert!(!html.contains("<01>"));
+    }
+
+    #[test]
+    fn test_render_annotated() {
+        let annotations = Annotations::from_csv("address,host,owner\n1-1-2,db-01,dba\n".as_bytes()).unwrap();
+        let report = render_annotated(&snapshot(), &annotations);
+
+        match &report.sections[4] {
+            Section::Table { header, rows, .. } => {
+                assert_eq!(header.last().map(|s| s.as_str()), Some("owner"));
+                assert_eq!(rows[0].last().map(|s| s.as_str()), Some(""));
+                assert_eq!(rows[1].last().map(|s| s.as_str()), Some("dba"));
+            },
+            _ => panic!("receptacle table missing"),
+        }
+    }
+}
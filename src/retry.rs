@@ -0,0 +1,132 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Retries with exponential backoff
+//!
+//! The MPX web server regularly drops the first request after being
+//! idle. With a [`RetryPolicy`] set via
+//! [`MPXBuilder::retry`](crate::MPXBuilder::retry) failed page fetches
+//! are repeated after an exponentially growing, optionally jittered,
+//! delay. Commands and settings writes are only retried if
+//! `retry_posts` is set, since the device may have executed a command
+//! whose response got lost.
+//!
+//! Only transport errors (connection failures, timeouts) are retried;
+//! authentication and parser errors are returned immediately.
+
+use crate::{MPX, MPXError};
+use crate::transport::HttpTransport;
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+/// Retry behaviour of a [`MPX`]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_backoff: Duration,
+    /// Factor the delay grows by after each attempt
+    pub multiplier: f32,
+    /// Randomize delays by up to ±50% to avoid synchronized retries
+    pub jitter: bool,
+    /// Also retry commands and settings writes
+    pub retry_posts: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: true,
+            retry_posts: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before attempt `attempt + 1` (`attempt` starts at 1), without jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = (self.multiplier as f64).powi(attempt.saturating_sub(1) as i32);
+        self.initial_backoff.mul_f64(factor).min(self.max_backoff)
+    }
+
+    fn jittered(&self, delay: Duration) -> Duration {
+        if !self.jitter {
+            return delay;
+        }
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+        delay.mul_f32(0.5 + (nanos % 1000) as f32 / 1000.0)
+    }
+}
+
+impl<H: HttpTransport> MPX<H> {
+    /// Run `operation` according to the retry policy
+    pub(crate) async fn with_retry<T, F, Fut>(&self, post: bool, operation: F) -> Result<T, MPXError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, MPXError>>,
+    {
+        let policy = match self.inner.retry {
+            Some(policy) if !post || policy.retry_posts => policy,
+            _ => return operation().await,
+        };
+
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if attempt < policy.max_attempts && e.is_retryable() => {
+                    let delay = policy.jittered(policy.backoff(attempt));
+                    #[cfg(feature = "log")]
+                    log::debug!(target: "liebert_mpx", device = self.inner.host.as_str(), attempt, error:% = e, outcome = "retry";
+                        "request to {} failed, retrying in {:?}", self.inner.host, delay);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TimeoutError;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(10), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_with_retry() {
+        let policy = RetryPolicy { initial_backoff: Duration::from_millis(1), ..RetryPolicy::default() };
+        let mpx = MPX::builder("pdu1").retry(policy).build().unwrap();
+        let calls = Cell::new(0);
+
+        let flaky = || async {
+            calls.set(calls.get() + 1);
+            match calls.get() {
+                1 => Err(MPXError::Timeout(TimeoutError { deadline: None })),
+                _ => Ok(()),
+            }
+        };
+        assert!(mpx.with_retry(false, flaky).await.is_ok());
+        assert_eq!(calls.get(), 2);
+
+        calls.set(0);
+        assert!(mpx.with_retry(true, flaky).await.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+}
@@ -0,0 +1,223 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Receptacle readings rolled up to branch modules and input phases
+//!
+//! Sums the power, current and energy of the receptacles of a
+//! [`Snapshot`] per branch module and per phase and compares the sums
+//! with the values measured by the branch modules and the PDU input.
+//! Large differences point to metering drift or to load connected
+//! without receptacle metering. For branch modules without own metering
+//! the sums are the only available estimate.
+//!
+//! Accumulated energy counters can be reset individually, so energy
+//! differences are only meaningful if all counters were reset together.
+
+use crate::{LineSource, ReceptacleInfo, Snapshot};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[derive(Copy,Clone,Debug,Default,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Summed receptacle readings
+pub struct Totals {
+    /// power in W
+    pub power: f32,
+    /// current in A AC
+    pub current: f32,
+    /// accumulated energy in kWh
+    pub energy: f32,
+    /// number of receptacles with readings
+    pub metered: usize,
+    /// number of receptacles without any reading
+    pub unmetered: usize,
+}
+
+impl Totals {
+    fn add(&mut self, info: &ReceptacleInfo) {
+        let s = &info.status;
+        if s.power.is_none() && s.current.is_none() && s.accumulated_energy.is_none() {
+            self.unmetered += 1;
+            return;
+        }
+        self.metered += 1;
+        self.power += s.power.unwrap_or(0.0);
+        self.current += s.current.unwrap_or(0.0);
+        self.energy += s.accumulated_energy.unwrap_or(0.0);
+    }
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Measured value next to the sum of the receptacle values
+pub struct Drift {
+    pub measured: f32,
+    pub summed: f32,
+}
+
+impl Drift {
+    /// Measured minus summed value, i.e. the unaccounted part
+    pub fn difference(&self) -> f32 {
+        self.measured - self.summed
+    }
+
+    /// Difference relative to the measured value, `None` if nothing is
+    /// measured
+    pub fn relative(&self) -> Option<f32> {
+        if self.measured == 0.0 {
+            return None;
+        }
+        Some(self.difference() / self.measured)
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Receptacle sums of a branch module compared with its own readings
+pub struct BranchRollup {
+    /// Branch number (usually 1-6)
+    pub branch: u8,
+    pub line_source: LineSource,
+    pub totals: Totals,
+    /// power in W
+    pub power: Drift,
+    /// current in A AC
+    pub current: Drift,
+    /// accumulated energy in kWh
+    pub energy: Drift,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Receptacle sums of an input phase compared with the input current
+pub struct PhaseRollup {
+    pub line_source: LineSource,
+    pub totals: Totals,
+    /// current in A AC, `None` for L2 and L3 of single phase PDUs
+    pub current: Option<Drift>,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Receptacle sums of the whole PDU compared with the input readings
+pub struct PDURollup {
+    pub totals: Totals,
+    /// power in W
+    pub power: Drift,
+    /// accumulated energy in kWh
+    pub energy: Drift,
+    /// L1, L2 and L3
+    pub phases: Vec<PhaseRollup>,
+}
+
+/// Summed readings of the receptacles of a branch module
+pub fn branch_totals(snapshot: &Snapshot, branch: u8) -> Totals {
+    let mut totals = Totals::default();
+    for r in snapshot.branch_receptacles(branch) {
+        totals.add(&r.info);
+    }
+    totals
+}
+
+/// Summed readings of the receptacles fed by L1, L2 and L3
+pub fn phase_totals(snapshot: &Snapshot) -> [Totals; 3] {
+    let mut totals = [Totals::default(); 3];
+    for r in snapshot.receptacles.iter() {
+        let index = match r.info.hardware.line_source {
+            LineSource::L1toN => 0,
+            LineSource::L2toN => 1,
+            LineSource::L3toN => 2,
+            _ => continue,
+        };
+        totals[index].add(&r.info);
+    }
+    totals
+}
+
+/// Compare the receptacle sums of a branch module with its readings,
+/// `None` if the branch module is not part of the snapshot
+pub fn branch_rollup(snapshot: &Snapshot, branch: u8) -> Option<BranchRollup> {
+    let b = snapshot.branch(branch)?;
+    let totals = branch_totals(snapshot, branch);
+    let status = &b.info.status;
+
+    Some(BranchRollup {
+        branch,
+        line_source: b.info.hardware.line_source.clone(),
+        totals,
+        power: Drift { measured: status.power, summed: totals.power },
+        current: Drift { measured: status.current, summed: totals.current },
+        energy: Drift { measured: status.accumulated_energy, summed: totals.energy },
+    })
+}
+
+/// Compare the receptacle sums of all branch modules of the snapshot
+pub fn branch_rollups(snapshot: &Snapshot) -> Vec<BranchRollup> {
+    snapshot.branches.iter().filter_map(|b| branch_rollup(snapshot, b.branch)).collect()
+}
+
+/// Compare the receptacle sums with the PDU input readings
+pub fn pdu_rollup(snapshot: &Snapshot) -> PDURollup {
+    let mut totals = Totals::default();
+    for r in snapshot.receptacles.iter() {
+        totals.add(&r.info);
+    }
+
+    let status = &snapshot.info.status;
+    let measured = [Some(status.current_l1), status.current_l2, status.current_l3];
+    let sources = [LineSource::L1toN, LineSource::L2toN, LineSource::L3toN];
+    let phases = phase_totals(snapshot).iter().zip(measured.iter()).zip(sources.iter())
+        .map(|((totals, measured), line_source)| PhaseRollup {
+            line_source: line_source.clone(),
+            totals: *totals,
+            current: measured.map(|measured| Drift { measured, summed: totals.current }),
+        })
+        .collect();
+
+    PDURollup {
+        totals,
+        power: Drift { measured: status.input_power, summed: totals.power },
+        energy: Drift { measured: status.accumulated_energy, summed: totals.energy },
+        phases,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::tests::snapshot;
+
+    #[test]
+    fn test_rollup() {
+        let mut snapshot = snapshot();
+        snapshot.receptacles[0].info.status.power = Some(300.0);
+        snapshot.receptacles[0].info.status.current = Some(1.5);
+        snapshot.receptacles[1].info.status.power = Some(100.0);
+        snapshot.receptacles[1].info.status.current = Some(0.5);
+        snapshot.branches[0].info.status.power = 500.0;
+        snapshot.branches[0].info.status.current = 2.0;
+
+        let rollup = branch_rollup(&snapshot, 1).unwrap();
+        assert_eq!(rollup.totals.metered, 2);
+        assert_eq!(rollup.power.summed, 400.0);
+        assert_eq!(rollup.power.difference(), 100.0);
+        assert_eq!(rollup.power.relative(), Some(0.2));
+        assert_eq!(rollup.current.difference(), 0.0);
+        assert!(branch_rollup(&snapshot, 2).is_none());
+
+        let status = &mut snapshot.receptacles[1].info.status;
+        status.power = None;
+        status.current = None;
+        status.accumulated_energy = None;
+        let totals = branch_totals(&snapshot, 1);
+        assert_eq!((totals.metered, totals.unmetered, totals.power), (1, 1, 300.0));
+
+        // receptacles of the test data are fed by L3
+        let pdu = pdu_rollup(&snapshot);
+        assert_eq!(pdu.power.summed, 300.0);
+        assert_eq!(pdu.phases[2].totals.current, 1.5);
+        assert_eq!(pdu.phases[0].totals, Totals::default());
+        assert_eq!(pdu.phases[2].current.unwrap().measured, snapshot.info.status.current_l3.unwrap());
+    }
+}
@@ -0,0 +1,98 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Receptacle lookup by user label
+//!
+//! Receptacles are often labeled with the host name of the connected
+//! device. [`MPX::find_receptacles`] matches the labels of the
+//! receptacle list against a [`LabelPattern`]:
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::search::LabelPattern;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         for r in pdu.find_receptacles(&LabelPattern::Substring("db".into())).await.unwrap() {
+//!             println!("{}-{}-{}: {}", r.pdu, r.branch, r.receptacle, r.label);
+//!         }
+//!     };
+//! }
+//! ```
+//!
+//! Regular expressions need the `regex` feature.
+
+use crate::{ReceptacleList, ReceptacleListEntry};
+#[cfg(feature = "client")]
+use crate::{MPX, MPXError};
+#[cfg(feature = "client")]
+use crate::transport::HttpTransport;
+
+#[derive(Clone,Debug)]
+/// Pattern for receptacle labels
+#[non_exhaustive]
+pub enum LabelPattern {
+    /// Label equals the text
+    Exact(String),
+    /// Label contains the text
+    Substring(String),
+    /// Label matches the regular expression (unanchored, use `^...$`
+    /// to match the whole label)
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl LabelPattern {
+    /// Check if a label matches the pattern
+    pub fn matches(&self, label: &str) -> bool {
+        match self {
+            LabelPattern::Exact(text) => label == text,
+            LabelPattern::Substring(text) => label.contains(text.as_str()),
+            #[cfg(feature = "regex")]
+            LabelPattern::Regex(regex) => regex.is_match(label),
+        }
+    }
+}
+
+/// Receptacles of a receptacle list with a label matching the pattern
+pub fn filter_receptacles(receptacles: &[ReceptacleListEntry], pattern: &LabelPattern) -> ReceptacleList {
+    receptacles.iter().filter(|r| pattern.matches(&r.label)).cloned().collect()
+}
+
+#[cfg(feature = "client")]
+impl<H: HttpTransport> MPX<H> {
+    /// Find all receptacles with a label matching the pattern
+    pub async fn find_receptacles(&self, pattern: &LabelPattern) -> Result<ReceptacleList, MPXError> {
+        Ok(filter_receptacles(&self.get_receptacles().await?, pattern))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_receptacles;
+
+    #[test]
+    fn test_filter_receptacles() {
+        let html = format!("<table id=\"rcpTable\">{}</table>", [("1-1-1", "db01"), ("1-1-2", "db02"), ("1-2-1", "web01")].iter().map(|(id, label)| format!(concat!(
+            "<tr id=\"{}\"><td><a href=\"#\"><nobr>{}</nobr></a></td><td></td>",
+            "<td><span title=\"On\"></span></td><td><span title=\"Unlocked\"></span></td>",
+            "<td><img src=\"../../../images/accept.png\"></td></tr>"), id, label)).collect::<String>());
+        let receptacles = parse_receptacles(html).unwrap();
+
+        let found = filter_receptacles(&receptacles, &LabelPattern::Exact("db02".to_string()));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].receptacle, 2);
+        assert!(filter_receptacles(&receptacles, &LabelPattern::Exact("db".to_string())).is_empty());
+        assert_eq!(filter_receptacles(&receptacles, &LabelPattern::Substring("01".to_string())).len(), 2);
+
+        #[cfg(feature = "regex")]
+        {
+            let pattern = LabelPattern::Regex(regex::Regex::new("^(db|web)0[1]$").unwrap());
+            let found = filter_receptacles(&receptacles, &pattern);
+            assert_eq!(found.iter().map(|r| r.label.as_str()).collect::<Vec<_>>(), ["db01", "web01"]);
+        }
+    }
+}
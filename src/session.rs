@@ -0,0 +1,121 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Form based session login
+//!
+//! Newer card firmware protects the rpc pages with a login form that
+//! sets a session cookie, instead of (or in addition to) HTTP basic
+//! auth. With [`MPXBuilder::session_login`](crate::MPXBuilder::session_login)
+//! the client logs in once the device asks for it (answers with 401/403
+//! or redirects to the login page), sends the session cookies with
+//! every request and logs in again once the session expired.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::session::SessionLogin;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::builder("192.168.23.42")
+//!         .credentials("Liebert", "Liebert")
+//!         .session_login(SessionLogin::new("/login.htm", "username", "password"))
+//!         .build()
+//!         .unwrap();
+//! }
+//! ```
+
+use crate::{AuthenticationError, MPX, MPXError};
+use crate::transport::{HttpTransport, Request, Response};
+
+#[derive(Clone,Debug,PartialEq)]
+/// Login form of the device
+pub struct SessionLogin {
+    /// Path the login form is posted to
+    pub path: String,
+    /// Form field carrying the username
+    pub username_field: String,
+    /// Form field carrying the password
+    pub password_field: String,
+    /// Additional fixed form fields (e.g. the submit button)
+    pub extra_fields: Vec<(String, String)>,
+}
+
+impl SessionLogin {
+    pub fn new(path: &str, username_field: &str, password_field: &str) -> Self {
+        SessionLogin {
+            path: path.to_string(),
+            username_field: username_field.to_string(),
+            password_field: password_field.to_string(),
+            extra_fields: Vec::new(),
+        }
+    }
+
+    /// Add a fixed form field to the login request
+    pub fn field(mut self, name: &str, value: &str) -> Self {
+        self.extra_fields.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Check if the device answered with a login prompt instead of the page
+    pub(crate) fn expired(&self, response: &Response) -> bool {
+        matches!(response.status, 401 | 403) || response.path() == self.path
+    }
+}
+
+/// Join the `name=value` parts of `Set-Cookie` headers into a `Cookie` header
+fn cookie_header<'a, I: Iterator<Item = &'a str>>(set_cookies: I) -> Option<String> {
+    let cookies: Vec<&str> = set_cookies
+        .filter_map(|c| c.split(';').next())
+        .map(|c| c.trim())
+        .filter(|c| c.contains('='))
+        .collect();
+
+    if cookies.is_empty() {
+        None
+    } else {
+        Some(cookies.join("; "))
+    }
+}
+
+impl<H: HttpTransport> MPX<H> {
+    /// Log in and store the session cookies
+    ///
+    /// Called automatically when a session login is configured; only
+    /// needed to check the credentials early.
+    pub async fn login(&self) -> Result<(), MPXError> {
+        let session = match &self.inner.session {
+            Some(session) => session,
+            None => return Ok(()),
+        };
+
+        let credentials = self.credentials().await?;
+        let mut form = vec![
+            (session.username_field.as_str(), credentials.username()),
+            (session.password_field.as_str(), credentials.password()),
+        ];
+        form.extend(session.extra_fields.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+
+        let response = self.exchange(Request::post(&self.url(&session.path), &form)).await?;
+        let cookie = cookie_header(response.header("Set-Cookie"));
+
+        match cookie {
+            Some(cookie) => {
+                *self.inner.session_cookie.lock().unwrap() = Some(cookie);
+                Ok(())
+            },
+            None => Err(AuthenticationError { status: response.status }.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cookie_header() {
+        let headers = ["SID=abc123; Path=/; HttpOnly", "lang=en", "Secure"];
+        assert_eq!(cookie_header(headers.iter().copied()), Some("SID=abc123; lang=en".to_string()));
+        assert_eq!(cookie_header([].iter().copied()), None);
+    }
+}
@@ -0,0 +1,341 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Point-in-time state of a PDU
+//!
+//! [`MPX::get_snapshot`] fetches the PDU, branch and receptacle
+//! information and the active events concurrently, so all parts are taken
+//! within a short time window:
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         let snapshot = pdu.get_snapshot(1).await.unwrap();
+//!         println!("{} receptacles, {} events", snapshot.receptacles.len(), snapshot.events.len());
+//!     };
+//! }
+//! ```
+
+use crate::{BranchInfo, Event, EventLevel, EventList, PDUInfo, ReceptacleInfo};
+#[cfg(feature = "client")]
+use crate::{BranchAddress, BranchId, MPX, MPXError, PduId, ReceptacleAddress};
+#[cfg(feature = "client")]
+use crate::topology::{run_bounded, DISCOVERY_CONCURRENCY};
+#[cfg(feature = "client")]
+use crate::transport::HttpTransport;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Information about a branch module together with its number
+pub struct BranchSnapshot {
+    /// Branch number (usually 1-6)
+    pub branch: u8,
+    pub info: BranchInfo,
+    /// Time the information page was received
+    pub taken_at: SystemTime,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Information about a receptacle together with its address
+pub struct ReceptacleSnapshot {
+    /// Branch number (usually 1-6)
+    pub branch: u8,
+    /// Receptacle number (usually 1-6)
+    pub receptacle: u8,
+    pub info: ReceptacleInfo,
+    /// Time the information page was received
+    pub taken_at: SystemTime,
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Worst event level and number of events per level, e.g. for a
+/// single traffic light per device
+pub struct OverallHealth {
+    /// Most severe level, `OK` without any active event
+    pub level: EventLevel,
+    pub ok: usize,
+    pub info: usize,
+    pub warning: usize,
+    pub alarm: usize,
+}
+
+impl OverallHealth {
+    /// Health of a list of PDU, branch and receptacle events
+    pub fn from_events(events: &[Event]) -> Self {
+        let mut health = OverallHealth { level: EventLevel::OK, ok: 0, info: 0, warning: 0, alarm: 0 };
+        for event in events.iter() {
+            match event.level {
+                EventLevel::OK => health.ok += 1,
+                EventLevel::INFO => health.info += 1,
+                EventLevel::WARNING => health.warning += 1,
+                EventLevel::ALARM => health.alarm += 1,
+            }
+            health.level = health.level.max(event.level);
+        }
+        health
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Full state of a PDU: input module, branches, receptacles and events
+pub struct Snapshot {
+    /// PDU number (usually 1)
+    pub pdu: u8,
+    pub info: PDUInfo,
+    pub branches: Vec<BranchSnapshot>,
+    pub receptacles: Vec<ReceptacleSnapshot>,
+    /// Active events of this PDU
+    pub events: EventList,
+    /// Time the fetch of the snapshot was started; all parts were
+    /// received after this time
+    pub taken_at: SystemTime,
+}
+
+impl Snapshot {
+    /// Time since the snapshot was taken
+    pub fn age(&self) -> Duration {
+        SystemTime::now().duration_since(self.taken_at).unwrap_or_default()
+    }
+
+    /// Information about a branch module
+    pub fn branch(&self, branch: u8) -> Option<&BranchSnapshot> {
+        self.branches.iter().find(|b| b.branch == branch)
+    }
+
+    /// Information about a receptacle
+    pub fn receptacle(&self, branch: u8, receptacle: u8) -> Option<&ReceptacleSnapshot> {
+        self.receptacles.iter().find(|r| r.branch == branch && r.receptacle == receptacle)
+    }
+
+    /// Receptacles connected to a branch module
+    pub fn branch_receptacles(&self, branch: u8) -> impl Iterator<Item = &ReceptacleSnapshot> {
+        self.receptacles.iter().filter(move |r| r.branch == branch)
+    }
+
+    /// Health of the PDU from its active events
+    pub fn overall_health(&self) -> OverallHealth {
+        OverallHealth::from_events(&self.events)
+    }
+}
+
+#[cfg(feature = "client")]
+impl<H: HttpTransport + 'static> MPX<H> {
+    /// Fetch the full state of a PDU with [`DISCOVERY_CONCURRENCY`]
+    /// concurrent requests
+    pub async fn get_snapshot(&self, pdu: impl Into<PduId>) -> Result<Snapshot, MPXError> {
+        self.get_snapshot_with(pdu, DISCOVERY_CONCURRENCY).await
+    }
+
+    /// Fetch the full state of a PDU with at most `concurrency` concurrent
+    /// requests for the branch and receptacle information pages
+    pub async fn get_snapshot_with(&self, pdu: impl Into<PduId>, concurrency: usize) -> Result<Snapshot, MPXError> {
+        let PduId(pdu) = pdu.into();
+        self.check_address(pdu, None, None)?;
+        let taken_at = SystemTime::now();
+
+        let modules = async {
+            let addresses = self.receptacle_addresses(pdu, None).await?;
+            let mut branches: Vec<u8> = addresses.iter().map(|a| a.branch.0).collect();
+            branches.sort_unstable();
+            branches.dedup();
+
+            let lookups = branches.into_iter().map(|branch| {
+                let mpx = self.clone();
+                async move {
+                    let info = mpx.get_info_branch((pdu, branch)).await?;
+                    Ok(BranchSnapshot { branch, info, taken_at: SystemTime::now() })
+                }
+            }).collect();
+            let branches = run_bounded(concurrency, lookups).await?;
+            let receptacles = self.get_receptacle_snapshots(addresses, concurrency).await?;
+            Ok((branches, receptacles))
+        };
+
+        let (info, events, (branches, receptacles)) = tokio::try_join!(self.get_info_pdu(pdu), self.get_events(), modules)?;
+
+        Ok(Snapshot {
+            pdu,
+            info,
+            branches,
+            receptacles,
+            events: events.into_iter().filter(|e| e.pdu == pdu).collect(),
+            taken_at,
+        })
+    }
+
+    /// Fetch the information of all receptacles of a PDU with
+    /// [`DISCOVERY_CONCURRENCY`] concurrent requests
+    pub async fn get_info_all_receptacles(&self, pdu: impl Into<PduId>) -> Result<Vec<(ReceptacleAddress, ReceptacleInfo)>, MPXError> {
+        self.get_info_all_receptacles_with(pdu, DISCOVERY_CONCURRENCY).await
+    }
+
+    /// Fetch the information of all receptacles of a PDU with at most
+    /// `concurrency` concurrent requests
+    pub async fn get_info_all_receptacles_with(&self, pdu: impl Into<PduId>, concurrency: usize) -> Result<Vec<(ReceptacleAddress, ReceptacleInfo)>, MPXError> {
+        let PduId(pdu) = pdu.into();
+        self.check_address(pdu, None, None)?;
+        let addresses = self.receptacle_addresses(pdu, None).await?;
+        self.get_info_receptacles(pdu, addresses, concurrency).await
+    }
+
+    /// Fetch the information of all receptacles of a branch with
+    /// [`DISCOVERY_CONCURRENCY`] concurrent requests
+    pub async fn get_info_branch_receptacles(&self, address: impl Into<BranchAddress>) -> Result<Vec<(ReceptacleAddress, ReceptacleInfo)>, MPXError> {
+        self.get_info_branch_receptacles_with(address, DISCOVERY_CONCURRENCY).await
+    }
+
+    /// Fetch the information of all receptacles of a branch with at most
+    /// `concurrency` concurrent requests
+    pub async fn get_info_branch_receptacles_with(&self, address: impl Into<BranchAddress>, concurrency: usize) -> Result<Vec<(ReceptacleAddress, ReceptacleInfo)>, MPXError> {
+        let BranchAddress { pdu: PduId(pdu), branch: BranchId(branch) } = address.into();
+        self.check_address(pdu, Some(branch), None)?;
+        let addresses = self.receptacle_addresses(pdu, Some(branch)).await?;
+        self.get_info_receptacles(pdu, addresses, concurrency).await
+    }
+
+    /// Health of all PDUs of the management card from the active events
+    pub async fn get_health(&self) -> Result<OverallHealth, MPXError> {
+        Ok(OverallHealth::from_events(&self.get_events().await?))
+    }
+
+    /// Addresses of the receptacles of a PDU or one of its branches
+    async fn receptacle_addresses(&self, pdu: u8, branch: Option<u8>) -> Result<Vec<ReceptacleAddress>, MPXError> {
+        Ok(self.get_receptacles().await?.into_iter()
+            .filter(|r| r.pdu == pdu && branch.is_none_or(|b| r.branch == b))
+            .map(|r| ReceptacleAddress::new(r.pdu, r.branch, r.receptacle))
+            .collect())
+    }
+
+    async fn get_info_receptacles(&self, pdu: u8, addresses: Vec<ReceptacleAddress>, concurrency: usize) -> Result<Vec<(ReceptacleAddress, ReceptacleInfo)>, MPXError> {
+        Ok(self.get_receptacle_snapshots(addresses, concurrency).await?.into_iter()
+            .map(|r| (ReceptacleAddress::new(pdu, r.branch, r.receptacle), r.info))
+            .collect())
+    }
+
+    async fn get_receptacle_snapshots(&self, addresses: Vec<ReceptacleAddress>, concurrency: usize) -> Result<Vec<ReceptacleSnapshot>, MPXError> {
+        let lookups = addresses.into_iter().map(|address| {
+            let mpx = self.clone();
+            async move {
+                let info = mpx.get_info_receptacle(address).await?;
+                Ok(ReceptacleSnapshot { branch: address.branch.0, receptacle: address.receptacle.0, info, taken_at: SystemTime::now() })
+            }
+        }).collect();
+        run_bounded(concurrency, lookups).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{parse_branch_info, parse_events, parse_pdu_info, parse_receptacle_info};
+    use std::time::UNIX_EPOCH;
+
+    /// Snapshot built from the test data pages, parsed once since that
+    /// takes seconds in debug builds
+    pub(crate) fn snapshot() -> Snapshot {
+        static SNAPSHOT: std::sync::OnceLock<Snapshot> = std::sync::OnceLock::new();
+        SNAPSHOT.get_or_init(parse_snapshot).clone()
+    }
+
+    fn parse_snapshot() -> Snapshot {
+        let info = parse_pdu_info(include_str!("../testdata/pdu-info.htm").to_string()).unwrap();
+        let branch = parse_branch_info(include_str!("../testdata/branch-info.htm").to_string()).unwrap();
+        let receptacle = parse_receptacle_info(include_str!("../testdata/receptacle-info.htm").to_string()).unwrap();
+        let events = parse_events(include_str!("../testdata/events-test.htm").to_string()).unwrap();
+
+        Snapshot {
+            pdu: 1,
+            info,
+            branches: vec![BranchSnapshot { branch: 1, info: branch, taken_at: UNIX_EPOCH }],
+            receptacles: vec![
+                ReceptacleSnapshot { branch: 1, receptacle: 1, info: receptacle.clone(), taken_at: UNIX_EPOCH },
+                ReceptacleSnapshot { branch: 1, receptacle: 2, info: receptacle, taken_at: UNIX_EPOCH },
+            ],
+            events,
+            taken_at: UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_overall_health() {
+        use crate::EventType;
+
+        let mut snapshot = snapshot();
+        snapshot.events.clear();
+        assert_eq!(snapshot.overall_health().level, EventLevel::OK);
+
+        let event = |level, branch, receptacle, event| Event { level, pdu: 1, branch, receptacle, event };
+        snapshot.events = vec![
+            event(EventLevel::WARNING, 1, 2, EventType::ReceptacleOverCurrent),
+            event(EventLevel::ALARM, 1, 0, EventType::BranchLowVoltage),
+            event(EventLevel::WARNING, 1, 1, EventType::ReceptacleLowCurrent),
+        ];
+        let health = snapshot.overall_health();
+        assert_eq!(health.level, EventLevel::ALARM);
+        assert_eq!((health.ok, health.info, health.warning, health.alarm), (0, 0, 2, 1));
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_get_snapshot() {
+        use crate::transport::{Request, Response};
+
+        struct Device {
+            events_status: u16,
+        }
+
+        impl HttpTransport for Device {
+            async fn send(&self, request: Request) -> Result<Response, MPXError> {
+                let body = match request.url.as_str() {
+                    url if url.contains("rpcAps.htm") => include_str!("../testdata/pdu-info.htm").to_string(),
+                    url if url.contains("rpcRem.htm") => include_str!("../testdata/branch-info.htm").to_string(),
+                    url if url.contains("rpcReceptacle.htm") => include_str!("../testdata/receptacle-info.htm").to_string(),
+                    url if url.contains("rpcActiveAlarms.htm") => include_str!("../testdata/events-test.htm").to_string(),
+                    _ => format!("<table id=\"rcpTable\">{}</table>", ["1-1-1", "1-1-2", "2-1-1"].iter().map(|id| format!(concat!(
+                        "<tr id=\"{}\"><td><a href=\"#\"><nobr>r</nobr></a></td><td></td>",
+                        "<td><span title=\"On\"></span></td><td><span title=\"Unlocked\"></span></td>",
+                        "<td><img src=\"../../../images/accept.png\"></td></tr>"), id)).collect::<String>()),
+                };
+                let status = if request.url.contains("rpcActiveAlarms.htm") { self.events_status } else { 200 };
+                Ok(Response { status, url: request.url, headers: Vec::new(), body })
+            }
+        }
+
+        let mpx = MPX::builder("pdu1").build_with_transport(Device { events_status: 200 });
+        let fetched = mpx.get_snapshot(1).await.unwrap();
+        assert_eq!(fetched.info, snapshot().info);
+        assert_eq!(fetched.branches.len(), 1);
+        assert_eq!(fetched.receptacles.iter().map(|r| r.receptacle).collect::<Vec<u8>>(), vec![1, 2]);
+        assert!(fetched.events.iter().all(|e| e.pdu == 1));
+        assert!(fetched.receptacles.iter().all(|r| r.taken_at >= fetched.taken_at));
+        assert!(fetched.age() < Duration::from_secs(60));
+
+        let receptacles = mpx.get_info_branch_receptacles_with((2, 1), 1).await.unwrap();
+        assert_eq!(receptacles.len(), 1);
+        assert_eq!(receptacles[0].0.to_string(), "2-1-1");
+        assert_eq!(mpx.get_info_all_receptacles(1).await.unwrap().len(), 2);
+
+        // a failing part fails the snapshot instead of panicking
+        let mpx = MPX::builder("pdu1").build_with_transport(Device { events_status: 403 });
+        assert!(matches!(mpx.get_snapshot(1).await, Err(MPXError::Authentication(_))));
+    }
+
+    #[test]
+    fn test_snapshot_lookup() {
+        let snapshot = snapshot();
+        assert!(snapshot.branch(1).is_some());
+        assert!(snapshot.branch(2).is_none());
+        assert!(snapshot.receptacle(1, 2).is_some());
+        assert_eq!(snapshot.branch_receptacles(1).count(), 2);
+    }
+}
@@ -0,0 +1,182 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Long running services under systemd
+//!
+//! Implements the `sd_notify` protocol (readiness, reload and stop
+//! notifications, watchdog pings) without linking libsystemd, and a
+//! [`Service`] driver which turns poll ticks, `SIGHUP` and
+//! `SIGTERM`/`SIGINT` into [`ServiceEvent`]s. Watchdog pings are sent
+//! automatically on every tick, so a stuck poll loop gets restarted.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::systemd::{Service, ServiceEvent};
+//! use std::time::Duration;
+//!
+//! fn main() {
+//!     async {
+//!         let mut service = Service::new(Duration::from_secs(30)).unwrap();
+//!         service.ready().unwrap();
+//!         loop {
+//!             match service.next().await {
+//!                 ServiceEvent::Tick => { /* poll PDUs */ },
+//!                 ServiceEvent::Reload => { /* re-read config */ service.reloaded().unwrap(); },
+//!                 ServiceEvent::Shutdown => { /* flush sinks */ break; },
+//!             }
+//!         }
+//!     };
+//! }
+//! ```
+//!
+//! The unit should use `Type=notify` and may set `WatchdogSec=` to a
+//! value larger than the poll interval.
+
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::time::Duration;
+use tokio::signal::unix::{signal, Signal, SignalKind};
+
+/// Send a raw state string (e.g. `"READY=1"`) to the service manager
+///
+/// Returns `false` if the process is not running under a service
+/// manager supporting notifications (no `NOTIFY_SOCKET`).
+pub fn notify(state: &str) -> io::Result<bool> {
+    let path = match std::env::var_os("NOTIFY_SOCKET") {
+        Some(path) => path,
+        None => return Ok(false),
+    };
+    let path = path.to_string_lossy();
+    let socket = UnixDatagram::unbound()?;
+
+    if let Some(name) = path.strip_prefix('@') {
+        send_abstract(&socket, name, state)?;
+    } else {
+        socket.send_to(state.as_bytes(), path.as_ref())?;
+    }
+
+    Ok(true)
+}
+
+#[cfg(target_os = "linux")]
+fn send_abstract(socket: &UnixDatagram, name: &str, state: &str) -> io::Result<()> {
+    use std::os::linux::net::SocketAddrExt;
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+    socket.send_to_addr(state.as_bytes(), &addr)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn send_abstract(_socket: &UnixDatagram, _name: &str, _state: &str) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "abstract sockets are only supported on Linux"))
+}
+
+/// Watchdog timeout requested by the service manager
+pub fn watchdog_timeout() -> Option<Duration> {
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    if let Some(pid) = std::env::var("WATCHDOG_PID").ok().and_then(|p| p.parse::<u32>().ok()) {
+        if pid != std::process::id() {
+            return None;
+        }
+    }
+    Some(Duration::from_micros(usec))
+}
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+/// Reason for waking up the service loop
+pub enum ServiceEvent {
+    /// Poll interval elapsed
+    Tick,
+    /// `SIGHUP` received, configuration should be re-read
+    Reload,
+    /// `SIGTERM` or `SIGINT` received, flush and exit
+    Shutdown,
+}
+
+/// Service loop driver, see the module documentation
+pub struct Service {
+    interval: tokio::time::Interval,
+    hangup: Signal,
+    terminate: Signal,
+    interrupt: Signal,
+    watchdog: bool,
+}
+
+impl Service {
+    /// Create the driver, ticking every `poll_interval`
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn new(poll_interval: Duration) -> io::Result<Self> {
+        let mut interval = tokio::time::interval(poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        Ok(Service {
+            interval,
+            hangup: signal(SignalKind::hangup())?,
+            terminate: signal(SignalKind::terminate())?,
+            interrupt: signal(SignalKind::interrupt())?,
+            watchdog: watchdog_timeout().is_some(),
+        })
+    }
+
+    /// Tell the service manager that startup finished
+    pub fn ready(&self) -> io::Result<bool> {
+        notify("READY=1")
+    }
+
+    /// Tell the service manager that a reload finished
+    pub fn reloaded(&self) -> io::Result<bool> {
+        notify("READY=1")
+    }
+
+    /// Publish a free form status line (shown by `systemctl status`)
+    pub fn status(&self, status: &str) -> io::Result<bool> {
+        notify(&format!("STATUS={}", status.replace('\n', " ")))
+    }
+
+    /// Wait for the next event
+    ///
+    /// The reload and stop notifications are sent to the service
+    /// manager before the corresponding event is returned.
+    pub async fn next(&mut self) -> ServiceEvent {
+        let event = tokio::select! {
+            _ = self.interval.tick() => ServiceEvent::Tick,
+            _ = self.hangup.recv() => ServiceEvent::Reload,
+            _ = self.terminate.recv() => ServiceEvent::Shutdown,
+            _ = self.interrupt.recv() => ServiceEvent::Shutdown,
+        };
+
+        // notifications are best effort, the service keeps running without
+        let _ = match event {
+            ServiceEvent::Tick if self.watchdog => notify("WATCHDOG=1"),
+            ServiceEvent::Tick => Ok(false),
+            ServiceEvent::Reload => notify("RELOADING=1"),
+            ServiceEvent::Shutdown => notify("STOPPING=1"),
+        };
+
+        event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notify() {
+        let dir = std::env::temp_dir().join(format!("liebert-mpx-notify-{}", std::process::id()));
+        let _ = std::fs::remove_file(&dir);
+        let listener = UnixDatagram::bind(&dir).unwrap();
+
+        std::env::set_var("NOTIFY_SOCKET", &dir);
+        assert!(notify("READY=1").unwrap());
+        std::env::remove_var("NOTIFY_SOCKET");
+        assert!(!notify("READY=1").unwrap());
+
+        let mut buf = [0u8; 64];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1");
+        std::fs::remove_file(&dir).unwrap();
+    }
+}
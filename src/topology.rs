@@ -0,0 +1,265 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Device tree of a management card
+//!
+//! [`MPX::discover_topology`] combines the receptacle list with the PDU,
+//! branch and receptacle information pages into a single tree. The
+//! information pages are fetched with a bounded number of concurrent
+//! requests ([`DISCOVERY_CONCURRENCY`] by default).
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         let topology = pdu.discover_topology().await.unwrap();
+//!         for address in topology.receptacle_addresses() {
+//!             println!("{}", address);
+//!         }
+//!     };
+//! }
+//! ```
+
+use crate::capabilities::ModuleType;
+use crate::{BRMModel, Capability, EventLevel, PEMModel, ReceptacleAddress};
+#[cfg(feature = "client")]
+use crate::{MPX, MPXError};
+#[cfg(feature = "client")]
+use crate::transport::HttpTransport;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "client")]
+use std::collections::HashMap;
+#[cfg(feature = "client")]
+use std::future::Future;
+
+/// Default number of concurrent requests of [`MPX::discover_topology`]
+pub const DISCOVERY_CONCURRENCY: usize = 4;
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Receptacle of a branch module
+pub struct ReceptacleNode {
+    /// Receptacle number (usually 1-6)
+    pub receptacle: u8,
+    /// Receptacle user label
+    pub label: String,
+    /// Receptacle state (on or off)
+    pub enabled: bool,
+    /// Receptacle lock state (locked or unlocked)
+    pub locked: bool,
+    /// Receptacle health status
+    pub status: EventLevel,
+    /// Measurement and control capabilities
+    pub capability: Capability,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Branch module of a PDU
+pub struct BranchNode {
+    /// Branch number (usually 1-6)
+    pub branch: u8,
+    /// Branch user label
+    pub label: String,
+    /// Branch health status
+    pub status: EventLevel,
+    /// Branch receptacle module model
+    pub model: BRMModel,
+    /// Monitoring level of the module, `None` for unknown models
+    pub module_type: Option<ModuleType>,
+    pub receptacles: Vec<ReceptacleNode>,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// PDU connected to the management card
+pub struct PDUNode {
+    /// PDU number (usually 1)
+    pub pdu: u8,
+    /// PDU user label
+    pub label: String,
+    /// PDU health status
+    pub status: EventLevel,
+    /// Power entry module model
+    pub model: PEMModel,
+    pub branches: Vec<BranchNode>,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// PDUs, branch modules and receptacles of a management card
+pub struct Topology {
+    pub pdus: Vec<PDUNode>,
+}
+
+impl Topology {
+    /// PDU by number
+    pub fn pdu(&self, pdu: u8) -> Option<&PDUNode> {
+        self.pdus.iter().find(|p| p.pdu == pdu)
+    }
+
+    /// Branch module by address
+    pub fn branch(&self, pdu: u8, branch: u8) -> Option<&BranchNode> {
+        self.pdu(pdu)?.branches.iter().find(|b| b.branch == branch)
+    }
+
+    /// Addresses of all receptacles, ordered by PDU, branch and receptacle
+    pub fn receptacle_addresses(&self) -> Vec<ReceptacleAddress> {
+        self.pdus.iter()
+            .flat_map(|p| p.branches.iter().map(move |b| (p.pdu, b)))
+            .flat_map(|(pdu, b)| b.receptacles.iter().map(move |r| ReceptacleAddress::new(pdu, b.branch, r.receptacle)))
+            .collect()
+    }
+}
+
+/// Run `tasks` with at most `concurrency` of them at a time and return
+/// their results in order, failing with the first error
+#[cfg(feature = "client")]
+pub(crate) async fn run_bounded<T, F>(concurrency: usize, tasks: Vec<F>) -> Result<Vec<T>, MPXError>
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T, MPXError>> + Send + 'static,
+{
+    let mut results: Vec<Option<T>> = tasks.iter().map(|_| None).collect();
+    let mut running = tokio::task::JoinSet::new();
+    for (index, task) in tasks.into_iter().enumerate() {
+        if running.len() >= concurrency.max(1) {
+            let (index, result) = running.join_next().await.unwrap().unwrap();
+            results[index] = Some(result?);
+        }
+        running.spawn(async move { (index, task.await) });
+    }
+    while let Some(joined) = running.join_next().await {
+        let (index, result) = joined.unwrap();
+        results[index] = Some(result?);
+    }
+    Ok(results.into_iter().map(Option::unwrap).collect())
+}
+
+#[cfg(feature = "client")]
+impl<H: HttpTransport + 'static> MPX<H> {
+    /// Discover the device tree with [`DISCOVERY_CONCURRENCY`] concurrent
+    /// requests
+    pub async fn discover_topology(&self) -> Result<Topology, MPXError> {
+        self.discover_topology_with(DISCOVERY_CONCURRENCY).await
+    }
+
+    /// Discover the device tree with at most `concurrency` concurrent
+    /// requests for the information pages
+    pub async fn discover_topology_with(&self, concurrency: usize) -> Result<Topology, MPXError> {
+        let receptacles = self.get_receptacles().await?;
+        let mut branches: Vec<(u8, u8)> = receptacles.iter().map(|r| (r.pdu, r.branch)).collect();
+        branches.sort_unstable();
+        branches.dedup();
+        let mut pdus: Vec<u8> = branches.iter().map(|(pdu, _)| *pdu).collect();
+        pdus.dedup();
+
+        let lookups = pdus.into_iter().map(|pdu| {
+            let mpx = self.clone();
+            async move { Ok((pdu, mpx.get_info_pdu(pdu).await?)) }
+        }).collect();
+        let pdu_infos = run_bounded(concurrency, lookups).await?;
+
+        let lookups = branches.iter().map(|&address| {
+            let mpx = self.clone();
+            async move { Ok((address, mpx.get_info_branch(address).await?)) }
+        }).collect();
+        let mut branch_infos: HashMap<_, _> = run_bounded(concurrency, lookups).await?.into_iter().collect();
+
+        let lookups = receptacles.iter().map(|entry| {
+            let mpx = self.clone();
+            let address = (entry.pdu, entry.branch, entry.receptacle);
+            async move {
+                let info = mpx.get_info_receptacle(address).await?;
+                Ok((address, info.hardware.capabilities))
+            }
+        }).collect();
+        let mut capabilities: HashMap<_, _> = run_bounded(concurrency, lookups).await?.into_iter().collect();
+
+        let pdus = pdu_infos.into_iter().map(|(pdu, info)| PDUNode {
+            branches: branches.iter().filter(|(p, _)| *p == pdu).map(|&(_, branch)| {
+                let info = branch_infos.remove(&(pdu, branch)).unwrap();
+                BranchNode {
+                    branch,
+                    label: info.settings.label,
+                    status: info.events.level(),
+                    module_type: info.hardware.brm_model.module_type(),
+                    model: info.hardware.brm_model,
+                    receptacles: receptacles.iter().filter(|r| r.pdu == pdu && r.branch == branch).map(|r| ReceptacleNode {
+                        receptacle: r.receptacle,
+                        label: r.label.clone(),
+                        enabled: r.enabled,
+                        locked: r.locked,
+                        status: r.status,
+                        capability: capabilities.remove(&(r.pdu, r.branch, r.receptacle)).unwrap(),
+                    }).collect(),
+                }
+            }).collect(),
+            pdu,
+            label: info.settings.label,
+            status: info.events.level(),
+            model: info.hardware.pem_model,
+        }).collect();
+
+        Ok(Topology { pdus })
+    }
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use super::*;
+    use crate::transport::{Request, Response};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Device {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    fn receptacle_row(branch: u8, receptacle: u8) -> String {
+        format!(concat!(
+            "<tr id=\"1-{}-{}\"><td><a href=\"#\"><nobr>r{}</nobr></a></td><td></td>",
+            "<td><span title=\"On\"></span></td><td><span title=\"Unlocked\"></span></td>",
+            "<td><img src=\"../../../images/accept.png\"></td></tr>"), branch, receptacle, receptacle)
+    }
+
+    impl HttpTransport for Device {
+        async fn send(&self, request: Request) -> Result<Response, MPXError> {
+            if request.url.contains("rpcReceptacleListData") {
+                let rows: String = [(1, 1), (1, 2), (1, 3), (2, 1)].iter().map(|&(b, r)| receptacle_row(b, r)).collect();
+                return Ok(Response { status: 200, url: request.url, headers: Vec::new(), body: format!("<table id=\"rcpTable\">{}</table>", rows) });
+            }
+
+            let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            let body = match request.url.as_str() {
+                url if url.contains("rpcAps.htm") => include_str!("../testdata/pdu-info.htm"),
+                url if url.contains("rpcRem.htm") => include_str!("../testdata/branch-info.htm"),
+                _ => include_str!("../testdata/receptacle-info.htm"),
+            };
+            Ok(Response { status: 200, url: request.url, headers: Vec::new(), body: body.to_string() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_discover_topology() {
+        let device = Device { in_flight: AtomicUsize::new(0), max_in_flight: AtomicUsize::new(0) };
+        let mpx = MPX::builder("pdu1").build_with_transport(device);
+        let topology = mpx.discover_topology_with(2).await.unwrap();
+
+        assert_eq!(topology.pdus.len(), 1);
+        assert_eq!(topology.pdu(1).unwrap().branches.len(), 2);
+        assert_eq!(topology.branch(1, 1).unwrap().model, BRMModel::ERBC6N3N);
+        assert_eq!(topology.branch(1, 1).unwrap().module_type, Some(ModuleType::ReceptacleManaged));
+        assert_eq!(topology.branch(1, 2).unwrap().receptacles.len(), 1);
+        assert_eq!(topology.receptacle_addresses().len(), 4);
+        assert_eq!(topology.receptacle_addresses()[3].to_string(), "1-2-1");
+        assert!(mpx.inner.transport.max_in_flight.load(Ordering::SeqCst) <= 2);
+    }
+}
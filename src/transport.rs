@@ -0,0 +1,255 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! HTTP transport
+//!
+//! All requests of a [`MPX`](crate::MPX) are sent through a
+//! [`HttpTransport`]. By default this is [`ReqwestTransport`]; another
+//! HTTP client, a tunnel or a recording transport for tests can be used
+//! with [`MPXBuilder::build_with_transport`](crate::MPXBuilder::build_with_transport).
+//! Authentication, sessions, retries, deadlines and rate limits are
+//! handled on top of the transport and work the same for all of them.
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::MPXError;
+//! use liebert::transport::{HttpTransport, Request, Response};
+//!
+//! struct Offline;
+//!
+//! impl HttpTransport for Offline {
+//!     async fn send(&self, request: Request) -> Result<Response, MPXError> {
+//!         let body = std::fs::read_to_string("receptacle-list.htm").unwrap_or_default();
+//!         Ok(Response { status: 200, url: request.url, headers: Vec::new(), body })
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::builder("pdu1").build_with_transport(Offline);
+//!     async {
+//!         let receptacles = pdu.get_receptacles().await.unwrap();
+//!     };
+//! }
+//! ```
+
+use crate::{MPXError, ResponseTooLargeError};
+use std::future::Future;
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+/// HTTP request method
+pub enum Method {
+    Get,
+    /// POST with a `application/x-www-form-urlencoded` body
+    Post,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// HTTP request
+pub struct Request {
+    pub method: Method,
+    /// Absolute URL
+    pub url: String,
+    /// Headers in addition to the transport defaults
+    pub headers: Vec<(String, String)>,
+    /// Form fields of a POST request
+    pub form: Vec<(String, String)>,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+/// HTTP response with the complete body
+pub struct Response {
+    pub status: u16,
+    /// Final URL, after following redirects
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Path and query of an absolute URL
+fn path_and_query(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let rest = rest.split('#').next().unwrap_or(rest);
+    match rest.find('/') {
+        Some(pos) => &rest[pos..],
+        None => "/",
+    }
+}
+
+impl Request {
+    pub fn get(url: &str) -> Self {
+        Request {
+            method: Method::Get,
+            url: url.to_string(),
+            headers: Vec::new(),
+            form: Vec::new(),
+        }
+    }
+
+    pub fn post(url: &str, form: &[(&str, &str)]) -> Self {
+        Request {
+            method: Method::Post,
+            url: url.to_string(),
+            headers: Vec::new(),
+            form: form.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    /// Add a header
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Path and query of the URL, e.g. `/rpc/rpcActiveAlarms.htm`
+    pub fn path_and_query(&self) -> &str {
+        path_and_query(&self.url)
+    }
+}
+
+impl Response {
+    /// Values of all headers called `name` (case insensitive)
+    pub fn header<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.headers.iter()
+            .filter(move |(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Path of the final URL
+    pub fn path(&self) -> &str {
+        let path = path_and_query(&self.url);
+        path.split('?').next().unwrap_or(path)
+    }
+}
+
+/// Sends HTTP requests on behalf of a [`MPX`](crate::MPX)
+pub trait HttpTransport: Send + Sync {
+    /// Send a request and read the complete response; error responses
+    /// (4xx, 5xx) are no errors on this level
+    ///
+    /// A configured maximum response size is checked after the fact,
+    /// transports should enforce their own limit while reading.
+    fn send(&self, request: Request) -> impl Future<Output = Result<Response, MPXError>> + Send;
+}
+
+#[derive(Clone,Debug,Default)]
+/// Default transport based on a (shared) [`reqwest::Client`]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    max_response_size: Option<usize>,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestTransport { client, max_response_size: None }
+    }
+
+    /// Stop reading responses with bodies larger than `bytes`
+    pub fn max_response_size(mut self, bytes: usize) -> Self {
+        self.max_response_size = Some(bytes);
+        self
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    async fn send(&self, request: Request) -> Result<Response, MPXError> {
+        let mut builder = match request.method {
+            Method::Get => self.client.get(&request.url),
+            Method::Post => self.client.post(&request.url).form(&request.form),
+        };
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        let mut response = builder.send().await?;
+        let limit = self.max_response_size.unwrap_or(usize::MAX);
+        if response.content_length().unwrap_or(0) > limit as u64 {
+            return Err(ResponseTooLargeError { limit }.into());
+        }
+
+        let status = response.status().as_u16();
+        let url = response.url().to_string();
+        let headers = response.headers().iter()
+            .filter_map(|(k, v)| Some((k.as_str().to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+
+        // read in chunks, the announced length may be missing or wrong
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > limit {
+                return Err(ResponseTooLargeError { limit }.into());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let body = String::from_utf8_lossy(&body).into_owned();
+
+        Ok(Response { status, url, headers, body })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MPX;
+    use std::sync::Mutex;
+
+    /// Answers with a fixed page and records all requests
+    struct Recording {
+        page: &'static str,
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl HttpTransport for Recording {
+        async fn send(&self, request: Request) -> Result<Response, MPXError> {
+            let url = request.url.clone();
+            self.requests.lock().unwrap().push(request);
+            Ok(Response { status: 200, url, headers: Vec::new(), body: self.page.to_string() })
+        }
+    }
+
+    #[test]
+    fn test_path_and_query() {
+        assert_eq!(Request::get("http://pdu1/rpc/rpcActiveAlarms.htm?x=1").path_and_query(), "/rpc/rpcActiveAlarms.htm?x=1");
+        assert_eq!(Request::get("http://[2001:db8::42]:8080").path_and_query(), "/");
+        let response = Response { status: 200, url: "https://pdu1/login.htm?next=%2F".to_string(), headers: vec![("Set-Cookie".to_string(), "SID=1".to_string())], body: String::new() };
+        assert_eq!(response.path(), "/login.htm");
+        assert_eq!(response.header("set-cookie").collect::<Vec<_>>(), vec!["SID=1"]);
+    }
+
+    #[tokio::test]
+    async fn test_max_response_size() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let _ = socket.read(&mut request).await.unwrap();
+            // no Content-Length, the body ends with the connection
+            socket.write_all(b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n").await.unwrap();
+            socket.write_all(&[b'x'; 4096]).await.unwrap();
+        });
+
+        let mpx = MPX::builder("127.0.0.1").port(port).max_response_size(1024).build().unwrap();
+        let result = mpx.get_events().await;
+        assert!(matches!(result, Err(MPXError::ResponseTooLarge(e)) if e.limit == 1024));
+    }
+
+    #[tokio::test]
+    async fn test_custom_transport() {
+        let transport = Recording {
+            page: include_str!("../testdata/events-test.htm"),
+            requests: Mutex::new(Vec::new()),
+        };
+        let mpx = MPX::builder("pdu1").credentials("Liebert", "secret").build_with_transport(transport);
+
+        assert!(mpx.get_events().await.is_ok());
+
+        let requests = mpx.inner.transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::Get);
+        assert!(requests[0].url.starts_with("http://pdu1/"));
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "Basic TGllYmVydDpzZWNyZXQ=".to_string())));
+    }
+}
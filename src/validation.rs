@@ -0,0 +1,147 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Validation of settings before they are written
+//!
+//! The card rejects out of range thresholds without a useful error page.
+//! [`MPX::set_pdu_settings`] and friends therefore validate the settings
+//! first and fail with
+//! [`MPXError::InvalidSetting`](crate::MPXError::InvalidSetting) without
+//! sending a request. The checks can also be run on their own:
+//!
+//! ```
+//! extern crate liebert_mpx as liebert;
+//! use std::time::Duration;
+//!
+//! let settings = liebert::ReceptacleSettings {
+//!     label: "db-01".to_string(),
+//!     asset_tag_1: String::new(),
+//!     asset_tag_2: String::new(),
+//!     over_current_alarm_threshold: 250,
+//!     over_current_warning_threshold: 80,
+//!     low_current_alarm_threshold: 0,
+//!     power_state: true,
+//!     power_control: true,
+//!     control_lock_state: false,
+//!     power_on_delay: Duration::from_secs(5),
+//! };
+//! assert_eq!(settings.validate().unwrap_err().to_string(),
+//!     "invalid setting over_current_alarm_threshold: 250 % out of range 0-100 %");
+//! ```
+//!
+//! [`MPX::set_pdu_settings`]: crate::MPX::set_pdu_settings
+
+use crate::{BranchSettings, InvalidSettingError, PDUSettings, ReceptacleSettings};
+
+/// Maximum current threshold in %
+pub const MAX_THRESHOLD: u32 = 100;
+
+fn invalid(field: &str, reason: String) -> InvalidSettingError {
+    InvalidSettingError { field: field.to_string(), reason }
+}
+
+fn check_threshold(field: &str, value: Option<u32>) -> Result<(), InvalidSettingError> {
+    match value {
+        Some(value) if value > MAX_THRESHOLD => Err(invalid(field, format!("{} % out of range 0-{} %", value, MAX_THRESHOLD))),
+        _ => Ok(()),
+    }
+}
+
+impl PDUSettings {
+    /// Check the thresholds against the limits of the card
+    pub fn validate(&self) -> Result<(), InvalidSettingError> {
+        for (field, value) in [
+            ("n_over_current_alarm_threshold", Some(self.n_over_current_alarm_threshold)),
+            ("n_over_current_warning_threshold", Some(self.n_over_current_warning_threshold)),
+            ("l1_low_current_alarm_threshold", Some(self.l1_low_current_alarm_threshold)),
+            ("l1_over_current_alarm_threshold", Some(self.l1_over_current_alarm_threshold)),
+            ("l1_over_current_warning_threshold", Some(self.l1_over_current_warning_threshold)),
+            ("l2_low_current_alarm_threshold", self.l2_low_current_alarm_threshold),
+            ("l2_over_current_alarm_threshold", self.l2_over_current_alarm_threshold),
+            ("l2_over_current_warning_threshold", self.l2_over_current_warning_threshold),
+            ("l3_low_current_alarm_threshold", self.l3_low_current_alarm_threshold),
+            ("l3_over_current_alarm_threshold", self.l3_over_current_alarm_threshold),
+            ("l3_over_current_warning_threshold", self.l3_over_current_warning_threshold),
+        ] {
+            check_threshold(field, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl BranchSettings {
+    /// Check the thresholds against the limits of the card
+    pub fn validate(&self) -> Result<(), InvalidSettingError> {
+        check_threshold("over_current_alarm_threshold", Some(self.over_current_alarm_threshold))?;
+        check_threshold("over_current_warning_threshold", Some(self.over_current_warning_threshold))?;
+        check_threshold("low_current_alarm_threshold", Some(self.low_current_alarm_threshold))
+    }
+}
+
+impl ReceptacleSettings {
+    /// Check the thresholds against the limits of the card and that the
+    /// power on delay can be written in seconds
+    pub fn validate(&self) -> Result<(), InvalidSettingError> {
+        check_threshold("over_current_alarm_threshold", Some(self.over_current_alarm_threshold))?;
+        check_threshold("over_current_warning_threshold", Some(self.over_current_warning_threshold))?;
+        check_threshold("low_current_alarm_threshold", Some(self.low_current_alarm_threshold))?;
+        if self.power_on_delay.subsec_nanos() != 0 {
+            let reason = format!("{:?} is not a whole number of seconds", self.power_on_delay);
+            return Err(invalid("power_on_delay", reason));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_branch_info, parse_pdu_info, parse_receptacle_info};
+    use std::time::Duration;
+
+    #[test]
+    fn test_validate_settings() {
+        let pdu = parse_pdu_info(include_str!("../testdata/pdu-info.htm").to_string()).unwrap().settings;
+        assert!(pdu.validate().is_ok());
+        let e = PDUSettings { l2_over_current_alarm_threshold: Some(250), ..pdu.clone() }.validate().unwrap_err();
+        assert_eq!(e.field, "l2_over_current_alarm_threshold");
+        assert!(PDUSettings { label: "x".repeat(60), ..pdu }.validate().is_ok());
+
+        let branch = parse_branch_info(include_str!("../testdata/branch-info.htm").to_string()).unwrap().settings;
+        assert!(branch.validate().is_ok());
+        assert!(BranchSettings { over_current_warning_threshold: 101, ..branch }.validate().is_err());
+
+        let receptacle = parse_receptacle_info(include_str!("../testdata/receptacle-info.htm").to_string()).unwrap().settings;
+        assert!(receptacle.validate().is_ok());
+        assert!(ReceptacleSettings { power_on_delay: Duration::from_secs(3600), ..receptacle.clone() }.validate().is_ok());
+        let e = ReceptacleSettings { power_on_delay: Duration::from_millis(1500), ..receptacle.clone() }.validate().unwrap_err();
+        assert_eq!(e.to_string(), "invalid setting power_on_delay: 1.5s is not a whole number of seconds");
+
+        #[cfg(all(feature = "serde", feature = "serde_json"))]
+        {
+            let json = serde_json::to_value(ReceptacleSettings { power_on_delay: Duration::from_secs(5), ..receptacle }).unwrap();
+            assert_eq!(json["power_on_delay"], 5);
+        }
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_no_request_for_invalid_settings() {
+        use crate::{ErrorKind, MPX, MPXError};
+        use crate::transport::{HttpTransport, Request, Response};
+
+        struct Unreachable;
+
+        impl HttpTransport for Unreachable {
+            async fn send(&self, _request: Request) -> Result<Response, MPXError> {
+                panic!("no request expected");
+            }
+        }
+
+        let mpx = MPX::builder("pdu1").build_with_transport(Unreachable);
+        let settings = parse_branch_info(include_str!("../testdata/branch-info.htm").to_string()).unwrap().settings;
+        let settings = BranchSettings { low_current_alarm_threshold: 101, ..settings };
+        assert_eq!(mpx.set_branch_settings((1, 2), &settings).await.unwrap_err().kind(), ErrorKind::InvalidSetting);
+    }
+}
@@ -0,0 +1,118 @@
+// Liebert MPX PDU Rust API
+// © 2021 Sebastian Reichel
+// SPDX-License-Identifier: ISC
+
+//! Multi-step operational workflows
+//!
+//! ```no_run
+//! extern crate liebert_mpx as liebert;
+//! use liebert::workflow::PowerCycleOptions;
+//!
+//! fn main() {
+//!     let pdu = liebert::MPX::new("192.168.23.42", "Liebert", "Liebert");
+//!     async {
+//!         let ready = || async {
+//!             tokio::net::TcpStream::connect("db-01:22").await.is_ok()
+//!         };
+//!         let took = pdu.power_cycle_and_wait((1, 2, 3), &PowerCycleOptions::default(), ready).await.unwrap();
+//!         println!("db-01 back after {:?}", took);
+//!     };
+//! }
+//! ```
+
+use crate::{MPX, MPXError, ReadinessTimeoutError, ReceptacleAddress};
+use crate::transport::HttpTransport;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+/// Timing of [`MPX::power_cycle_and_wait`]
+pub struct PowerCycleOptions {
+    /// Delay after the power cycle before the first readiness check,
+    /// so the attached device is not reported ready before it went down
+    pub initial_delay: Duration,
+    /// Delay between readiness checks
+    pub poll_interval: Duration,
+    /// Maximum time to wait for readiness after the power cycle
+    pub timeout: Duration,
+}
+
+impl Default for PowerCycleOptions {
+    fn default() -> Self {
+        PowerCycleOptions {
+            initial_delay: Duration::from_secs(10),
+            poll_interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Poll `readiness_check` until it succeeds or the timeout expires,
+/// returning the time since `start`
+async fn wait_ready<F, Fut>(start: Instant, options: &PowerCycleOptions, mut readiness_check: F) -> Result<Duration, MPXError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    tokio::time::sleep(options.initial_delay).await;
+
+    loop {
+        if readiness_check().await {
+            return Ok(start.elapsed());
+        }
+
+        if start.elapsed() + options.poll_interval > options.timeout {
+            return Err(ReadinessTimeoutError { waited: start.elapsed() }.into());
+        }
+
+        #[cfg(feature = "log")]
+        log::debug!(target: "liebert_mpx", waited:? = start.elapsed(), outcome = "retry";
+            "device not ready yet, checking again in {:?}", options.poll_interval);
+        tokio::time::sleep(options.poll_interval).await;
+    }
+}
+
+impl<H: HttpTransport> MPX<H> {
+    /// Power cycle a receptacle and wait until the attached device is ready
+    ///
+    /// `readiness_check` is called repeatedly after the power cycle (e.g.
+    /// a TCP connect to the attached host) until it returns `true`. The
+    /// time from the power cycle until readiness is returned; if the
+    /// device is not ready within `options.timeout` a
+    /// `MPXError::ReadinessTimeout` is returned.
+    pub async fn power_cycle_and_wait<F, Fut>(&self, address: impl Into<ReceptacleAddress>, options: &PowerCycleOptions, readiness_check: F) -> Result<Duration, MPXError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = bool>,
+    {
+        self.receptacle_reboot(address).await?;
+        wait_ready(Instant::now(), options, readiness_check).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[tokio::test]
+    async fn test_wait_ready() {
+        let options = PowerCycleOptions {
+            initial_delay: Duration::from_millis(1),
+            poll_interval: Duration::from_millis(1),
+            timeout: Duration::from_millis(200),
+        };
+
+        let calls = Cell::new(0);
+        let result = wait_ready(Instant::now(), &options, || {
+            calls.set(calls.get() + 1);
+            let ready = calls.get() == 3;
+            async move { ready }
+        }).await;
+        assert!(result.is_ok());
+        assert_eq!(calls.get(), 3);
+
+        let result = wait_ready(Instant::now(), &options, || async { false }).await;
+        assert!(matches!(result, Err(MPXError::ReadinessTimeout(_))));
+    }
+}